@@ -0,0 +1,155 @@
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+use ethers::utils::keccak256;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use starknet::core::types::FieldElement;
+
+use crate::{config::Addresses, utils::to_hex};
+
+// validation rules of the configured network, read by the request extractors
+static RULES: OnceLock<Addresses> = OnceLock::new();
+
+/// Sets the validation rules of the addresses read from requests. Only the first call
+/// has an effect, the default Starknet rules are used until then.
+pub fn configure(rules: &Addresses) {
+    let _ = RULES.set(rules.clone());
+}
+
+/// A Starknet contract address read from a request. It accepts decimal or hex in any
+/// padding, checks the checksum of mixed case hex and rejects values outside of the
+/// contract address range of the network. It always prints as the padded hex stored
+/// in Mongo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StarknetAddress(FieldElement);
+
+impl StarknetAddress {
+    pub fn felt(&self) -> FieldElement {
+        self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.0)
+    }
+
+    /// Forms the address can be stored in, sponsor clicks were stored in decimal
+    /// before the addresses were normalized to padded hex.
+    pub fn stored_forms(&self) -> Vec<String> {
+        vec![self.to_hex(), self.0.to_string()]
+    }
+
+    /// Parses an address with the given validation rules
+    pub fn parse_with(value: &str, rules: &Addresses) -> Result<Self, String> {
+        let value = value.trim();
+        let felt = match value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            Some(digits) => {
+                if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("{} is not an hexadecimal address", value));
+                }
+                let significant = digits.trim_start_matches('0');
+                if significant.len() > 64 {
+                    return Err(format!("{} is longer than a Starknet address", value));
+                }
+                // 20 bytes is the size of an Ethereum address, Starknet ones are 32 bytes
+                if digits.len() == 40 {
+                    return Err(format!(
+                        "{} is an Ethereum address, not a Starknet one",
+                        value
+                    ));
+                }
+                let felt = FieldElement::from_hex_be(if significant.is_empty() {
+                    "0"
+                } else {
+                    significant
+                })
+                .map_err(|_| format!("{} is above the Starknet field prime", value))?;
+                let has_lowercase = digits.chars().any(|c| c.is_ascii_lowercase());
+                let has_uppercase = digits.chars().any(|c| c.is_ascii_uppercase());
+                if rules.checksum
+                    && has_lowercase
+                    && has_uppercase
+                    && checksum(&felt) != significant
+                {
+                    return Err(format!("{} has an invalid checksum", value));
+                }
+                felt
+            }
+            None => FieldElement::from_dec_str(value)
+                .map_err(|_| format!("{} is not a valid address", value))?,
+        };
+
+        if felt >= rules.max_address {
+            return Err(format!(
+                "{} is outside of the Starknet contract address range",
+                value
+            ));
+        }
+        Ok(StarknetAddress(felt))
+    }
+}
+
+impl FromStr for StarknetAddress {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        StarknetAddress::parse_with(value, RULES.get_or_init(Addresses::default))
+    }
+}
+
+/// Mixed case encoding of an address without its leading zeros, the case of each
+/// hex digit is given by the keccak hash of the address (as done by starknet.js).
+pub fn checksum(felt: &FieldElement) -> String {
+    let bytes = felt.to_bytes_be();
+    let first_byte = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len() - 1);
+    let hash = keccak256(&bytes[first_byte..]);
+
+    let padded = to_hex(felt);
+    let padded = padded.trim_start_matches("0x");
+    let encoded: String = padded
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = if i % 2 == 0 {
+                hash[i / 2] >> 4
+            } else {
+                hash[i / 2] & 0x0f
+            };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    encoded.trim_start_matches('0').to_string()
+}
+
+impl fmt::Display for StarknetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for StarknetAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for StarknetAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
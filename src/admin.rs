@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderMap},
+};
+use ethers::utils::keccak256;
+
+use crate::{config::Admin, errors::ApiError, models::AppState};
+
+/// Extractor of admin endpoints, rejects requests without the admin api key in their
+/// `x-api-key` header. Admin endpoints are disabled when no key is configured.
+pub struct AdminKey;
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AdminKey {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        check_admin_key(&state.conf.admin, &parts.headers)?;
+        Ok(AdminKey)
+    }
+}
+
+pub fn check_admin_key(admin: &Admin, headers: &HeaderMap) -> Result<(), ApiError> {
+    let provided = headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok());
+    match provided {
+        Some(provided) if !admin.api_key.is_empty() && keys_match(&admin.api_key, provided) => {
+            Ok(())
+        }
+        _ => Err(ApiError::Unauthorized("Invalid api key".to_string())),
+    }
+}
+
+// Compares the hashes of the keys in constant time, so that neither the matching prefix
+// nor the length of the key leak through the response time
+fn keys_match(expected: &str, provided: &str) -> bool {
+    let (expected, provided) = (keccak256(expected), keccak256(provided));
+    expected
+        .iter()
+        .zip(provided.iter())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
@@ -0,0 +1,347 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    options::FindOneOptions,
+    IndexModel,
+};
+use serde::Serialize;
+use serde_json::Value;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    config::Cache,
+    models::{AppState, IdentityData},
+    utils::to_hex,
+};
+
+// indexed collections watched for changes, with the fields used as invalidation tags
+const WATCHED_COLLECTIONS: [(&str, &[&str]); 4] = [
+    (
+        "domains",
+        &["domain", "id", "legacy_address", "rev_address"],
+    ),
+    ("id_owners", &["id", "owner"]),
+    ("id_user_data", &["id", "data"]),
+    // subdomains of the custom resolvers, their domains are tagged by custom_resolution_tags
+    ("custom_resolutions", &["value"]),
+];
+
+struct CacheEntry {
+    value: Value,
+    tags: Vec<String>,
+    inserted_at: Instant,
+    ttl: Duration,
+}
+
+/// Caches endpoint responses keyed by endpoint and query. Each entry is tagged with
+/// the domains, ids and addresses it depends on so that it can be dropped as soon as
+/// the indexer writes a new version of one of them, see `invalidate_indexed_changes`.
+pub struct ResponseCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    // highest indexed block already checked for changes
+    last_block: Mutex<Option<i64>>,
+}
+
+impl ResponseCache {
+    pub fn new(settings: &Cache) -> Self {
+        ResponseCache {
+            ttl: Duration::from_secs_f64(settings.ttl),
+            max_entries: settings.max_entries,
+            entries: Mutex::new(HashMap::new()),
+            last_block: Mutex::new(None),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.inserted_at.elapsed() < entry.ttl => Some(entry.value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: String, value: &impl Serialize, tags: &[impl AsRef<str>]) {
+        self.insert_with_ttl(key, value, tags, self.ttl);
+    }
+
+    /// Inserts a value which must not be served after the `valid_until` timestamp, such
+    /// as an offchain resolution which is only valid as long as its hint
+    pub fn insert_until(
+        &self,
+        key: String,
+        value: &impl Serialize,
+        tags: &[impl AsRef<str>],
+        valid_until: Option<u64>,
+    ) {
+        let ttl = match valid_until {
+            Some(valid_until) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                Duration::from_secs(valid_until)
+                    .saturating_sub(now)
+                    .min(self.ttl)
+            }
+            None => self.ttl,
+        };
+        self.insert_with_ttl(key, value, tags, ttl);
+    }
+
+    fn insert_with_ttl(
+        &self,
+        key: String,
+        value: &impl Serialize,
+        tags: &[impl AsRef<str>],
+        ttl: Duration,
+    ) {
+        if self.max_entries == 0 || ttl.is_zero() {
+            return;
+        }
+        let value = match serde_json::to_value(value) {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, entry| entry.inserted_at.elapsed() < entry.ttl);
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.inserted_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                tags: tags.iter().map(|tag| normalize_tag(tag.as_ref())).collect(),
+                inserted_at: Instant::now(),
+                ttl,
+            },
+        );
+    }
+
+    /// Drops every entry tagged with one of the given domains, ids or addresses
+    pub fn invalidate(&self, tags: &HashSet<String>) {
+        if tags.is_empty() {
+            return;
+        }
+        let tags: HashSet<String> = tags.iter().map(|tag| normalize_tag(tag)).collect();
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, entry| !entry.tags.iter().any(|tag| tags.contains(tag)));
+    }
+}
+
+/// Tags of an identity response: its id, owner, domain and domain addresses
+pub fn identity_tags(data: &IdentityData) -> Vec<String> {
+    let mut tags = vec![to_hex(&data.id), to_hex(&data.owner)];
+    if let Some(domain) = &data.domain {
+        tags.push(domain.domain.clone());
+        tags.extend(domain.legacy_address.iter().map(to_hex));
+        tags.extend(domain.rev_address.iter().map(to_hex));
+    }
+    tags
+}
+
+/// Domains of a `custom_resolutions` row, its subdomain of every root domain handled
+/// by its resolver in the config `custom_resolvers`
+pub fn custom_resolution_tags(
+    row: &Document,
+    custom_resolvers: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    let (slice, resolver) = match (row.get_str("domain_slice"), row.get_str("resolver")) {
+        (Ok(slice), Ok(resolver)) => (slice, normalize_tag(resolver)),
+        _ => return Vec::new(),
+    };
+    custom_resolvers
+        .iter()
+        .filter(|(custom_resolver, _)| normalize_tag(custom_resolver) == resolver)
+        .flat_map(|(_, root_domains)| root_domains.iter())
+        .map(|root_domain| format!("{}{}", slice, root_domain))
+        .collect()
+}
+
+// addresses and ids are compared in their padded hex form
+fn normalize_tag(tag: &str) -> String {
+    match FieldElement::from_hex_be(tag) {
+        Ok(felt) if tag.starts_with("0x") => to_hex(&felt),
+        _ => tag.to_string(),
+    }
+}
+
+/// Indexes of the block bounds of the watched rows, the changes are looked up by them
+pub fn cursor_indexes() -> Vec<IndexModel> {
+    vec![
+        IndexModel::builder()
+            .keys(doc! { "_cursor.from": 1 })
+            .build(),
+        IndexModel::builder().keys(doc! { "_cursor.to": 1 }).build(),
+    ]
+}
+
+pub async fn create_cursor_indexes(state: &Arc<AppState>) -> mongodb::error::Result<()> {
+    for (collection_name, _) in WATCHED_COLLECTIONS {
+        state
+            .starknetid_db
+            .collection::<Document>(collection_name)
+            .create_indexes(cursor_indexes(), None)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Looks for rows written by the indexer since the last call and drops the cached
+/// responses depending on them. The first call only records the latest indexed block.
+pub async fn invalidate_indexed_changes(state: &Arc<AppState>) {
+    let last_block = *state.cache.last_block.lock().unwrap();
+    let last_block = match last_block {
+        Some(block) => block,
+        None => {
+            let latest = latest_indexed_block(state).await;
+            *state.cache.last_block.lock().unwrap() = latest;
+            return;
+        }
+    };
+
+    let mut newest_block = last_block;
+    let mut tags = HashSet::new();
+    let mut changed_ids = HashSet::new();
+    for (collection_name, fields) in WATCHED_COLLECTIONS {
+        let collection = state.starknetid_db.collection::<Document>(collection_name);
+        // closed rows are needed too, they hold the previous owner or address. The last
+        // block is checked again as the indexer may still have been writing it.
+        let filter = doc! { "$or": [
+            { "_cursor.from": { "$gte": last_block } },
+            { "_cursor.to": { "$gte": last_block } },
+        ] };
+        let rows = match collection.find(filter, None).await {
+            Ok(cursor) => cursor.try_collect::<Vec<Document>>().await,
+            Err(e) => Err(e),
+        };
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                state.logger.warning(format!(
+                    "Unable to look for changes in {}: {}",
+                    collection_name, e
+                ));
+                // retried from the same block on the next call
+                return;
+            }
+        };
+        for row in rows {
+            if let Ok(cursor) = row.get_document("_cursor") {
+                for bound in ["from", "to"] {
+                    if let Some(block) = get_block(cursor.get(bound)) {
+                        newest_block = newest_block.max(block);
+                    }
+                }
+            }
+            for field in fields.iter() {
+                if let Ok(value) = row.get_str(field) {
+                    tags.insert(value.to_string());
+                }
+            }
+            if collection_name == "custom_resolutions" {
+                tags.extend(custom_resolution_tags(&row, &state.conf.custom_resolvers));
+            } else if collection_name != "domains" {
+                if let Ok(id) = row.get_str("id") {
+                    changed_ids.insert(id.to_string());
+                }
+            }
+        }
+    }
+
+    // a new owner or starknet address changes the resolution of the identity domains
+    if !changed_ids.is_empty() {
+        let filter = doc! {
+            "id": { "$in": changed_ids.into_iter().collect::<Vec<String>>() },
+            "_cursor.to": null,
+        };
+        let domains = match state
+            .starknetid_db
+            .collection::<Document>("domains")
+            .find(filter, None)
+            .await
+        {
+            Ok(cursor) => cursor.try_collect::<Vec<Document>>().await,
+            Err(e) => Err(e),
+        };
+        match domains {
+            Ok(domains) => tags.extend(
+                domains
+                    .iter()
+                    .filter_map(|doc| doc.get_str("domain").ok().map(|d| d.to_string())),
+            ),
+            Err(e) => {
+                state.logger.warning(format!(
+                    "Unable to find domains of updated identities: {}",
+                    e
+                ));
+                return;
+            }
+        }
+    }
+
+    state.cache.invalidate(&tags);
+    *state.cache.last_block.lock().unwrap() = Some(newest_block);
+}
+
+async fn latest_indexed_block(state: &Arc<AppState>) -> Option<i64> {
+    let options = FindOneOptions::builder()
+        .sort(doc! { "_cursor.from": -1 })
+        .build();
+    let mut latest = None;
+    for (collection_name, _) in WATCHED_COLLECTIONS {
+        let row = state
+            .starknetid_db
+            .collection::<Document>(collection_name)
+            .find_one(None, options.clone())
+            .await;
+        match row {
+            Ok(Some(row)) => {
+                let block = row
+                    .get_document("_cursor")
+                    .ok()
+                    .and_then(|cursor| get_block(cursor.get("from")));
+                latest = latest.max(block);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                state.logger.warning(format!(
+                    "Unable to fetch the latest indexed block of {}: {}",
+                    collection_name, e
+                ));
+                return None;
+            }
+        }
+    }
+    // an empty database is watched from its first block
+    latest.or(Some(0))
+}
+
+fn get_block(value: Option<&Bson>) -> Option<i64> {
+    match value {
+        Some(Bson::Int64(block)) => Some(*block),
+        Some(Bson::Int32(block)) => Some(*block as i64),
+        _ => None,
+    }
+}
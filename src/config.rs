@@ -1,3 +1,4 @@
+use ethers::types::Address;
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use starknet::core::types::FieldElement;
@@ -5,8 +6,8 @@ use starknet::core::utils::cairo_short_string_to_felt;
 use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::time::Duration;
 
-use crate::endpoints::crosschain::ethereum::text_records::HandlerType;
 use crate::utils::to_hex;
 
 macro_rules! pub_struct {
@@ -40,6 +41,8 @@ pub_struct!(Clone, Deserialize; Contracts {
     pp_verifier: FieldElement,
     argent_multicall: FieldElement,
     free_domains: FieldElement,
+    // domain prices in ETH, required by /get_domain_price
+    pricing: Option<FieldElement>,
 });
 
 pub_struct!(Clone, Deserialize; Paymaster {
@@ -52,10 +55,14 @@ pub_struct!(Clone, Deserialize; Starkscan {
     api_key: String,
 });
 
-pub_struct!(Clone, Deserialize; Solana {
-    rpc_url: String,
-    private_key: FieldElement,
-});
+#[derive(Clone, Deserialize)]
+pub struct Solana {
+    pub rpc_url: String,
+    // SNS owners are cross-checked against these endpoints too
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    pub private_key: FieldElement,
+}
 
 pub_struct!(Clone, Debug, Deserialize; AltcoinData {
     address: FieldElement,
@@ -78,8 +85,50 @@ pub_struct!(Clone, Debug; Altcoins {
     avnu_api: String,
     private_key: FieldElement,
     data: HashMap<FieldElement, AltcoinData>,
+    // name of each token in the config, e.g. STRK
+    symbols: HashMap<FieldElement, String>,
 });
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct PriceOracle {
+    // in percent, quotes are refused when a source is further than this from the median
+    pub max_deviation: f64,
+    // sources which must answer for a quote to be signed
+    pub min_sources: usize,
+    // in seconds, for each source
+    pub timeout: f64,
+    // only AVNU (altcoins.avnu_api) is queried when empty
+    pub sources: HashMap<String, PriceSource>,
+    // in seconds, delay between two background price updates, 0 disables them
+    pub refresh_delay: f64,
+    // in percent, a cached signed quote is reused until the price moves further
+    pub requote_threshold: f64,
+    // in seconds, a cached signed quote is not reused when it expires sooner
+    pub requote_margin: i64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PriceSource {
+    // Avnu, Pragma or HttpJson
+    pub source: String,
+    // Avnu, defaults to altcoins.avnu_api
+    pub api_url: Option<String>,
+    // Pragma, defaults to variables.rpc_url
+    pub rpc_url: Option<String>,
+    pub contract: Option<FieldElement>,
+    // HttpJson, {pair} is replaced by the pair of the token
+    pub url: Option<String>,
+    pub json_path: Option<String>,
+    // Pragma and HttpJson prices are converted to ETH with the price of this pair
+    pub eth_pair: Option<String>,
+    // Pragma, in seconds, prices updated longer ago are refused (10 minutes by default)
+    pub max_age: Option<f64>,
+    // pair of each token on this source, keyed by its name in [altcoins]
+    #[serde(default)]
+    pub pairs: HashMap<String, String>,
+}
+
 pub_struct!(Clone, Debug, Deserialize; Variables {
     rpc_url: String,
     refresh_delay: f64,
@@ -95,14 +144,89 @@ pub_struct!(Clone, Debug, Deserialize; Variables {
 struct TempOffchainResolver {
     root_domain: String,
     resolver_address: String,
+    public_key: Option<FieldElement>,
     uri: Vec<String>,
 }
 
 pub_struct!(Clone, Debug, Deserialize; OffchainResolver {
     resolver_address: String,
+    // key used by the gateways to sign hints, checked before calling the resolver
+    public_key: Option<FieldElement>,
     uri: Vec<String>,
 });
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct OffchainResolving {
+    // in seconds
+    pub timeout: f64,
+    // consecutive failures before a uri is skipped
+    pub failure_threshold: u32,
+    // in seconds, how long a failing uri is skipped
+    pub cooldown: f64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Addresses {
+    // contract addresses of the network are below this bound
+    pub max_address: FieldElement,
+    // mixed case hex addresses must match their checksum
+    pub checksum: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BatchResolution {
+    // domains accepted in one /domains_to_addrs request, larger batches are refused
+    pub max_domains: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Cache {
+    // in seconds
+    pub ttl: f64,
+    // 0 disables the cache
+    pub max_entries: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SocialNames {
+    // in seconds, stored social names older than this are fetched again
+    pub ttl: f64,
+    // expired names refreshed in the background on each refresh
+    pub refresh_batch: i64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Admin {
+    // expected in the x-api-key header of admin endpoints, empty disables them
+    pub api_key: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReverseResolving {
+    // verifiers proving that an identity controls an EVM address, reverse names are only
+    // answered from their data, empty disables reverse resolution
+    pub verifier_contracts: Vec<FieldElement>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Ens {
+    // Ethereum RPC used to read ENS ownership, empty disables .eth claims
+    pub rpc_url: String,
+    // BaseRegistrar of .eth names, holding the registrant of each label
+    pub registrar: Address,
+    pub name_wrapper: Address,
+    // key signing the .eth.stark subdomain claims
+    pub private_key: FieldElement,
+}
+
 pub_struct!(Clone, Debug, Deserialize; Evm {
     private_key: String,
 });
@@ -110,11 +234,25 @@ pub_struct!(Clone, Debug, Deserialize; Evm {
 #[derive(Debug, Clone)]
 pub struct OffchainResolvers(HashMap<String, OffchainResolver>);
 
-pub_struct!(Clone, Debug, Deserialize; EvmRecordVerifier {
-    verifier_contracts: Vec<FieldElement>,
-    field: String,
-    handler: HandlerType,
-});
+#[derive(Clone, Debug, Deserialize)]
+pub struct EvmRecordVerifier {
+    pub verifier_contracts: Vec<FieldElement>,
+    pub field: String,
+    // name of the record handler turning the verified social id into the record value
+    pub handler: String,
+    // in seconds
+    pub timeout: Option<f64>,
+    // in seconds, how long returned values are cached, 0 disables caching
+    pub cache_ttl: Option<f64>,
+    // handler settings: api url and token of GetTelegramName and GetFarcasterName,
+    // url, json_path and headers of HttpJsonPath
+    pub api_url: Option<String>,
+    pub token: Option<String>,
+    pub url: Option<String>,
+    pub json_path: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
 
 pub_struct!(Clone, Debug, Deserialize; FreeDomains {
     priv_key: FieldElement,
@@ -131,8 +269,26 @@ struct RawConfig {
     custom_resolvers: HashMap<String, Vec<String>>,
     solana: Solana,
     altcoins: Altcoins,
+    #[serde(default)]
+    price_oracle: PriceOracle,
     offchain_resolvers: OffchainResolvers,
+    #[serde(default)]
+    offchain_resolving: OffchainResolving,
+    #[serde(default)]
+    addresses: Addresses,
+    #[serde(default)]
+    batch_resolution: BatchResolution,
+    #[serde(default)]
+    cache: Cache,
+    #[serde(default)]
+    social_names: SocialNames,
+    #[serde(default)]
+    admin: Admin,
+    #[serde(default)]
+    ens: Ens,
     evm: Evm,
+    #[serde(default)]
+    reverse_resolving: ReverseResolving,
     evm_networks: HashMap<String, u64>,
     evm_records_verifiers: HashMap<String, EvmRecordVerifier>,
     free_domains: FreeDomains,
@@ -150,8 +306,17 @@ pub_struct!(Clone, Deserialize; Config {
     reversed_resolvers: HashMap<String, String>,
     solana: Solana,
     altcoins: Altcoins,
+    price_oracle: PriceOracle,
     offchain_resolvers: OffchainResolvers,
+    offchain_resolving: OffchainResolving,
+    addresses: Addresses,
+    batch_resolution: BatchResolution,
+    cache: Cache,
+    social_names: SocialNames,
+    admin: Admin,
+    ens: Ens,
     evm: Evm,
+    reverse_resolving: ReverseResolving,
     evm_networks: HashMap<u64, FieldElement>,
     evm_records_verifiers: HashMap<String, EvmRecordVerifier>,
     subscription_to_altcoin: HashMap<FieldElement, String>,
@@ -175,6 +340,11 @@ pub_struct!(Clone, Deserialize; WatchtowerTypes {
 
 impl Altcoins {
     fn new(temp: TempAltcoins) -> Self {
+        let symbols = temp
+            .data
+            .iter()
+            .map(|(symbol, val)| (val.address, symbol.clone()))
+            .collect();
         let data: HashMap<FieldElement, AltcoinData> = temp
             .data
             .into_values()
@@ -195,6 +365,7 @@ impl Altcoins {
             avnu_api: temp.avnu_api,
             private_key: temp.private_key,
             data,
+            symbols,
         }
     }
 }
@@ -239,6 +410,7 @@ impl<'de> Deserialize<'de> for OffchainResolvers {
                 {
                     let resolver = OffchainResolver {
                         resolver_address: temp_resolver.resolver_address,
+                        public_key: temp_resolver.public_key,
                         uri: temp_resolver.uri,
                     };
                     hash_map.insert(temp_resolver.root_domain, resolver);
@@ -284,8 +456,17 @@ impl From<RawConfig> for Config {
             reversed_resolvers,
             solana: raw.solana,
             altcoins: raw.altcoins,
+            price_oracle: raw.price_oracle,
             offchain_resolvers: raw.offchain_resolvers,
+            offchain_resolving: raw.offchain_resolving,
+            addresses: raw.addresses,
+            batch_resolution: raw.batch_resolution,
+            cache: raw.cache,
+            social_names: raw.social_names,
+            admin: raw.admin,
+            ens: raw.ens,
             evm: raw.evm,
+            reverse_resolving: raw.reverse_resolving,
             evm_networks: reversed_evm_networks,
             evm_records_verifiers: raw.evm_records_verifiers,
             subscription_to_altcoin,
@@ -295,6 +476,17 @@ impl From<RawConfig> for Config {
     }
 }
 
+/// Duration of a setting given in seconds, negative, NaN or too large values are
+/// refused instead of panicking when the duration is built
+pub fn seconds_setting(name: &str, seconds: f64) -> Result<Duration, String> {
+    Duration::try_from_secs_f64(seconds).map_err(|_| {
+        format!(
+            "{} must be a positive number of seconds, got {}",
+            name, seconds
+        )
+    })
+}
+
 pub fn load() -> Config {
     let args: Vec<String> = env::args().collect();
     let config_path = if args.len() <= 1 {
@@ -352,6 +544,7 @@ impl Default for Config {
                 pp_verifier: FieldElement::default(),
                 argent_multicall: FieldElement::default(),
                 free_domains: FieldElement::default(),
+                pricing: None,
             },
             paymaster: Paymaster {
                 api_key: "default_api_key".to_string(),
@@ -365,17 +558,28 @@ impl Default for Config {
             reversed_resolvers: HashMap::new(),
             solana: Solana {
                 rpc_url: "https://solana-api.example.com".to_string(),
+                fallback_rpc_urls: vec![],
                 private_key: FieldElement::default(),
             },
             altcoins: Altcoins {
                 avnu_api: "https://api.example.com".to_string(),
                 private_key: FieldElement::default(),
                 data: HashMap::new(),
+                symbols: HashMap::new(),
             },
+            price_oracle: PriceOracle::default(),
             offchain_resolvers: OffchainResolvers(HashMap::new()),
+            offchain_resolving: OffchainResolving::default(),
+            addresses: Addresses::default(),
+            batch_resolution: BatchResolution::default(),
+            cache: Cache::default(),
+            social_names: SocialNames::default(),
+            admin: Admin::default(),
+            ens: Ens::default(),
             evm: Evm {
                 private_key: "default_private_key".to_string(),
             },
+            reverse_resolving: ReverseResolving::default(),
             evm_networks: HashMap::new(),
             evm_records_verifiers: HashMap::new(),
             subscription_to_altcoin: HashMap::new(),
@@ -411,3 +615,80 @@ impl Default for Variables {
         }
     }
 }
+
+impl Default for OffchainResolving {
+    fn default() -> Self {
+        OffchainResolving {
+            timeout: 5.0,
+            failure_threshold: 3,
+            cooldown: 60.0,
+        }
+    }
+}
+
+impl Default for Addresses {
+    fn default() -> Self {
+        Addresses {
+            // 2**251 - 256 on Starknet
+            max_address: FieldElement::from_hex_be(
+                "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00",
+            )
+            .unwrap(),
+            checksum: true,
+        }
+    }
+}
+
+impl Default for BatchResolution {
+    fn default() -> Self {
+        BatchResolution { max_domains: 100 }
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache {
+            ttl: 60.0,
+            max_entries: 10000,
+        }
+    }
+}
+
+impl Default for SocialNames {
+    fn default() -> Self {
+        SocialNames {
+            ttl: 86400.0,
+            refresh_batch: 100,
+        }
+    }
+}
+
+impl Default for PriceOracle {
+    fn default() -> Self {
+        PriceOracle {
+            max_deviation: 5.0,
+            min_sources: 1,
+            timeout: 5.0,
+            sources: HashMap::new(),
+            refresh_delay: 30.0,
+            requote_threshold: 0.5,
+            requote_margin: 60,
+        }
+    }
+}
+
+impl Default for Ens {
+    fn default() -> Self {
+        // mainnet deployments
+        Ens {
+            rpc_url: String::new(),
+            registrar: "0x57f1887a8BF19b14fC0dF6Fd9B2acc9Af147eA85"
+                .parse()
+                .unwrap(),
+            name_wrapper: "0xD4416b13d2b3a9aBae7AcD5D6C2BbDBE25686401"
+                .parse()
+                .unwrap(),
+            private_key: FieldElement::ZERO,
+        }
+    }
+}
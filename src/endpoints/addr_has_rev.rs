@@ -1,29 +1,34 @@
-use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
-};
-use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::IntoResponse,
-    Json,
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use axum_auto_routes::route;
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[schema(as = AddrHasRevData)]
 pub struct AddrToDomainData {
     has_rev: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AddrHasRevQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
+#[utoipa::path(
+    get,
+    path = "/addr_has_rev",
+    tag = "starknetid",
+    params(AddrHasRevQuery),
+    responses(
+        (status = 200, description = "Success", body = AddrToDomainData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/addr_has_rev", crate::endpoints::addr_has_rev)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -32,7 +37,7 @@ pub async fn handler(
     let domains = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("domains");
-    let hex_addr = to_hex(&query.addr);
+    let hex_addr = query.addr.to_hex();
     let document = domains
         .find_one(
             doc! {
@@ -51,6 +56,6 @@ pub async fn handler(
             }),
         )
             .into_response(),
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -1,10 +1,12 @@
 use crate::{
+    address::StarknetAddress,
+    errors::ApiError,
+    extractors::Query,
     models::AppState,
     resolving::get_custom_resolver,
-    utils::{get_error, to_hex},
 };
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -15,17 +17,30 @@ use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct AvailableIds {
     ids: Vec<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AddrQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
+#[utoipa::path(
+    get,
+    path = "/addr_to_available_ids",
+    tag = "starknetid",
+    params(AddrQuery),
+    responses(
+        (status = 200, description = "Success", body = AvailableIds),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/addr_to_available_ids", crate::endpoints::addr_to_available_ids)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -37,7 +52,7 @@ pub async fn handler(
     let domains = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("domains");
-    let addr = to_hex(&query.addr);
+    let addr = query.addr.to_hex();
     let documents = starknet_ids
         .find(
             doc! {
@@ -83,6 +98,6 @@ pub async fn handler(
             }
             (StatusCode::OK, Json(AvailableIds { ids })).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -1,14 +1,12 @@
 use crate::{
+    address::StarknetAddress,
+    errors::ApiError,
+    extractors::Query,
+    history::{at_block, requested_block},
     models::AppState,
-    utils::{get_error, to_hex},
 };
 use anyhow::{bail, Result};
-use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::IntoResponse,
-    Json,
-};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use axum_auto_routes::route;
 use futures::StreamExt;
 use mongodb::{
@@ -17,18 +15,22 @@ use mongodb::{
     Cursor,
 };
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct AddrToDomainData {
     domain: String,
     domain_expiry: Option<i64>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AddrToDomainQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
+    block: Option<u64>,
+    timestamp: Option<u64>,
 }
 
 async fn read_cursor(mut cursor: Cursor<Document>) -> Result<AddrToDomainData> {
@@ -55,18 +57,47 @@ async fn aggregate_data(
     read_cursor(cursor).await
 }
 
+#[utoipa::path(
+    get,
+    path = "/addr_to_domain",
+    tag = "starknetid",
+    params(AddrToDomainQuery),
+    responses(
+        (status = 200, description = "Success", body = AddrToDomainData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/addr_to_domain", crate::endpoints::addr_to_domain)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
     Query(query): Query<AddrToDomainQuery>,
 ) -> impl IntoResponse {
-    let hex_addr = to_hex(&query.addr);
+    let hex_addr = query.addr.to_hex();
     let domains_collection = state.starknetid_db.collection::<Document>("domains");
     let id_owners_collection = state.starknetid_db.collection::<Document>("id_owners");
 
-    let legacy_pipeline = create_legacy_pipeline(&hex_addr);
-    let normal_pipeline = create_normal_pipeline(&hex_addr);
-    let main_id_pipeline = create_main_id_pipeline(&hex_addr);
+    let block = match requested_block(&state, query.block, query.timestamp).await {
+        Ok(block) => block,
+        Err(e) => return e.into_response(),
+    };
+    // only the latest state is cached
+    let cache_key = format!("addr_to_domain:{}", hex_addr);
+    if block.is_none() {
+        if let Some(cached) = state.cache.get(&cache_key) {
+            return (StatusCode::OK, Json(cached)).into_response();
+        }
+    }
+    let pipelines = at_block(create_legacy_pipeline(&hex_addr), block).and_then(|legacy| {
+        Ok((
+            legacy,
+            at_block(create_normal_pipeline(&hex_addr), block)?,
+            at_block(create_main_id_pipeline(&hex_addr), block)?,
+        ))
+    });
+    let (legacy_pipeline, normal_pipeline, main_id_pipeline) = match pipelines {
+        Ok(pipelines) => pipelines,
+        Err(e) => return e.into_response(),
+    };
 
     let results = [
         aggregate_data(domains_collection.clone(), legacy_pipeline),
@@ -76,12 +107,19 @@ pub async fn handler(
 
     for result in results {
         match result.await {
-            Ok(data) => return (StatusCode::OK, Json(data)).into_response(),
+            Ok(data) => {
+                if block.is_none() {
+                    state
+                        .cache
+                        .insert(cache_key, &data, &[&hex_addr, &data.domain]);
+                }
+                return (StatusCode::OK, Json(data)).into_response();
+            }
             Err(_) => continue,
         }
     }
 
-    get_error("No data found for the given address".to_string())
+    ApiError::NotFound("No data found for the given address".to_string()).into_response()
 }
 
 fn create_legacy_pipeline(address: &String) -> Vec<Document> {
@@ -96,7 +134,7 @@ fn create_legacy_pipeline(address: &String) -> Vec<Document> {
     ]
 }
 
-fn create_normal_pipeline(address: &String) -> Vec<Document> {
+pub fn create_normal_pipeline(address: &String) -> Vec<Document> {
     vec![
         doc! {
             "$match": doc! {
@@ -250,6 +288,7 @@ fn create_normal_pipeline(address: &String) -> Vec<Document> {
                 "pipeline": [
                     doc! {
                         "$match": doc! {
+                            "_cursor.to": null,
                             "$expr": doc! {
                                 "$and": [
                                     doc! {
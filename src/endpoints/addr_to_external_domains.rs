@@ -1,9 +1,8 @@
 use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
+    address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState, utils::to_hex,
 };
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
 };
@@ -12,18 +11,32 @@ use futures::StreamExt;
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
-use std::sync::Arc; // for stream handling
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema}; // for stream handling
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[schema(as = ExternalDomainsData)]
 pub struct DomainData {
     domains: Vec<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct DomainQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
+#[utoipa::path(
+    get,
+    path = "/addr_to_external_domains",
+    tag = "starknetid",
+    params(DomainQuery),
+    responses(
+        (status = 200, description = "Success", body = DomainData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     get,
     "/addr_to_external_domains",
@@ -36,14 +49,14 @@ pub async fn handler(
     let subdomains = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("custom_resolutions");
-    let addr = &query.addr;
+    let addr = query.addr.to_hex();
     let mut domains_list = Vec::new();
 
     let cursor = subdomains
         .find(
             doc! {
                 "field" : "0x000000000000000000000000000000000000000000000000737461726b6e6574", // starknet encoded
-                "value": to_hex(addr),
+                "value": &addr,
                 "_cursor.to": null,
             },
             None,
@@ -71,7 +84,7 @@ pub async fn handler(
                             None => {}
                         }
                     }
-                    Err(_) => return get_error("Error while fetching from database".to_string()),
+                    Err(e) => return ApiError::Database(e.to_string()).into_response(),
                 }
             }
 
@@ -84,6 +97,6 @@ pub async fn handler(
             });
             (StatusCode::OK, headers, response).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
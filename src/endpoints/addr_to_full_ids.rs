@@ -1,13 +1,12 @@
 use crate::{
+    address::StarknetAddress,
+    errors::ApiError,
+    extractors::Query,
     models::AppState,
-    utils::{fetch_img_url, get_error, to_hex, to_u256},
-};
-use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::IntoResponse,
-    Json,
+    resolving::ResolutionResult,
+    utils::{fetch_img_url, to_hex, to_u256},
 };
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use axum_auto_routes::route;
 use futures::future::join_all;
 use futures::stream::StreamExt;
@@ -17,13 +16,17 @@ use mongodb::{
 };
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct FullId {
     id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     domain: Option<String>,
+    // address the domain resolves to, through the same resolvers as domain_to_addr
+    #[serde(skip_serializing_if = "Option::is_none")]
+    addr: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     domain_expiry: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -43,16 +46,28 @@ struct NFTPP {
     id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AddrQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct FullIdResponse {
     full_ids: Vec<FullId>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/addr_to_full_ids",
+    tag = "starknetid",
+    params(AddrQuery),
+    responses(
+        (status = 200, description = "Success", body = FullIdResponse),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/addr_to_full_ids", crate::endpoints::addr_to_full_ids)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -65,7 +80,7 @@ pub async fn handler(
     let pipeline = [
         doc! {
             "$match": doc! {
-                "owner": to_hex(&query.addr),
+                "owner": query.addr.to_hex(),
                 "id" : {
                     "$ne" : null
                   },
@@ -208,6 +223,18 @@ pub async fn handler(
                     });
                 }
             }
+
+            // domains are resolved like every forward resolution endpoint does
+            let domains: Vec<String> = temp_full_ids
+                .iter()
+                .filter_map(|id| id.domain.clone())
+                .collect();
+            let resolutions: HashMap<String, ResolutionResult> = domains
+                .iter()
+                .cloned()
+                .zip(state.resolvers.resolve_many(&state, &domains, None).await)
+                .collect();
+
             let api_url = state.conf.starkscan.api_url.clone();
             let api_key = state.conf.starkscan.api_key.clone();
             let full_ids_futures: Vec<_> = temp_full_ids
@@ -215,6 +242,11 @@ pub async fn handler(
                 .map(|id| {
                     let api_url_clone = api_url.clone();
                     let api_key_clone = api_key.clone();
+                    let resolution = id
+                        .domain
+                        .as_ref()
+                        .and_then(|domain| resolutions.get(domain))
+                        .and_then(|resolution| resolution.as_ref().ok());
                     async move {
                         let pp_url = match &id.pp_url_info {
                             Some((contract, id)) => {
@@ -232,7 +264,11 @@ pub async fn handler(
                         FullId {
                             id: id.id.clone(),
                             domain: id.domain.clone(),
-                            domain_expiry: id.domain_expiry,
+                            addr: resolution.map(|resolution| resolution.addr.clone()),
+                            // the indexed expiry is kept for domains which don't resolve
+                            domain_expiry: resolution
+                                .and_then(|resolution| resolution.domain_expiry)
+                                .or(id.domain_expiry),
                             pp_url: pp_url,
                         }
                     }
@@ -244,6 +280,6 @@ pub async fn handler(
             let response = FullIdResponse { full_ids: full_ids };
             (StatusCode::OK, Json(response)).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
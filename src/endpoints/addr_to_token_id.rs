@@ -1,9 +1,6 @@
-use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -12,17 +9,30 @@ use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct TokenIdData {
     token_id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TokenIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
+#[utoipa::path(
+    get,
+    path = "/addr_to_token_id",
+    tag = "starknetid",
+    params(TokenIdQuery),
+    responses(
+        (status = 200, description = "Success", body = TokenIdData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/addr_to_token_id", crate::endpoints::addr_to_token_id)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -31,7 +41,7 @@ pub async fn handler(
     let domains = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("domains");
-    let addr = to_hex(&query.addr);
+    let addr = query.addr.to_hex();
 
     let document = domains
         .find_one(
@@ -56,9 +66,10 @@ pub async fn handler(
                 let data = TokenIdData { token_id: id };
                 (StatusCode::OK, headers, Json(data)).into_response()
             } else {
-                get_error("no main domain found for this address".to_string())
+                ApiError::NotFound("no main domain found for this address".to_string())
+                    .into_response()
             }
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
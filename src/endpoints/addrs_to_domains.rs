@@ -1,10 +1,6 @@
-use crate::{models::AppState, utils::to_hex};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Json, models::AppState};
 use anyhow::{Context, Result};
-use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
-};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
 use axum_auto_routes::route;
 use futures::stream::StreamExt;
 use mongodb::{
@@ -13,18 +9,20 @@ use mongodb::{
     Cursor,
 };
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-#[derive(Serialize, Debug)]
-struct AddrToDomainData {
+#[derive(Serialize, Debug, ToSchema)]
+#[schema(as = AddrsToDomainsData)]
+pub struct AddrToDomainData {
     domain: Option<String>,
     address: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AddrToDomainsQuery {
-    addresses: Vec<FieldElement>,
+    #[schema(value_type = Vec<String>)]
+    addresses: Vec<StarknetAddress>,
 }
 
 async fn process_cursor(
@@ -57,6 +55,16 @@ async fn run_aggregation_pipeline(
     process_cursor(cursor, results).await
 }
 
+#[utoipa::path(
+    post,
+    path = "/addrs_to_domains",
+    tag = "starknetid",
+    request_body = AddrToDomainsQuery,
+    responses(
+        (status = 200, description = "Success", body = [AddrToDomainData]),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(post, "/addrs_to_domains", crate::endpoints::addrs_to_domains)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -69,7 +77,11 @@ pub async fn handler(
         .starknetid_db
         .collection::<mongodb::bson::Document>("id_owners");
 
-    let addresses: Vec<String> = query.addresses.iter().map(to_hex).collect();
+    let addresses: Vec<String> = query
+        .addresses
+        .iter()
+        .map(StarknetAddress::to_hex)
+        .collect();
 
     let mut results = addresses
         .iter()
@@ -83,14 +95,14 @@ pub async fn handler(
     if let Err(e) =
         run_aggregation_pipeline(domains_collection.clone(), legacy_pipeline, &mut results).await
     {
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response();
+        return ApiError::Database(e.to_string()).into_response();
     }
 
     let normal_pipeline = create_normal_pipeline(&addresses);
     if let Err(e) =
         run_aggregation_pipeline(domains_collection.clone(), normal_pipeline, &mut results).await
     {
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response();
+        return ApiError::Database(e.to_string()).into_response();
     }
 
     let fallback_addresses = results
@@ -102,7 +114,7 @@ pub async fn handler(
     if let Err(e) =
         run_aggregation_pipeline(id_owners_collection, fallback_pipeline, &mut results).await
     {
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response();
+        return ApiError::Database(e.to_string()).into_response();
     }
 
     (StatusCode::OK, Json(results)).into_response()
@@ -252,6 +264,7 @@ fn create_normal_pipeline(addresses: &[String]) -> Vec<Document> {
                 "pipeline": [
                     doc! {
                         "$match": doc! {
+                            "_cursor.to": null,
                             "$expr": doc! {
                                 "$and": [
                                     doc! {
@@ -1,6 +1,9 @@
-use crate::{ecdsa_sign::non_determinist_ecdsa_sign, models::AppState, utils::get_error};
+use crate::{
+    address::StarknetAddress, ecdsa_sign::non_determinist_ecdsa_sign, errors::ApiError,
+    extractors::Query, models::AppState,
+};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     response::{IntoResponse, Json},
 };
 use axum_auto_routes::route;
@@ -11,12 +14,15 @@ use serde_json::json;
 use starknet::core::types::FieldElement;
 use starknet_crypto::pedersen_hash;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 use crate::utils::to_hex;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FreeDomainQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
     code: String,
     domain: String,
 }
@@ -26,6 +32,16 @@ lazy_static::lazy_static! {
     static ref FREE_DOMAIN_STR: FieldElement = FieldElement::from_dec_str("2511989689804727759073888271181282305524144280507626647406").unwrap();
 }
 
+#[utoipa::path(
+    get,
+    path = "/campaigns/get_free_domain",
+    tag = "campaigns",
+    params(FreeDomainQuery),
+    responses(
+        (status = 200, description = "Success"),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     get,
     "/campaigns/get_free_domain",
@@ -39,7 +55,7 @@ pub async fn handler(
     // assert domain is a root domain & get domain length
     let domain_parts = query.domain.split('.').collect::<Vec<&str>>();
     if domain_parts.len() != 2 {
-        return get_error("Domain must be a root domain".to_string());
+        return ApiError::Validation("Domain must be a root domain".to_string()).into_response();
     }
     let domain_len = domain_parts[0].len();
 
@@ -60,7 +76,7 @@ pub async fn handler(
             if let Ok(spent) = doc.get_bool("spent") {
                 if spent {
                     if let Ok(spent_by) = doc.get_str("spent_by") {
-                        if spent_by == to_hex(&query.addr) {
+                        if spent_by == query.addr.to_hex() {
                             let r = doc.get_str("r").unwrap();
                             let s = doc.get_str("s").unwrap();
                             return (
@@ -72,15 +88,15 @@ pub async fn handler(
                             )
                                 .into_response();
                         } else {
-                            return get_error(format!("Coupon code already used by {}\nIf you own this account, this means you have already used this coupon code with the other account. Please switch to it.", spent_by));
+                            return ApiError::Validation(format!("Coupon code already used by {}\nIf you own this account, this means you have already used this coupon code with the other account. Please switch to it.", spent_by)).into_response();
                         }
                     } else {
-                        return get_error("Coupon code already used by someone else".to_string());
+                        return ApiError::Validation("Coupon code already used by someone else".to_string()).into_response();
                     }
                 }
             } else {
                 logger.warning(format!("Error while verifying coupon code spent status and user address"));
-                return get_error("Error while verifying coupon code availability".to_string());
+                return ApiError::Internal("Error while verifying coupon code availability".to_string()).into_response();
             }
 
             // Check domain length matches the coupon type
@@ -88,25 +104,25 @@ pub async fn handler(
                 if let Some(pos) = coupon_type.find('+') {
                     if let Ok(domain_min_size) = coupon_type[..pos].parse::<usize>() {
                         if domain_len < domain_min_size {
-                            return get_error(format!(
+                            return ApiError::Validation(format!(
                                 "Domain length is less than {}",
                                 domain_min_size
-                            ));
+                            )).into_response();
                         }
                     } else {
-                        return get_error(
+                        return ApiError::Validation(
                             "Failed to parse the numeric part of the coupon type".to_string(),
-                        );
+                        ).into_response();
                     }
                 } else {
-                    return get_error("Invalid coupon type format".to_string());
+                    return ApiError::Validation("Invalid coupon type format".to_string()).into_response();
                 }
             } else {
-                return get_error("Error while verifying coupon code type".to_string());
+                return ApiError::Internal("Error while verifying coupon code type".to_string()).into_response();
             }
 
             // generate the signature
-            let message_hash = pedersen_hash(&query.addr, &FREE_DOMAIN_STR);
+            let message_hash = pedersen_hash(&query.addr.felt(), &FREE_DOMAIN_STR);
             match non_determinist_ecdsa_sign(
                 &state.conf.free_domains.priv_key.clone(),
                 &message_hash,
@@ -122,7 +138,7 @@ pub async fn handler(
                             doc! {
                                 "$set" : {
                                     "spent" : true,
-                                    "spent_by" : to_hex(&query.addr),
+                                    "spent_by" : query.addr.to_hex(),
                                     "r" : signature.r.to_string(),
                                     "s" : signature.s.to_string(),
                                 },
@@ -136,7 +152,7 @@ pub async fn handler(
                             let api_url = format!(
                                 "{}/accounts/{}/rewards",
                                 state.conf.paymaster.api_url,
-                                to_hex(&query.addr)
+                                query.addr.to_hex()
                             );
                             let api_key = &state.conf.paymaster.api_key;
                             let starknet_id_contract = &state.conf.contracts.starknetid;
@@ -147,7 +163,7 @@ pub async fn handler(
                                 .post(&api_url)
                                 .header("api-key", api_key)
                                 .json(&json!({
-                                    "address": to_hex(&query.addr),
+                                    "address": query.addr.to_hex(),
                                     "campaign": "Free Domain",
                                     "protocol": "STARKNETID",
                                     "freeTx": 1,
@@ -174,22 +190,22 @@ pub async fn handler(
                                     })),
                                 )
                                     .into_response(),
-                                Ok(response) => get_error(format!(
+                                Ok(response) => ApiError::Upstream(format!(
                                     "Paymaster API request failed with status: {}",
                                     response.status()
-                                )),
-                                Err(e) => get_error(format!(
+                                )).into_response(),
+                                Err(e) => ApiError::Upstream(format!(
                                     "Error while requesting Paymaster API: {}",
                                     e
-                                )),
+                                )).into_response(),
                             }
                         }
-                        Err(e) => get_error(format!("Error while updating coupon code: {}", e)),
+                        Err(e) => ApiError::Database(format!("Error while updating coupon code: {}", e)).into_response(),
                     }
                 }
-                Err(e) => get_error(format!("Error while generating signature: {}", e)),
+                Err(e) => ApiError::Internal(format!("Error while generating signature: {}", e)).into_response(),
             }
         }
-        _ => get_error("Coupon code not found".to_string()),
+        _ => ApiError::NotFound("Coupon code not found".to_string()).into_response(),
     }
 }
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use crate::{
+    address::StarknetAddress, admin::AdminKey, errors::ApiError, extractors::Query,
+    models::AppState,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use chrono::Utc;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    error::{Error, ErrorKind, WriteFailure},
+    options::{FindOneAndUpdateOptions, FindOptions},
+    Collection,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+const DUPLICATE_KEY: i32 = 11000;
+
+/// A claim of a domain of another naming system on Starknet, stored whether a signature
+/// was issued or the claim was rejected because another target still had a valid one.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct CrosschainClaim {
+    // naming system of the domain, sns or ens
+    pub source: String,
+    pub domain: String,
+    // owner of the domain in the source naming system
+    pub owner: String,
+    pub target_address: String,
+    // path of the claim endpoint
+    pub endpoint: String,
+    // expiry of the signature of the owner
+    pub source_max_validity: i64,
+    // r and s of the Starknet signature, null when rejected
+    pub signature: Option<Vec<String>>,
+    // expiry of the Starknet signature, null when rejected
+    pub max_validity: Option<i64>,
+    pub rejected: bool,
+    pub created_at: i64,
+}
+
+impl CrosschainClaim {
+    pub fn new(
+        source: &str,
+        endpoint: &str,
+        domain: &str,
+        owner: String,
+        target_address: String,
+        source_max_validity: u64,
+    ) -> Self {
+        CrosschainClaim {
+            source: source.to_string(),
+            domain: domain.to_string(),
+            owner,
+            target_address,
+            endpoint: endpoint.to_string(),
+            source_max_validity: source_max_validity as i64,
+            signature: None,
+            max_validity: None,
+            rejected: false,
+            created_at: Utc::now().timestamp(),
+        }
+    }
+}
+
+// claims are written by the API, so they are kept out of the indexed database
+fn crosschain_claims(state: &Arc<AppState>) -> Collection<Document> {
+    state.sales_db.collection::<Document>("crosschain_claims")
+}
+
+// Domains reserved for the target of their latest signature, keyed by source and domain
+fn claim_reservations(state: &Arc<AppState>) -> Collection<Document> {
+    state
+        .sales_db
+        .collection::<Document>("crosschain_claim_reservations")
+}
+
+/// Key of the reservation of a domain. Domains of the source naming systems are case
+/// insensitive, so the domain is lowercased and its trailing dot dropped.
+pub fn reservation_key(source: &str, domain: &str) -> String {
+    format!(
+        "{}:{}",
+        source,
+        domain.trim().trim_end_matches('.').to_lowercase()
+    )
+}
+
+fn is_duplicate_key(error: &Error) -> bool {
+    match error.kind.as_ref() {
+        ErrorKind::Command(error) => error.code == DUPLICATE_KEY,
+        ErrorKind::Write(WriteFailure::WriteError(error)) => error.code == DUPLICATE_KEY,
+        _ => false,
+    }
+}
+
+/// Reserves the domain for the target address of the claim until `max_validity`, so that
+/// a leaked owner signature can't redirect a claim. The reservation is a single upsert
+/// keyed by the domain: while a signature issued to another target is still valid the
+/// filter doesn't match and the insert fails on the duplicate key, so concurrent claims
+/// can't both pass. Rejected claims are stored.
+pub async fn reserve_claim(
+    state: &Arc<AppState>,
+    claim: &CrosschainClaim,
+    max_validity: i64,
+) -> Result<(), ApiError> {
+    let key = reservation_key(&claim.source, &claim.domain);
+    let result = claim_reservations(state)
+        .find_one_and_update(
+            doc! {
+                "_id": &key,
+                "$or": [
+                    { "target_address": &claim.target_address },
+                    { "max_validity": { "$lte": Utc::now().timestamp() } },
+                ],
+            },
+            doc! { "$set": {
+                "target_address": &claim.target_address,
+                "max_validity": max_validity,
+            } },
+            FindOneAndUpdateOptions::builder().upsert(true).build(),
+        )
+        .await;
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) if is_duplicate_key(&e) => {
+            let pending = claim_reservations(state)
+                .find_one(doc! { "_id": &key }, None)
+                .await?;
+            let mut rejected = claim.clone();
+            rejected.rejected = true;
+            store_claim(state, &rejected).await?;
+            Err(ApiError::Validation(format!(
+                "{} was already claimed for another address, retry after {}",
+                claim.domain,
+                pending
+                    .and_then(|pending| pending.get_i64("max_validity").ok())
+                    .unwrap_or_default()
+            )))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Stores an issued claim, the signature must not be returned if this fails
+pub async fn store_claim(state: &Arc<AppState>, claim: &CrosschainClaim) -> Result<(), ApiError> {
+    let document = to_document(claim)
+        .map_err(|e| ApiError::Internal(format!("Unable to serialize claim: {}", e)))?;
+    crosschain_claims(state).insert_one(document, None).await?;
+    Ok(())
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ClaimsQuery {
+    source: Option<String>,
+    domain: Option<String>,
+    owner: Option<String>,
+    #[param(value_type = Option<String>)]
+    target_address: Option<StarknetAddress>,
+    // 100 by default, at most 1000
+    limit: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/crosschain/claims",
+    tag = "crosschain",
+    params(
+        ClaimsQuery,
+        ("x-api-key" = String, Header, description = "Admin api key")
+    ),
+    responses(
+        (status = 200, description = "Success", body = [CrosschainClaim]),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
+#[route(get, "/crosschain/claims", crate::endpoints::crosschain::claims)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminKey,
+    Query(query): Query<ClaimsQuery>,
+) -> impl IntoResponse {
+    let mut filter = doc! {};
+    if let Some(source) = &query.source {
+        filter.insert("source", source);
+    }
+    if let Some(domain) = &query.domain {
+        filter.insert("domain", domain);
+    }
+    if let Some(owner) = &query.owner {
+        filter.insert("owner", owner);
+    }
+    if let Some(target_address) = &query.target_address {
+        filter.insert("target_address", target_address.to_hex());
+    }
+    let options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .limit(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT))
+        .build();
+
+    let documents = match crosschain_claims(&state).find(filter, options).await {
+        Ok(cursor) => cursor.try_collect::<Vec<Document>>().await,
+        Err(e) => Err(e),
+    };
+    match documents {
+        Ok(documents) => {
+            let claims: Vec<CrosschainClaim> = documents
+                .into_iter()
+                .filter_map(|document| from_document(document).ok())
+                .collect();
+            (StatusCode::OK, Json(claims)).into_response()
+        }
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
+    }
+}
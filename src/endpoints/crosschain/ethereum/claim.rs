@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use crate::{
+    address::StarknetAddress,
+    endpoints::crosschain::{
+        ethereum::ens::Ens,
+        source_naming::{handle_claim, ClaimRequest},
+    },
+    errors::ApiError,
+    extractors::Json,
+    models::AppState,
+};
+use axum::{extract::State, response::IntoResponse};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Debug, Clone, ToSchema)]
+pub struct EnsClaimQuery {
+    source_domain: String,
+    #[schema(value_type = String)]
+    target_address: StarknetAddress,
+    // EIP-191 signature of the claim message by the ENS owner, as hex
+    source_signature: String,
+    max_validity: u64,
+}
+
+#[utoipa::path(
+    post,
+    path = "/crosschain/ethereum/claim",
+    tag = "crosschain",
+    request_body = EnsClaimQuery,
+    responses(
+        (status = 200, description = "Success"),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
+#[route(
+    post,
+    "/crosschain/ethereum/claim",
+    crate::endpoints::crosschain::ethereum::claim
+)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<EnsClaimQuery>,
+) -> impl IntoResponse {
+    let signature = query.source_signature;
+    let signature = match hex::decode(signature.strip_prefix("0x").unwrap_or(&signature)) {
+        Ok(signature) => signature,
+        Err(_) => {
+            return ApiError::Validation("Invalid signature encoding".to_string()).into_response()
+        }
+    };
+    let request = ClaimRequest {
+        source_domain: query.source_domain,
+        target_address: query.target_address.felt(),
+        signature,
+        max_validity: query.max_validity,
+    };
+    handle_claim(&state, &Ens, "/crosschain/ethereum/claim", request).await
+}
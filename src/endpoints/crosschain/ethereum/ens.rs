@@ -0,0 +1,216 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::async_trait;
+use ethers::{
+    abi::{self, ParamType, Token},
+    providers::{Http, Middleware, Provider, RpcError},
+    types::{Address, Signature, TransactionRequest},
+    utils::{id, keccak256},
+};
+use starknet::{core::types::FieldElement, macros::short_string};
+
+use crate::{
+    config::Config, endpoints::crosschain::source_naming::SourceNamingSystem, errors::ApiError,
+    models::AppState,
+};
+
+lazy_static::lazy_static! {
+    static ref ETH_SUBDOMAIN_STR: FieldElement = short_string!("eth subdomain");
+}
+
+/// Normalizes an ENS name before it is hashed: labels are lowercased and labels
+/// ENSIP-15 disallows are rejected. Only its ASCII rules are enforced, other
+/// characters are kept once lowercased.
+pub fn normalize_name(domain: &str) -> Result<String, ApiError> {
+    if domain.is_empty() {
+        return Ok(String::new());
+    }
+    let invalid = || ApiError::Validation(format!("Invalid ENS name: {}", domain));
+    let labels = domain
+        .split('.')
+        .map(|label| {
+            let label = label.to_lowercase();
+            let valid_chars = label.chars().all(|c| {
+                c.is_ascii_alphanumeric()
+                    || c == '-'
+                    || c == '_'
+                    || !(c.is_ascii() || c.is_whitespace() || c.is_control())
+            });
+            // underscores are only allowed as a prefix, and "xn--" like labels are
+            // reserved for punycode
+            if label.is_empty()
+                || !valid_chars
+                || label.trim_start_matches('_').contains('_')
+                || label.get(2..4) == Some("--")
+            {
+                return Err(invalid());
+            }
+            Ok(label)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(labels.join("."))
+}
+
+/// ENS node of a domain, as defined in EIP-137. The domain is normalized first so
+/// that all the spellings of a name share its node.
+pub fn namehash(domain: &str) -> Result<[u8; 32], ApiError> {
+    Ok(normalize_name(domain)?
+        .rsplit('.')
+        .filter(|label| !label.is_empty())
+        .fold([0u8; 32], |node, label| {
+            keccak256([node, keccak256(label.as_bytes())].concat())
+        }))
+}
+
+/// Returns the label of a `.eth` domain, subdomains are not supported
+pub fn parse_eth_domain(domain: &str) -> Result<&str, ApiError> {
+    normalize_name(domain)?;
+    match domain.strip_suffix(".eth") {
+        Some(name) if !name.is_empty() && !name.contains('.') => Ok(name),
+        _ => Err(ApiError::Validation(format!(
+            "Invalid ENS domain: {}",
+            domain
+        ))),
+    }
+}
+
+// Calls a view function taking a single 32 bytes argument, returns None when the
+// call reverts or returns nothing
+async fn call(
+    provider: &Provider<Http>,
+    contract: Address,
+    signature: &str,
+    arg: [u8; 32],
+    output: ParamType,
+) -> Result<Option<Token>, ApiError> {
+    let data = [
+        id(signature).to_vec(),
+        abi::encode(&[Token::FixedBytes(arg.to_vec())]),
+    ]
+    .concat();
+    let tx = TransactionRequest::new().to(contract).data(data);
+    let result = match provider.call(&tx.into(), None).await {
+        Ok(result) => result,
+        Err(e) => {
+            return match e.as_error_response() {
+                Some(error) if error.code == 3 || error.message.contains("revert") => Ok(None),
+                _ => Err(ApiError::Upstream(format!(
+                    "Ethereum RPC: {}: {}",
+                    signature, e
+                ))),
+            }
+        }
+    };
+    if result.is_empty() {
+        return Ok(None);
+    }
+    abi::decode(&[output], &result)
+        .ok()
+        .and_then(|mut tokens| tokens.pop())
+        .map(Some)
+        .ok_or_else(|| ApiError::Upstream(format!("Ethereum RPC: {}: invalid result", signature)))
+}
+
+// Owner returned by an `ownerOf(uint256)` call, None for reverted calls and the zero
+// address
+async fn call_owner_of(
+    provider: &Provider<Http>,
+    contract: Address,
+    token_id: [u8; 32],
+) -> Result<Option<Address>, ApiError> {
+    let owner = call(
+        provider,
+        contract,
+        "ownerOf(uint256)",
+        token_id,
+        ParamType::Address,
+    )
+    .await?;
+    match owner {
+        Some(owner) => match owner.into_address() {
+            Some(owner) if owner.is_zero() => Ok(None),
+            Some(owner) => Ok(Some(owner)),
+            None => Err(ApiError::Upstream(
+                "Ethereum RPC: ownerOf(uint256): invalid result".to_string(),
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Returns the registrant of a `.eth` domain, read from the BaseRegistrar. The
+/// controller set in the ENS registry is ignored as it isn't updated on transfers.
+/// Names wrapped in the NameWrapper are owned by the holder of their token, and
+/// expired names have no owner.
+pub async fn get_ens_owner(conf: &Config, domain: &str) -> Result<Address, ApiError> {
+    let domain = &normalize_name(domain)?;
+    let label = parse_eth_domain(domain)?;
+    if conf.ens.rpc_url.is_empty() {
+        return Err(ApiError::Validation(
+            "ENS claims are not enabled".to_string(),
+        ));
+    }
+    let provider = Provider::<Http>::try_from(conf.ens.rpc_url.as_str())
+        .map_err(|e| ApiError::Internal(format!("Invalid Ethereum RPC url: {}", e)))?;
+    let not_registered = || ApiError::NotFound(format!("ENS domain {} is not registered", domain));
+
+    // ownerOf reverts once the name expired
+    let registrant = call_owner_of(&provider, conf.ens.registrar, keccak256(label.as_bytes()))
+        .await?
+        .ok_or_else(not_registered)?;
+    if registrant != conf.ens.name_wrapper {
+        return Ok(registrant);
+    }
+    // the wrapper returns the zero address for expired names
+    call_owner_of(&provider, conf.ens.name_wrapper, namehash(domain)?)
+        .await?
+        .ok_or_else(not_registered)
+}
+
+/// Checks an EIP-191 (personal_sign) signature of a message
+pub fn verify_eip191(owner: &str, message: &str, signature: &[u8]) -> Result<(), ApiError> {
+    let signature = Signature::try_from(signature)
+        .map_err(|e| ApiError::Validation(format!("Invalid signature: {}", e)))?;
+    let owner = Address::from_str(owner)
+        .map_err(|e| ApiError::Internal(format!("Invalid ENS owner: {}", e)))?;
+    signature
+        .verify(message, owner)
+        .map_err(|e| ApiError::Validation(format!("Signature verification failed: {}", e)))
+}
+
+/// ENS domains, claimed with an EIP-191 signature of the claim message
+pub struct Ens;
+
+#[async_trait]
+impl SourceNamingSystem for Ens {
+    fn name(&self) -> &'static str {
+        "ens"
+    }
+
+    fn subdomain_str(&self) -> FieldElement {
+        *ETH_SUBDOMAIN_STR
+    }
+
+    fn private_key(&self, conf: &Config) -> FieldElement {
+        conf.ens.private_key
+    }
+
+    fn parse_domain<'a>(&self, domain: &'a str) -> Result<&'a str, ApiError> {
+        parse_eth_domain(domain)
+    }
+
+    async fn get_owner(&self, state: &Arc<AppState>, domain: &str) -> Result<String, ApiError> {
+        let owner = get_ens_owner(&state.conf, domain).await?;
+        // lowercase hex, as signed by wallets
+        Ok(format!("{:?}", owner))
+    }
+
+    fn verify_signature(
+        &self,
+        owner: &str,
+        message: &str,
+        signature: &[u8],
+    ) -> Result<(), ApiError> {
+        verify_eip191(owner, message, signature)
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use ethabi::ParamType;
+use ethers::types::U256;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolverFunctionCall {
@@ -11,13 +12,13 @@ pub enum ResolverFunctionCall {
     Name(Vec<u8>),
     // abi(bytes32 node, uint256 contentTypes) returns (uint256, bytes)
     // 0x2203ab56
-    Abi,
+    Abi(Vec<u8>, U256),
     // text(bytes32 node, string key) returns (string)
     // 0x59d1d43c
     Text(Vec<u8>, String),
     // contenthash(bytes32 node) returns (bytes)
     // 0xbc1c58d1
-    ContentHash,
+    ContentHash(Vec<u8>),
     // interfaceImplementer(bytes32 node, bytes4 interfaceID) returns (address)
     // 0xb8f2bbb4
     InterfaceImplementer,
@@ -26,58 +27,114 @@ pub enum ResolverFunctionCall {
     AddrMultichain(Vec<u8>, u64),
     // pubkey(bytes32 node) returns (bytes32, bytes32)
     // 0xc8690233
-    PubKey,
+    PubKey(Vec<u8>),
+    // multicall(bytes[] data) returns (bytes[]), ENSIP-10
+    // 0xac9650d8
+    Multicall(Vec<ResolverFunctionCall>),
 }
 
 impl TryFrom<&[u8]> for ResolverFunctionCall {
     type Error = anyhow::Error;
 
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        let selector: &[u8; 4] = data[0..4].try_into().expect("Array length is correct");
+        if data.len() < 4 {
+            return Err(anyhow!("Call data is too short: {} bytes", data.len()));
+        }
+        let (selector, payload) = data.split_at(4);
         let selector_hex = hex::encode(selector);
 
-        let payload = &data[4..];
-
         match selector_hex.as_str() {
-            "3b3b57de" => {
-                let result = ethers::abi::decode(&[ParamType::FixedBytes(32)], payload)
-                    .map_err(|e| anyhow!("ABI decode error: {}", e))?;
+            "3b3b57de" => Ok(ResolverFunctionCall::Addr(decode_namehash(payload)?)),
+            "691f3431" => Ok(ResolverFunctionCall::Name(decode_namehash(payload)?)),
+            "2203ab56" => {
+                let result = ethers::abi::decode(
+                    &[ParamType::FixedBytes(32), ParamType::Uint(256)],
+                    payload,
+                )
+                .map_err(|e| anyhow!("ABI decode error: {}", e))?;
                 let namehash = result[0]
                     .clone()
                     .into_fixed_bytes()
                     .ok_or_else(|| anyhow!("Invalid namehash"))?;
+                let content_types = result[1]
+                    .clone()
+                    .into_uint()
+                    .ok_or_else(|| anyhow!("Invalid content types"))?;
 
-                Ok(ResolverFunctionCall::Addr(namehash))
-            }
-            "691f3431" => {
-                let result = ethers::abi::decode(&[ParamType::FixedBytes(32)], payload).unwrap();
-                let namehash = result[0].clone().into_fixed_bytes().unwrap();
-
-                Ok(ResolverFunctionCall::Name(namehash))
+                Ok(ResolverFunctionCall::Abi(namehash, content_types))
             }
-            "2203ab56" => Ok(ResolverFunctionCall::Abi),
             "59d1d43c" => {
                 let result =
                     ethers::abi::decode(&[ParamType::FixedBytes(32), ParamType::String], payload)
-                        .unwrap();
-                let namehash = result[0].clone().into_fixed_bytes().unwrap();
-                let record = result[1].clone().into_string().unwrap();
+                        .map_err(|e| anyhow!("ABI decode error: {}", e))?;
+                let namehash = result[0]
+                    .clone()
+                    .into_fixed_bytes()
+                    .ok_or_else(|| anyhow!("Invalid namehash"))?;
+                let record = result[1]
+                    .clone()
+                    .into_string()
+                    .ok_or_else(|| anyhow!("Invalid record key"))?;
 
                 Ok(ResolverFunctionCall::Text(namehash, record))
             }
-            "bc1c58d1" => Ok(ResolverFunctionCall::ContentHash),
+            "bc1c58d1" => Ok(ResolverFunctionCall::ContentHash(decode_namehash(payload)?)),
             "b8f2bbb4" => Ok(ResolverFunctionCall::InterfaceImplementer),
             "f1cb7e06" => {
                 let result =
                     ethers::abi::decode(&[ParamType::FixedBytes(32), ParamType::Uint(64)], payload)
-                        .unwrap();
-                let namehash = result.get(0).unwrap().clone().into_fixed_bytes().unwrap();
-                let coin_type: u64 = result.get(1).unwrap().clone().into_uint().unwrap().as_u64();
+                        .map_err(|e| anyhow!("ABI decode error: {}", e))?;
+                let namehash = result[0]
+                    .clone()
+                    .into_fixed_bytes()
+                    .ok_or_else(|| anyhow!("Invalid namehash"))?;
+                let coin_type = result[1]
+                    .clone()
+                    .into_uint()
+                    .ok_or_else(|| anyhow!("Invalid coin type"))?;
+                // the word is not checked to fit in 64 bits by the decoder
+                if coin_type > U256::from(u64::MAX) {
+                    return Err(anyhow!("Invalid coin type: {}", coin_type));
+                }
+                let coin_type = coin_type.as_u64();
 
                 Ok(ResolverFunctionCall::AddrMultichain(namehash, coin_type))
             }
-            "c8690233" => Ok(ResolverFunctionCall::PubKey),
+            "c8690233" => Ok(ResolverFunctionCall::PubKey(decode_namehash(payload)?)),
+            "ac9650d8" => {
+                let result =
+                    ethers::abi::decode(&[ParamType::Array(Box::new(ParamType::Bytes))], payload)
+                        .map_err(|e| anyhow!("ABI decode error: {}", e))?;
+                let calls = result[0]
+                    .clone()
+                    .into_array()
+                    .ok_or_else(|| anyhow!("Invalid multicall data"))?
+                    .into_iter()
+                    .map(|call| {
+                        let call = call
+                            .into_bytes()
+                            .ok_or_else(|| anyhow!("Invalid multicall data"))?;
+                        match ResolverFunctionCall::try_from(call.as_slice())? {
+                            ResolverFunctionCall::Multicall(_) => {
+                                Err(anyhow!("Nested multicall is not supported"))
+                            }
+                            call => Ok(call),
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(ResolverFunctionCall::Multicall(calls))
+            }
             _ => Err(anyhow!("Invalid function selector: {}", selector_hex)),
         }
     }
 }
+
+fn decode_namehash(payload: &[u8]) -> Result<Vec<u8>> {
+    let result = ethers::abi::decode(&[ParamType::FixedBytes(32)], payload)
+        .map_err(|e| anyhow!("ABI decode error: {}", e))?;
+    result[0]
+        .clone()
+        .into_fixed_bytes()
+        .ok_or_else(|| anyhow!("Invalid namehash"))
+}
@@ -1,4 +1,10 @@
+pub mod claim;
+pub mod ens;
 pub mod lookup;
+pub mod profile_records;
+pub mod record_handlers;
 pub mod resolve;
+pub mod reverse;
+pub mod social_names;
 pub mod text_records;
 pub mod utils;
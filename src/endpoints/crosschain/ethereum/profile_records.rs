@@ -0,0 +1,189 @@
+use anyhow::{anyhow, bail, Result};
+use ethabi::Token;
+use ethers::types::U256;
+
+// ENSIP-7 namespaces, as unsigned varints
+const IPFS_NAMESPACE: [u8; 2] = [0xe3, 0x01];
+const SWARM_NAMESPACE: [u8; 2] = [0xe4, 0x01];
+const IPNS_NAMESPACE: [u8; 2] = [0xe5, 0x01];
+
+// CID codecs
+const CID_V1: u8 = 0x01;
+const DAG_PB: u8 = 0x70;
+const LIBP2P_KEY: u8 = 0x72;
+const SWARM_MANIFEST: [u8; 2] = [0xfa, 0x01];
+
+// multihash codes
+const IDENTITY: u8 = 0x00;
+const KECCAK_256: u8 = 0x1b;
+
+// ENS abi content types
+const ABI_JSON: u64 = 1;
+const ABI_URI: u64 = 8;
+
+/// Encodes a `contenthash` user data value (`ipfs://`, `ipns://` or `bzz://` uri)
+/// as the bytes returned by the ENS `contenthash` function (ENSIP-7).
+pub fn encode_content_hash(uri: &str) -> Result<Vec<u8>> {
+    let uri = uri.trim();
+    if let Some(cid) = strip_scheme(uri, "ipfs") {
+        let cid = decode_cid(cid, DAG_PB).ok_or_else(|| anyhow!("Invalid IPFS CID: {}", cid))?;
+        Ok([IPFS_NAMESPACE.to_vec(), cid].concat())
+    } else if let Some(name) = strip_scheme(uri, "ipns") {
+        let cid = match decode_cid(name, LIBP2P_KEY) {
+            Some(cid) => cid,
+            // a DNSLink name, stored inline with the identity multihash
+            None if !name.is_empty() && name.len() < 128 => [
+                vec![CID_V1, LIBP2P_KEY, IDENTITY, name.len() as u8],
+                name.as_bytes().to_vec(),
+            ]
+            .concat(),
+            None => bail!("Invalid IPNS name: {}", name),
+        };
+        Ok([IPNS_NAMESPACE.to_vec(), cid].concat())
+    } else if let Some(hash) = strip_scheme(uri, "bzz") {
+        let hash = hex::decode(hash.trim_start_matches("0x"))?;
+        if hash.len() != 32 {
+            bail!("Invalid Swarm hash length: {}", hash.len());
+        }
+        Ok([
+            SWARM_NAMESPACE.to_vec(),
+            vec![CID_V1],
+            SWARM_MANIFEST.to_vec(),
+            vec![KECCAK_256, 32],
+            hash,
+        ]
+        .concat())
+    } else {
+        bail!("Unsupported content hash: {}", uri)
+    }
+}
+
+fn strip_scheme<'a>(uri: &'a str, protocol: &str) -> Option<&'a str> {
+    uri.strip_prefix(&format!("{}://", protocol))
+        .or_else(|| uri.strip_prefix(&format!("/{}/", protocol)))
+        .map(|value| value.trim_end_matches('/'))
+}
+
+// Returns the binary CIDv1 of a CID (v0 or v1) or of a base58 multihash
+fn decode_cid(cid: &str, codec: u8) -> Option<Vec<u8>> {
+    let mut chars = cid.chars();
+    let cid_v1 = match chars.next()? {
+        'b' => decode_base32(chars.as_str()),
+        'k' => decode_base36(chars.as_str()),
+        'z' => bs58::decode(chars.as_str()).into_vec().ok(),
+        _ => None,
+    };
+    match cid_v1 {
+        Some(bytes) if bytes.first() == Some(&CID_V1) => Some(bytes),
+        _ => {
+            // CIDv0 and peer ids are a bare multihash
+            let multihash = bs58::decode(cid).into_vec().ok()?;
+            let length = *multihash.get(1)? as usize;
+            if multihash.len() != length + 2 {
+                return None;
+            }
+            Some([vec![CID_V1, codec], multihash].concat())
+        }
+    }
+}
+
+// RFC 4648 lowercase base32 without padding, as used by multibase
+fn decode_base32(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in value.chars() {
+        let digit = match c {
+            'a'..='z' => c as u32 - 'a' as u32,
+            '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | digit;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+// lowercase base36, as used by multibase
+fn decode_base36(value: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in value.chars() {
+        let mut carry = c.to_digit(36)?;
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 36;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    // leading zeros are encoded as leading '0' digits
+    let zeros = value.chars().take_while(|c| *c == '0').count();
+    Some([vec![0; zeros], bytes].concat())
+}
+
+/// Splits a `pubkey` user data value, an uncompressed SECP256k1 public key in hex
+/// with or without its 0x04 prefix, into the x and y coordinates returned by ENS.
+pub fn decode_pubkey(value: &str) -> Result<(Vec<u8>, Vec<u8>)> {
+    let bytes = hex::decode(value.trim().trim_start_matches("0x"))?;
+    let coordinates = match bytes.len() {
+        64 => &bytes[..],
+        65 if bytes[0] == 0x04 => &bytes[1..],
+        length => bail!("Invalid public key length: {}", length),
+    };
+    Ok((coordinates[..32].to_vec(), coordinates[32..].to_vec()))
+}
+
+/// Returns the content type and data of the ENS `abi` record stored in the `abi` user
+/// data, a JSON ABI or an uri pointing to it, or `(0, [])` if none of the requested
+/// content types matches.
+pub fn encode_abi(value: &str, content_types: U256) -> (U256, Vec<u8>) {
+    let value = value.trim();
+    let content_type = if ["http://", "https://", "ipfs://"]
+        .iter()
+        .any(|scheme| value.starts_with(scheme))
+    {
+        ABI_URI
+    } else {
+        ABI_JSON
+    };
+    if content_types & U256::from(content_type) == U256::zero() {
+        return (U256::zero(), Vec::new());
+    }
+    (U256::from(content_type), value.as_bytes().to_vec())
+}
+
+// ENS resolvers answer unset records with empty values instead of reverting, malformed
+// records are answered the same way since they can't be returned either
+
+/// Return values of `contenthash(bytes32)` for a `contenthash` user data value.
+pub fn content_hash_tokens(record: Option<&str>) -> Vec<Token> {
+    let content_hash = record
+        .and_then(|uri| encode_content_hash(uri).ok())
+        .unwrap_or_default();
+    vec![Token::Bytes(content_hash)]
+}
+
+/// Return values of `pubkey(bytes32)` for a `pubkey` user data value.
+pub fn pubkey_tokens(record: Option<&str>) -> Vec<Token> {
+    let (x, y) = record
+        .and_then(|pubkey| decode_pubkey(pubkey).ok())
+        .unwrap_or_else(|| (vec![0; 32], vec![0; 32]));
+    vec![Token::FixedBytes(x), Token::FixedBytes(y)]
+}
+
+/// Return values of `ABI(bytes32,uint256)` for an `abi` user data value.
+pub fn abi_tokens(record: Option<&str>, content_types: U256) -> Vec<Token> {
+    let (content_type, data) = match record {
+        Some(abi) => encode_abi(abi, content_types),
+        None => (U256::zero(), Vec::new()),
+    };
+    vec![Token::Uint(content_type), Token::Bytes(data)]
+}
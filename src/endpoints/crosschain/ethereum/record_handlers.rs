@@ -0,0 +1,276 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use axum::async_trait;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use starknet::core::types::FieldElement;
+
+use crate::config::{Config, EvmRecordVerifier};
+
+// in seconds, used when a record doesn't set its own
+const DEFAULT_TIMEOUT: f64 = 5.0;
+const DEFAULT_CACHE_TTL: f64 = 600.0;
+// expired names are dropped once a handler caches this many of them
+const MAX_CACHED_NAMES: usize = 10000;
+
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+const FARCASTER_HUB_URL: &str = "https://hub.pinata.cloud";
+// USER_DATA_TYPE_USERNAME of the Farcaster hub API
+const FARCASTER_USERNAME_TYPE: u32 = 6;
+
+/// Turns the social id verified for a text record (a Discord user id, a Farcaster
+/// fid...) into the value returned for this record, usually a username.
+#[async_trait]
+pub trait RecordHandler: Send + Sync {
+    async fn handle(&self, client: &Client, social_id: FieldElement) -> Result<String>;
+}
+
+/// Returns the social id itself
+pub struct StaticHandler;
+
+#[async_trait]
+impl RecordHandler for StaticHandler {
+    async fn handle(&self, _client: &Client, social_id: FieldElement) -> Result<String> {
+        Ok(social_id.to_string())
+    }
+}
+
+/// Fetches a JSON document from `url`, where `{id}` is replaced by the social id, and
+/// returns the value found at `json_path`.
+pub struct HttpJsonHandler {
+    url: String,
+    json_path: String,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpJsonHandler {
+    pub fn new(url: String, json_path: &str) -> Self {
+        HttpJsonHandler {
+            url,
+            json_path: json_path.to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: String) -> Self {
+        self.headers.push((name.to_string(), value));
+        self
+    }
+}
+
+#[async_trait]
+impl RecordHandler for HttpJsonHandler {
+    async fn handle(&self, client: &Client, social_id: FieldElement) -> Result<String> {
+        let url = self.url.replace("{id}", &social_id.to_string());
+        let mut request = client.get(&url).header("Accept", "application/json");
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        // the url is dropped from the errors, it can hold an api token
+        let response = request
+            .send()
+            .await
+            .map_err(|e| e.without_url())
+            .context("Failed to send request")?;
+        if response.status() != StatusCode::OK {
+            bail!("API returned non-OK status: {}", response.status());
+        }
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| e.without_url())
+            .context("Failed to parse JSON response")?;
+        extract_json_path(&json, &self.json_path)
+            .ok_or_else(|| anyhow!("No value found at {}", self.json_path))
+    }
+}
+
+/// Returns the string or number found at a dot separated path of object keys and
+/// array indexes, e.g. `data.users.0.name`.
+pub fn extract_json_path(json: &Value, path: &str) -> Option<String> {
+    let mut value = json;
+    for key in path.split('.').filter(|key| !key.is_empty()) {
+        value = match value {
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?)?,
+            value => value.get(key)?,
+        };
+    }
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+struct RegisteredHandler {
+    handler: Box<dyn RecordHandler>,
+    timeout: Duration,
+    cache_ttl: Duration,
+    names: Mutex<HashMap<FieldElement, (String, Instant)>>,
+}
+
+impl RegisteredHandler {
+    fn cached_name(&self, social_id: &FieldElement) -> Option<String> {
+        match self.names.lock().unwrap().get(social_id) {
+            Some((name, fetched_at)) if fetched_at.elapsed() < self.cache_ttl => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    fn cache_name(&self, social_id: FieldElement, name: &str) {
+        if self.cache_ttl.is_zero() {
+            return;
+        }
+        let mut names = self.names.lock().unwrap();
+        if names.len() >= MAX_CACHED_NAMES {
+            let cache_ttl = self.cache_ttl;
+            names.retain(|_, (_, fetched_at)| fetched_at.elapsed() < cache_ttl);
+        }
+        if names.len() < MAX_CACHED_NAMES {
+            names.insert(social_id, (name.to_string(), Instant::now()));
+        }
+    }
+}
+
+/// Handlers of the `evm_records_verifiers` text records, keyed by record name. Each
+/// one runs with its own timeout and caches the names it returns.
+pub struct RecordHandlers {
+    client: Client,
+    handlers: HashMap<String, RegisteredHandler>,
+}
+
+impl RecordHandlers {
+    pub fn new(conf: &Config) -> Result<Self> {
+        let mut record_handlers = RecordHandlers {
+            client: Client::new(),
+            handlers: HashMap::new(),
+        };
+        for (record, verifier) in &conf.evm_records_verifiers {
+            let handler = build_handler(conf, verifier)
+                .with_context(|| format!("Invalid handler for record {}", record))?;
+            record_handlers.register(
+                record,
+                handler,
+                Duration::from_secs_f64(verifier.timeout.unwrap_or(DEFAULT_TIMEOUT)),
+                Duration::from_secs_f64(verifier.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL)),
+            );
+        }
+        Ok(record_handlers)
+    }
+
+    /// Adds or replaces the handler of a record, a zero cache_ttl disables caching
+    pub fn register(
+        &mut self,
+        record: &str,
+        handler: Box<dyn RecordHandler>,
+        timeout: Duration,
+        cache_ttl: Duration,
+    ) {
+        self.handlers.insert(
+            record.to_string(),
+            RegisteredHandler {
+                handler,
+                timeout,
+                cache_ttl,
+                names: Mutex::new(HashMap::new()),
+            },
+        );
+    }
+
+    /// Returns the name of a social id cached in memory, if still valid
+    pub fn cached_name(&self, record: &str, social_id: &FieldElement) -> Option<String> {
+        self.handlers.get(record)?.cached_name(social_id)
+    }
+
+    pub async fn execute(&self, record: &str, social_id: FieldElement) -> Result<String> {
+        let registered = self
+            .handlers
+            .get(record)
+            .ok_or_else(|| anyhow!("No handler registered for record {}", record))?;
+        if let Some(name) = registered.cached_name(&social_id) {
+            return Ok(name);
+        }
+        let name = tokio::time::timeout(
+            registered.timeout,
+            registered.handler.handle(&self.client, social_id),
+        )
+        .await
+        .map_err(|_| anyhow!("Handler of record {} timed out", record))??;
+        registered.cache_name(social_id, &name);
+        Ok(name)
+    }
+}
+
+// Builds the handler named in the config of a record
+fn build_handler(conf: &Config, verifier: &EvmRecordVerifier) -> Result<Box<dyn RecordHandler>> {
+    let variables = &conf.variables;
+    let handler: Box<dyn RecordHandler> = match verifier.handler.as_str() {
+        "Static" => Box::new(StaticHandler),
+        "GetDiscordName" => Box::new(
+            HttpJsonHandler::new(
+                format!("{}/users/{{id}}", variables.discord_api_url),
+                "username",
+            )
+            .header("Authorization", format!("Bot {}", variables.discord_token)),
+        ),
+        "GetGithubName" => Box::new(
+            HttpJsonHandler::new(format!("{}/user/{{id}}", variables.github_api_url), "login")
+                .header("User-Agent", "request".to_string()),
+        ),
+        "GetTwitterName" => Box::new(
+            HttpJsonHandler::new(
+                format!(
+                    "{}/get-user-by-id?user_id={{id}}",
+                    variables.twitter_api_url
+                ),
+                "data.user_result.result.legacy.screen_name",
+            )
+            .header("X-RapidAPI-Key", variables.twitter_api_key.clone())
+            .header("X-RapidAPI-Host", "twttrapi.p.rapidapi.com".to_string()),
+        ),
+        "GetTelegramName" => {
+            let token = verifier
+                .token
+                .as_ref()
+                .ok_or_else(|| anyhow!("GetTelegramName requires a bot token"))?;
+            Box::new(HttpJsonHandler::new(
+                format!(
+                    "{}/bot{}/getChat?chat_id={{id}}",
+                    verifier.api_url.as_deref().unwrap_or(TELEGRAM_API_URL),
+                    token
+                ),
+                "result.username",
+            ))
+        }
+        "GetFarcasterName" => Box::new(HttpJsonHandler::new(
+            format!(
+                "{}/v1/userDataByFid?fid={{id}}&user_data_type={}",
+                verifier.api_url.as_deref().unwrap_or(FARCASTER_HUB_URL),
+                FARCASTER_USERNAME_TYPE
+            ),
+            "data.userDataBody.value",
+        )),
+        "HttpJsonPath" => {
+            let url = verifier
+                .url
+                .as_ref()
+                .ok_or_else(|| anyhow!("HttpJsonPath requires an url"))?;
+            let json_path = verifier
+                .json_path
+                .as_ref()
+                .ok_or_else(|| anyhow!("HttpJsonPath requires a json_path"))?;
+            let handler = verifier.headers.iter().fold(
+                HttpJsonHandler::new(url.clone(), json_path),
+                |handler, (name, value)| handler.header(name, value.clone()),
+            );
+            Box::new(handler)
+        }
+        handler => bail!("Unknown record handler {}", handler),
+    };
+    Ok(handler)
+}
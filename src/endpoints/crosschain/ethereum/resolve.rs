@@ -3,13 +3,17 @@ use std::{str::FromStr, sync::Arc};
 use crate::{
     endpoints::crosschain::ethereum::{
         lookup::ResolverFunctionCall,
+        profile_records::{abi_tokens, content_hash_tokens, pubkey_tokens},
+        reverse::{parse_reverse_name, reverse_lookup},
         utils::{
-            decode_data, domain_to_address, get_profile_picture, get_user_data,
-            get_user_data_multicall, sign_message, to_eth_hex,
+            decode_data, get_profile_picture, get_user_data, get_user_data_multicall, sign_message,
+            to_eth_hex,
         },
     },
+    errors::ApiError,
     models::AppState,
-    utils::{get_error, to_hex},
+    resolving::ResolutionError,
+    utils::to_hex,
 };
 use anyhow::Result;
 use axum::{
@@ -24,7 +28,7 @@ use axum_auto_routes::route;
 use bytes::{BufMut, BytesMut};
 use ethabi::Token;
 use ethers::{signers::LocalWallet, types::H160, utils::keccak256};
-use futures::{pin_mut, stream::StreamExt as _};
+use futures::{future::join_all, pin_mut, stream::StreamExt as _};
 use lazy_static::lazy_static;
 use mongodb::bson::doc;
 use reqwest::Url;
@@ -36,10 +40,11 @@ use starknet::{
     providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
 };
 use starknet_id::encode;
+use utoipa::ToSchema;
 
 use super::text_records::{get_unbounded_user_data, get_verifier_data};
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, ToSchema)]
 pub struct ResolveQuery {
     data: String,   // data encoded
     sender: String, // resolver contract address
@@ -50,20 +55,12 @@ pub enum Query {
     Form(ResolveQuery),
 }
 
-pub struct CustomRejection(String);
-
-impl IntoResponse for CustomRejection {
-    fn into_response(self) -> Response {
-        (StatusCode::BAD_REQUEST, self.0).into_response()
-    }
-}
-
 #[async_trait]
 impl<S> FromRequest<S, Body> for Query
 where
     S: Send + Sync + 'static,
 {
-    type Rejection = CustomRejection;
+    type Rejection = ApiError;
 
     async fn from_request(req: Request<Body>, _state: &S) -> Result<Self, Self::Rejection> {
         let body = req.into_body();
@@ -73,8 +70,8 @@ where
 
         // Collecting data chunks from the body stream
         while let Some(chunk) = body.next().await {
-            let chunk =
-                chunk.map_err(|_| CustomRejection("Failed to read request body".to_string()))?;
+            let chunk = chunk
+                .map_err(|_| ApiError::Validation("Failed to read request body".to_string()))?;
             bytes.put(chunk);
         }
 
@@ -90,15 +87,25 @@ where
             return Ok(Query::Form(form_data));
         }
 
-        Err(CustomRejection("Unsupported Content Type".to_string()))
+        Err(ApiError::Validation("Unsupported Content Type".to_string()))
     }
 }
 
 lazy_static! {
-    static ref EVM_ADDRESS: FieldElement = short_string!("evm-address");
-    static ref ETHEREUM: FieldElement = short_string!("ethereum");
+    pub static ref EVM_ADDRESS: FieldElement = short_string!("evm-address");
+    pub static ref ETHEREUM: FieldElement = short_string!("ethereum");
 }
 
+#[utoipa::path(
+    post,
+    path = "/crosschain/ethereum/resolve",
+    tag = "crosschain",
+    request_body = ResolveQuery,
+    responses(
+        (status = 200, description = "Success"),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     post,
     "/crosschain/ethereum/resolve",
@@ -113,23 +120,51 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
 
     match decode_data(&encoded_data) {
         Ok((name, resolver_function_call)) => {
+            // reverse nodes only answer name(), with the domain of the queried address
+            if let Some((address, coin_type)) = parse_reverse_name(&name) {
+                let payload = match resolver_function_call {
+                    ResolverFunctionCall::Name(_bf) => {
+                        match reverse_lookup(&state, address, coin_type).await {
+                            Ok(domain) => vec![Token::String(domain)],
+                            Err(e) => return e.into_response(),
+                        }
+                    }
+                    _ => {
+                        return ApiError::Validation(format!(
+                            "Only name() can be resolved for reverse name {}",
+                            name
+                        ))
+                        .into_response()
+                    }
+                };
+                return signed_response(&state, &encoded_data, &sender, payload);
+            }
+
             let parts: Vec<&str> = name.split('.').collect();
             let root_domain = if parts.len() > 2 {
                 &parts[..parts.len() - 2]
             } else {
-                return get_error(format!("Domain with wrong size {}", name));
+                return ApiError::Validation(format!("Domain with wrong size {}", name))
+                    .into_response();
             };
-            let encoded_domain: Vec<FieldElement> = root_domain
+            let encoded_domain: Vec<FieldElement> = match root_domain
                 .iter()
-                .map(|&part| encode(part).unwrap())
-                .collect();
+                .map(|&part| encode(part))
+                .collect::<Result<_, _>>()
+            {
+                Ok(encoded_domain) => encoded_domain,
+                Err(_) => {
+                    return ApiError::Validation(format!("Domain can't be encoded: {}", name))
+                        .into_response()
+                }
+            };
 
             // get the id of the domain
             let provider = JsonRpcClient::new(HttpTransport::new(
                 Url::parse(&state.conf.variables.rpc_url).unwrap(),
             ));
             let mut calldata: Vec<FieldElement> = vec![FieldElement::from(encoded_domain.len())];
-            calldata.extend(encoded_domain.clone());
+            calldata.extend(encoded_domain);
             let call_result = provider
                 .call(
                     FunctionCall {
@@ -142,252 +177,293 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                 .await;
             match call_result {
                 Ok(result) => {
-                    if result[0] == FieldElement::ZERO {
-                        return get_error(format!("No identity found for : {}", name));
-                    }
-                    let id: FieldElement = result[0];
+                    let id = match result.first() {
+                        Some(id) if *id != FieldElement::ZERO => *id,
+                        _ => {
+                            return ApiError::NotFound(format!("No identity found for : {}", name))
+                                .into_response()
+                        }
+                    };
 
                     let payload: Vec<Token> = match resolver_function_call {
-                        ResolverFunctionCall::Text(_alt_hash, record) => {
-                            match record.as_str() {
-                                "avatar" => {
-                                    match get_profile_picture(
-                                        &state.conf,
-                                        &provider,
-                                        state.starknetid_db.collection::<mongodb::bson::Document>(
-                                            "id_verifier_data",
-                                        ),
-                                        state.conf.contracts.pp_verifier,
-                                        id,
-                                        &state,
-                                    )
-                                    .await
-                                    {
-                                        Some(pfp) => vec![Token::String(pfp)],
-                                        None => {
-                                            return get_error(
-                                                "No profile picture specified for this domain"
-                                                    .to_string(),
-                                            )
-                                        }
+                        ResolverFunctionCall::Multicall(calls) => {
+                            // each call is answered on its own, a failing one returns empty bytes
+                            let results = join_all(calls.into_iter().map(|call| {
+                                resolve_call(&state, &provider, id, root_domain, call)
+                            }))
+                            .await;
+                            let results = results
+                                .into_iter()
+                                .map(|result| match result {
+                                    Ok(payload) => Token::Bytes(ethers::abi::encode(&payload)),
+                                    Err(e) => {
+                                        logger.warning(format!("Multicall call failed: {}", e));
+                                        Token::Bytes(Vec::new())
                                     }
+                                })
+                                .collect();
+                            vec![Token::Array(results)]
+                        }
+                        call => {
+                            match resolve_call(&state, &provider, id, root_domain, call).await {
+                                Ok(payload) => payload,
+                                Err(e) => return e.into_response(),
+                            }
+                        }
+                    };
+
+                    signed_response(&state, &encoded_data, &sender, payload)
+                }
+                Err(e) => {
+                    ApiError::Upstream(format!("Error fetching identity : {}", e)).into_response()
+                }
+            }
+        }
+        Err(e) => ApiError::Validation(format!("Error decoding data: {:?}", e)).into_response(),
+    }
+}
+
+// Signs the ABI encoded payload for the resolver contract, as expected by its callback
+fn signed_response(
+    state: &Arc<AppState>,
+    encoded_data: &str,
+    sender: &str,
+    payload: Vec<Token>,
+) -> Response {
+    let ttl = 3600;
+    let expires = chrono::Utc::now().timestamp() as u64 + ttl;
+    let request_payload = match hex::decode(encoded_data.trim_start_matches("0x")) {
+        Ok(request_payload) => request_payload,
+        Err(e) => return ApiError::Validation(format!("Invalid data: {}", e)).into_response(),
+    };
+    let data = ethers::abi::encode(&payload);
+    let request_hash = keccak256(request_payload).to_vec();
+    let result_hash = keccak256(&data).to_vec();
+
+    // Return signature
+    let wallet = match LocalWallet::from_str(state.conf.evm.private_key.as_str()) {
+        Ok(wallet) => wallet,
+        Err(e) => return ApiError::Internal(format!("Invalid signing key: {}", e)).into_response(),
+    };
+
+    match sign_message(wallet, sender, expires, request_hash, result_hash, data) {
+        Ok(res) => (
+            StatusCode::OK,
+            Json(json!({
+                "data": res
+            })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+// Answers a single resolver call for the identity behind the queried name
+async fn resolve_call(
+    state: &Arc<AppState>,
+    provider: &JsonRpcClient<HttpTransport>,
+    id: FieldElement,
+    root_domain: &[&str],
+    resolver_function_call: ResolverFunctionCall,
+) -> Result<Vec<Token>, ApiError> {
+    let logger = &state.logger;
+    let payload: Vec<Token> = match resolver_function_call {
+        ResolverFunctionCall::Text(_alt_hash, record) => {
+            match record.as_str() {
+                "avatar" => {
+                    match get_profile_picture(
+                        &state.conf,
+                        provider,
+                        state
+                            .starknetid_db
+                            .collection::<mongodb::bson::Document>("id_verifier_data"),
+                        state.conf.contracts.pp_verifier,
+                        id,
+                        state,
+                    )
+                    .await
+                    {
+                        Some(pfp) => vec![Token::String(pfp)],
+                        None => {
+                            return Err(ApiError::NotFound(
+                                "No profile picture specified for this domain".to_string(),
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    // we check if this data was added through a verifier
+                    // let record_config = state.conf.evm_records_verifiers.get(&record).unwrap();
+                    match state.conf.evm_records_verifiers.get(&record) {
+                        Some(record_config) => {
+                            let record_data =
+                                get_verifier_data(state, provider, id, &record, record_config)
+                                    .await;
+                            match record_data {
+                                Some(record_data) => {
+                                    vec![Token::String(record_data)]
                                 }
-                                _ => {
-                                    // we check if this data was added through a verifier
-                                    // let record_config = state.conf.evm_records_verifiers.get(&record).unwrap();
-                                    match state.conf.evm_records_verifiers.get(&record) {
-                                        Some(record_config) => {
-                                            let record_data = get_verifier_data(
-                                                &state,
-                                                &provider,
-                                                id,
-                                                record_config,
-                                            )
-                                            .await;
-                                            match record_data {
-                                                Some(record_data) => {
-                                                    vec![Token::String(record_data)]
-                                                }
-                                                None => {
-                                                    return get_error(format!(
-                                                        "No data found for record: {}",
-                                                        record
-                                                    ));
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            // if not we fetch user data for this record
-                                            // existing records : header (image url), display, name, url, description, email, mail, notice, location, phone
-                                            match get_unbounded_user_data(
-                                                &state, &provider, id, &record,
-                                            )
-                                            .await
-                                            {
-                                                Some(data) => vec![Token::String(data)],
-                                                None => {
-                                                    return get_error(format!(
-                                                        "No data found for record: {}",
-                                                        record
-                                                    ));
-                                                }
-                                            }
-                                        }
-                                    }
+                                None => {
+                                    return Err(ApiError::NotFound(format!(
+                                        "No data found for record: {}",
+                                        record
+                                    )));
                                 }
                             }
                         }
-                        ResolverFunctionCall::AddrMultichain(_bf, chain) => {
-                            logger.info(format!("AddrMultichain for chain: {:?}", chain));
-
-                            // EVM chains have an id >=  0x80000000 (2147483648)
-                            if chain >= 2147483648 {
-                                if chain == 2147492652 {
-                                    // Starknet chain id, we fetch the user address from the domain
-                                    print!("Fetch Starknet address");
-                                    match domain_to_address(
-                                        &provider,
-                                        state.conf.contracts.naming,
-                                        encoded_domain,
-                                        &state,
-                                    )
-                                    .await
-                                    {
-                                        Some(addr) => {
-                                            let hex_addr = to_hex(&addr);
-                                            let trimmed_hex_addr =
-                                                hex_addr.trim_start_matches("0x");
-                                            let bytes =
-                                                ethers::utils::hex::decode(trimmed_hex_addr)
-                                                    .map_err(|err| {
-                                                        get_error(format!(
-                                                            "Invalid Structure: {}",
-                                                            err
-                                                        ))
-                                                    })
-                                                    .unwrap();
-                                            vec![Token::Bytes(bytes)]
-                                        }
-                                        None => {
-                                            return get_error(
-                                                "No starknet address specified for this domain"
-                                                    .to_string(),
-                                            );
-                                        }
-                                    }
-                                } else {
-                                    // evm chain
-                                    match state.conf.evm_networks.get(&chain) {
-                                        Some(field_name) => {
-                                            match get_user_data_multicall(
-                                                &provider,
-                                                &state,
-                                                id,
-                                                vec![*field_name, *EVM_ADDRESS],
-                                            )
-                                            .await
-                                            {
-                                                Some(addr) => {
-                                                    let hex_addr = to_eth_hex(&addr);
-                                                    let trimmed_hex_addr =
-                                                        hex_addr.trim_start_matches("0x");
-                                                    let bytes = ethers::utils::hex::decode(
-                                                        trimmed_hex_addr,
-                                                    )
-                                                    .map_err(|err| {
-                                                        get_error(format!(
-                                                            "Invalid Structure: {}",
-                                                            err
-                                                        ))
-                                                    })
-                                                    .unwrap();
-                                                    vec![Token::Bytes(bytes)]
-                                                }
-                                                None => {
-                                                    return get_error(
-                                                        "No evm address specified for this domain"
-                                                            .to_string(),
-                                                    );
-                                                }
-                                            }
-                                        }
-                                        None => {
-                                            // we will just query evm-address field
-                                            match get_user_data(
-                                                &provider,
-                                                state.conf.contracts.starknetid,
-                                                id,
-                                                *EVM_ADDRESS,
-                                                &state,
-                                            )
-                                            .await
-                                            {
-                                                Some(addr) => {
-                                                    let hex_addr = to_eth_hex(&addr);
-                                                    let trimmed_hex_addr =
-                                                        hex_addr.trim_start_matches("0x");
-                                                    let bytes = ethers::utils::hex::decode(
-                                                        trimmed_hex_addr,
-                                                    )
-                                                    .map_err(|err| {
-                                                        get_error(format!(
-                                                            "Invalid Structure: {}",
-                                                            err
-                                                        ))
-                                                    })
-                                                    .unwrap();
-                                                    vec![Token::Bytes(bytes)]
-                                                }
-                                                None => {
-                                                    return get_error(
-                                                        "No evm address specified for this domain"
-                                                            .to_string(),
-                                                    );
-                                                }
-                                            }
-                                        }
-                                    }
+                        None => {
+                            // if not we fetch user data for this record
+                            // existing records : header (image url), display, name, url, description, email, mail, notice, location, phone
+                            match get_unbounded_user_data(state, provider, id, &record).await {
+                                Some(data) => vec![Token::String(data)],
+                                None => {
+                                    return Err(ApiError::NotFound(format!(
+                                        "No data found for record: {}",
+                                        record
+                                    )));
                                 }
-                            } else {
-                                return get_error(format!("Chain not implemented: {}", chain));
                             }
                         }
-                        ResolverFunctionCall::Addr(_bf) => {
+                    }
+                }
+            }
+        }
+        ResolverFunctionCall::AddrMultichain(_bf, chain) => {
+            logger.info(format!("AddrMultichain for chain: {:?}", chain));
+
+            // EVM chains have an id >=  0x80000000 (2147483648)
+            if chain >= 2147483648 {
+                if chain == 2147492652 {
+                    // Starknet chain id, we fetch the user address from the domain
+                    let domain = format!("{}.stark", root_domain.join("."));
+                    // the resolver errors are surfaced, only a missing address is a not found
+                    let resolution = state
+                        .resolvers
+                        .resolve(state, &domain, None)
+                        .await
+                        .map_err(|err| match err {
+                            ResolutionError::NotFound(_) => ApiError::NotFound(
+                                "No starknet address specified for this domain".to_string(),
+                            ),
+                            err => ApiError::from(err),
+                        })?;
+                    let addr = FieldElement::from_hex_be(&resolution.addr).map_err(|err| {
+                        ApiError::Internal(format!("Invalid starknet address: {}", err))
+                    })?;
+                    let hex_addr = to_hex(&addr);
+                    let trimmed_hex_addr = hex_addr.trim_start_matches("0x");
+                    let bytes = ethers::utils::hex::decode(trimmed_hex_addr)
+                        .map_err(|err| ApiError::Internal(format!("Invalid Structure: {}", err)))?;
+                    vec![Token::Bytes(bytes)]
+                } else {
+                    // evm chain
+                    match state.conf.evm_networks.get(&chain) {
+                        Some(field_name) => {
                             match get_user_data_multicall(
-                                &provider,
-                                &state,
+                                provider,
+                                state,
                                 id,
-                                vec![*ETHEREUM, *EVM_ADDRESS],
+                                vec![*field_name, *EVM_ADDRESS],
                             )
                             .await
                             {
                                 Some(addr) => {
-                                    let eth_addr = to_eth_hex(&addr);
-                                    let eth_addr = eth_addr
-                                        .parse::<H160>()
-                                        .map_err(|_| "Failed to parse address".to_string())
-                                        .unwrap();
-                                    vec![Token::Address(eth_addr)]
+                                    let hex_addr = to_eth_hex(&addr);
+                                    let trimmed_hex_addr = hex_addr.trim_start_matches("0x");
+                                    let bytes = ethers::utils::hex::decode(trimmed_hex_addr)
+                                        .map_err(|err| {
+                                            ApiError::Internal(format!(
+                                                "Invalid Structure: {}",
+                                                err
+                                            ))
+                                        })?;
+                                    vec![Token::Bytes(bytes)]
                                 }
                                 None => {
-                                    return get_error(
+                                    return Err(ApiError::NotFound(
                                         "No evm address specified for this domain".to_string(),
-                                    );
+                                    ));
                                 }
                             }
                         }
-                        _ => {
-                            logger.warning(format!("Unimplemented Method"));
-                            Vec::new()
+                        None => {
+                            // we will just query evm-address field
+                            match get_user_data(
+                                provider,
+                                state.conf.contracts.starknetid,
+                                id,
+                                *EVM_ADDRESS,
+                                state,
+                            )
+                            .await
+                            {
+                                Some(addr) => {
+                                    let hex_addr = to_eth_hex(&addr);
+                                    let trimmed_hex_addr = hex_addr.trim_start_matches("0x");
+                                    let bytes = ethers::utils::hex::decode(trimmed_hex_addr)
+                                        .map_err(|err| {
+                                            ApiError::Internal(format!(
+                                                "Invalid Structure: {}",
+                                                err
+                                            ))
+                                        })?;
+                                    vec![Token::Bytes(bytes)]
+                                }
+                                None => {
+                                    return Err(ApiError::NotFound(
+                                        "No evm address specified for this domain".to_string(),
+                                    ));
+                                }
+                            }
                         }
-                    };
-
-                    let ttl = 3600;
-                    let expires = chrono::Utc::now().timestamp() as u64 + ttl;
-                    let request_payload =
-                        hex::decode(encoded_data.trim_start_matches("0x")).unwrap();
-                    let data = ethers::abi::encode(&payload);
-                    let request_hash = keccak256(request_payload).to_vec();
-                    let result_hash = keccak256(&data).to_vec();
-
-                    // Return signature
-                    let wallet: LocalWallet =
-                        LocalWallet::from_str(state.conf.evm.private_key.as_str()).unwrap();
-
-                    match sign_message(wallet, &sender, expires, request_hash, result_hash, data) {
-                        Ok(res) => (
-                            StatusCode::OK,
-                            Json(json!({
-                                "data": res
-                            })),
-                        )
-                            .into_response(),
-                        Err(e) => get_error(format!("Error signing message : {}", e)),
                     }
                 }
-                Err(e) => get_error(format!("Error fetching identity : {}", e)),
+            } else {
+                return Err(ApiError::Validation(format!(
+                    "Chain not implemented: {}",
+                    chain
+                )));
             }
         }
-        Err(e) => get_error(format!("Error decoding data: {:?}", e)),
-    }
+        ResolverFunctionCall::Addr(_bf) => {
+            match get_user_data_multicall(provider, state, id, vec![*ETHEREUM, *EVM_ADDRESS]).await
+            {
+                Some(addr) => {
+                    let eth_addr = to_eth_hex(&addr).parse::<H160>().map_err(|_| {
+                        ApiError::Validation(
+                            "The evm address of this domain is not a valid address".to_string(),
+                        )
+                    })?;
+                    vec![Token::Address(eth_addr)]
+                }
+                None => {
+                    return Err(ApiError::NotFound(
+                        "No evm address specified for this domain".to_string(),
+                    ));
+                }
+            }
+        }
+        ResolverFunctionCall::ContentHash(_bf) => {
+            let record = get_unbounded_user_data(state, provider, id, "contenthash").await;
+            content_hash_tokens(record.as_deref())
+        }
+        ResolverFunctionCall::PubKey(_bf) => {
+            let record = get_unbounded_user_data(state, provider, id, "pubkey").await;
+            pubkey_tokens(record.as_deref())
+        }
+        ResolverFunctionCall::Abi(_bf, content_types) => {
+            let record = get_unbounded_user_data(state, provider, id, "abi").await;
+            abi_tokens(record.as_deref(), content_types)
+        }
+        ResolverFunctionCall::Name(_bf) => {
+            // name() is only meaningful for reverse nodes, forward nodes have no name
+            Vec::new()
+        }
+        _ => {
+            logger.warning(format!("Unimplemented Method"));
+            Vec::new()
+        }
+    };
+
+    Ok(payload)
 }
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use mongodb::bson::{doc, Document};
+use starknet::core::types::FieldElement;
+
+use crate::{
+    endpoints::crosschain::ethereum::resolve::{ETHEREUM, EVM_ADDRESS},
+    errors::ApiError,
+    models::AppState,
+    utils::to_hex,
+};
+
+// coin type of the Ethereum mainnet, used by `addr.reverse` names
+const ETHEREUM_COIN_TYPE: u64 = 60;
+
+/// Parses an ENS reverse name, `<address>.addr.reverse` for Ethereum or
+/// `<address>.<coin type in hex>.reverse` for other EVM chains (ENSIP-19),
+/// into the queried EVM address and its coin type.
+pub fn parse_reverse_name(name: &str) -> Option<(FieldElement, u64)> {
+    let labels: Vec<&str> = name.split('.').collect();
+    let (address, namespace) = match labels.as_slice() {
+        [address, namespace, "reverse"] => (address, namespace),
+        _ => return None,
+    };
+    if address.len() != 40 || !address.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let coin_type = match *namespace {
+        "addr" => ETHEREUM_COIN_TYPE,
+        namespace => u64::from_str_radix(namespace, 16).ok()?,
+    };
+    Some((FieldElement::from_hex_be(address).ok()?, coin_type))
+}
+
+/// Returns the domain of the identity holding the given EVM address for this coin type
+/// in the data of a reverse resolving verifier, main identities coming first. The user
+/// data isn't used as anyone can write any address in their own identity.
+pub async fn reverse_lookup(
+    state: &Arc<AppState>,
+    address: FieldElement,
+    coin_type: u64,
+) -> Result<String, ApiError> {
+    let verifiers: Vec<String> = state
+        .conf
+        .reverse_resolving
+        .verifier_contracts
+        .iter()
+        .map(to_hex)
+        .collect();
+    if verifiers.is_empty() {
+        return Err(ApiError::NotFound(
+            "Reverse resolution is not enabled".to_string(),
+        ));
+    }
+
+    // the same fields as the forward resolution of this coin type
+    let fields = if coin_type == ETHEREUM_COIN_TYPE {
+        vec![*ETHEREUM, *EVM_ADDRESS]
+    } else {
+        match state.conf.evm_networks.get(&coin_type) {
+            Some(field) => vec![*field, *EVM_ADDRESS],
+            None => vec![*EVM_ADDRESS],
+        }
+    };
+    let fields: Vec<String> = fields.iter().map(to_hex).collect();
+
+    let pipeline = vec![
+        doc! { "$match": {
+            "_cursor.to": null,
+            "verifier": { "$in": verifiers },
+            "field": { "$in": fields },
+            "data": to_hex(&address),
+        } },
+        doc! { "$lookup": {
+            "from": "id_owners",
+            "let": { "id": "$id" },
+            "pipeline": [
+                doc! { "$match": {
+                    "_cursor.to": null,
+                    "$expr": { "$eq": ["$id", "$$id"] }
+                } }
+            ],
+            "as": "identity"
+        } },
+        doc! { "$unwind": "$identity" },
+        doc! { "$lookup": {
+            "from": "domains",
+            "let": { "id": "$id" },
+            "pipeline": [
+                doc! { "$match": {
+                    "_cursor.to": null,
+                    "$expr": { "$eq": ["$id", "$$id"] }
+                } }
+            ],
+            "as": "domain"
+        } },
+        doc! { "$unwind": "$domain" },
+        doc! { "$sort": { "identity.main": -1 } },
+        doc! { "$limit": 1 },
+        doc! { "$project": { "domain": "$domain.domain" } },
+    ];
+
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("id_verifier_data")
+        .aggregate(pipeline, None)
+        .await?;
+    match cursor.next().await {
+        Some(Ok(doc)) => doc
+            .get_str("domain")
+            .map(|domain| domain.to_string())
+            .map_err(|e| ApiError::Internal(format!("Malformed document: {}", e))),
+        Some(Err(e)) => Err(e.into()),
+        None => Err(ApiError::NotFound(format!(
+            "No domain found for address {}",
+            to_hex(&address)
+        ))),
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, DateTime as BsonDateTime, Document},
+    options::{FindOptions, IndexOptions, UpdateOptions},
+    Collection, IndexModel,
+};
+use starknet::core::types::FieldElement;
+
+use crate::{models::AppState, utils::to_hex};
+
+// the names are written by the API, so they are kept out of the indexed database
+fn social_names(state: &Arc<AppState>) -> Collection<Document> {
+    state.sales_db.collection::<Document>("social_names")
+}
+
+/// Indexes of the name lookups, unique by record and social id so that concurrent
+/// first lookups can't store the same name twice, and of the background refresh, which
+/// goes through the least recently checked names
+pub fn social_names_indexes() -> Vec<IndexModel> {
+    vec![
+        IndexModel::builder()
+            .keys(doc! { "record": 1, "social_id": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build(),
+        IndexModel::builder().keys(doc! { "checked_at": 1 }).build(),
+    ]
+}
+
+pub async fn create_social_names_indexes(state: &Arc<AppState>) -> mongodb::error::Result<()> {
+    social_names(state)
+        .create_indexes(social_names_indexes(), None)
+        .await?;
+    Ok(())
+}
+
+// stored names fetched before this date are expired
+fn expired_before(state: &Arc<AppState>) -> BsonDateTime {
+    let ttl = (state.conf.social_names.ttl * 1000.0) as i64;
+    BsonDateTime::from_millis(BsonDateTime::now().timestamp_millis() - ttl)
+}
+
+/// Returns the name of a verified social id. Names returned by the record handlers
+/// are stored in the `social_names` collection and fetched again once expired, the
+/// stored name is still returned while the handler fails.
+pub async fn get_social_name(
+    state: &Arc<AppState>,
+    record: &str,
+    social_id: FieldElement,
+) -> Result<String> {
+    if let Some(name) = state.record_handlers.cached_name(record, &social_id) {
+        return Ok(name);
+    }
+
+    let stored = match social_names(state)
+        .find_one(
+            doc! { "record": record, "social_id": to_hex(&social_id) },
+            None,
+        )
+        .await
+    {
+        Ok(stored) => stored.as_ref().and_then(parse_stored_name),
+        Err(e) => {
+            state
+                .logger
+                .warning(format!("Unable to read stored social name: {}", e));
+            None
+        }
+    };
+    if let Some((name, fetched_at)) = &stored {
+        if *fetched_at >= expired_before(state) {
+            return Ok(name.clone());
+        }
+    }
+
+    let fetched = state.record_handlers.execute(record, social_id).await;
+    if let Ok(name) = &fetched {
+        store_name(state, record, social_id, Some(name)).await;
+    } else if let (Err(e), Some(_)) = (&fetched, &stored) {
+        state.logger.warning(format!(
+            "Serving stale {} name of {}: {:?}",
+            record,
+            to_hex(&social_id),
+            e
+        ));
+    }
+    fetched_or_stale(fetched, stored.map(|(name, _)| name))
+}
+
+/// Name and fetch date of a `social_names` row
+pub fn parse_stored_name(doc: &Document) -> Option<(String, BsonDateTime)> {
+    let name = doc.get_str("name").ok()?.to_string();
+    let fetched_at = *doc.get_datetime("fetched_at").ok()?;
+    Some((name, fetched_at))
+}
+
+/// The name returned by the record handler, or the stored one while the handler fails
+pub fn fetched_or_stale(fetched: Result<String>, stored: Option<String>) -> Result<String> {
+    match (fetched, stored) {
+        (Ok(name), _) => Ok(name),
+        (Err(_), Some(name)) => Ok(name),
+        (Err(e), None) => Err(e),
+    }
+}
+
+/// Update of a `social_names` row after a fetch, a failed fetch only records the date
+/// of the attempt so that the row isn't retried on every refresh
+pub fn name_update(name: Option<&str>, now: BsonDateTime) -> Document {
+    match name {
+        Some(name) => doc! { "$set": { "name": name, "fetched_at": now, "checked_at": now } },
+        None => doc! { "$set": { "checked_at": now } },
+    }
+}
+
+/// Filter of the rows refreshed in the background, checked before `expired_before`
+pub fn expired_filter(expired_before: BsonDateTime) -> Document {
+    doc! { "checked_at": { "$lt": expired_before } }
+}
+
+// Stores a fetched name, or only the date of the attempt when the handler failed
+async fn store_name(
+    state: &Arc<AppState>,
+    record: &str,
+    social_id: FieldElement,
+    name: Option<&str>,
+) {
+    let result = social_names(state)
+        .update_one(
+            doc! { "record": record, "social_id": to_hex(&social_id) },
+            name_update(name, BsonDateTime::now()),
+            UpdateOptions::builder().upsert(name.is_some()).build(),
+        )
+        .await;
+    if let Err(e) = result {
+        state
+            .logger
+            .warning(format!("Unable to store social name: {}", e));
+    }
+}
+
+/// Fetches again the expired stored names, least recently checked first, so that
+/// lookups rarely have to wait for a record handler.
+pub async fn refresh_social_names(state: &Arc<AppState>) {
+    let options = FindOptions::builder()
+        .sort(doc! { "checked_at": 1 })
+        .limit(state.conf.social_names.refresh_batch)
+        .build();
+    let rows = match social_names(state)
+        .find(expired_filter(expired_before(state)), options)
+        .await
+    {
+        Ok(cursor) => cursor.try_collect::<Vec<Document>>().await,
+        Err(e) => Err(e),
+    };
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            state
+                .logger
+                .warning(format!("Unable to find expired social names: {}", e));
+            return;
+        }
+    };
+
+    for row in rows {
+        let social_id = row
+            .get_str("social_id")
+            .ok()
+            .and_then(|social_id| FieldElement::from_hex_be(social_id).ok());
+        let (record, social_id) = match (row.get_str("record"), social_id) {
+            (Ok(record), Some(social_id)) => (record, social_id),
+            _ => continue,
+        };
+        match state.record_handlers.execute(record, social_id).await {
+            Ok(name) => store_name(state, record, social_id, Some(&name)).await,
+            Err(e) => {
+                state.logger.warning(format!(
+                    "Unable to refresh {} name of {}: {:?}",
+                    record,
+                    to_hex(&social_id),
+                    e
+                ));
+                store_name(state, record, social_id, None).await;
+            }
+        }
+    }
+}
@@ -25,6 +25,7 @@ use std::fmt::Write;
 use crate::{
     config::Config,
     endpoints::uri::VerifierData,
+    errors::ApiError,
     models::AppState,
     utils::{fetch_image_url, parse_base64_image, to_hex},
     Arc,
@@ -39,19 +40,26 @@ lazy_static! {
         "0x00000000000000000000000000000000000000000000006e66745f70705f6964".to_string();
 }
 
-pub fn decode_ens(name: &str) -> String {
+/// Decodes a DNS wire format name, each label is prefixed by its length
+/// and the name ends with an empty label.
+pub fn decode_ens(name: &[u8]) -> Result<String> {
     let mut labels: Vec<&str> = Vec::new();
     let mut idx = 0;
     loop {
-        let len = name.as_bytes()[idx] as usize;
+        let len = *name
+            .get(idx)
+            .ok_or_else(|| anyhow!("Name is not terminated"))? as usize;
         if len == 0 {
             break;
         }
-        labels.push(&name[idx + 1..idx + len + 1]);
+        let label = name
+            .get(idx + 1..idx + len + 1)
+            .ok_or_else(|| anyhow!("Label longer than the name"))?;
+        labels.push(std::str::from_utf8(label)?);
         idx += len + 1;
     }
 
-    labels.join(".")
+    Ok(labels.join("."))
 }
 
 pub fn to_eth_hex(felt: &FieldElement) -> String {
@@ -88,9 +96,7 @@ pub fn decode_data(data: &str) -> Result<(String, ResolverFunctionCall)> {
         .into_bytes()
         .ok_or_else(|| anyhow!("Invalid bytes"))?;
 
-    let name = String::from_utf8(dns_encoded_name)?;
-
-    let name = decode_ens(&name);
+    let name = decode_ens(&dns_encoded_name)?;
 
     let rest_of_the_data = decoded[1]
         .clone()
@@ -109,27 +115,31 @@ pub fn sign_message(
     request_hash: Vec<u8>,
     result_hash: Vec<u8>,
     data: Vec<u8>,
-) -> Result<String> {
+) -> Result<String, ApiError> {
+    let sender = sender
+        .parse::<H160>()
+        .map_err(|e| ApiError::Validation(format!("Invalid sender address: {}", e)))?;
     let encoded = ethers::abi::encode_packed(&[
         Token::Uint(U256::from(0x1900)),
-        Token::Address(
-            sender
-                .parse::<H160>()
-                .expect("Failed to parse sender address"),
-        ),
+        Token::Address(sender),
         Token::FixedBytes(U64::from(expires).0[0].to_be_bytes().to_vec()),
         Token::FixedBytes(request_hash),
         Token::FixedBytes(result_hash),
     ])
-    .unwrap();
+    .map_err(|e| ApiError::Internal(format!("Unable to encode the message: {}", e)))?;
 
     let message_hash = keccak256(encoded);
 
-    let signature: ethers::types::Signature = wallet.sign_hash(message_hash.into()).unwrap();
+    let signature: ethers::types::Signature = wallet
+        .sign_hash(message_hash.into())
+        .map_err(|e| ApiError::Internal(format!("Error signing message: {}", e)))?;
 
     let signature_r = signature.r.encode();
     let signature_s = signature.s.encode();
-    let signature_v = vec![signature.v.try_into().unwrap()];
+    let signature_v = vec![signature
+        .v
+        .try_into()
+        .map_err(|_| ApiError::Internal("Invalid signature recovery id".to_string()))?];
 
     let signature = [signature_r, signature_s, signature_v].concat();
 
@@ -235,45 +245,6 @@ pub async fn get_user_data_multicall(
     }
 }
 
-pub async fn domain_to_address(
-    provider: &JsonRpcClient<HttpTransport>,
-    naming_contract: FieldElement,
-    encoded_domain: Vec<FieldElement>,
-    state: &Arc<AppState>,
-) -> Option<FieldElement> {
-    let logger = &state.logger;
-    let mut calldata: Vec<FieldElement> = vec![FieldElement::from(encoded_domain.len())];
-    calldata.extend(encoded_domain);
-    calldata.push(FieldElement::ZERO);
-    let call_result = provider
-        .call(
-            FunctionCall {
-                contract_address: naming_contract,
-                entry_point_selector: selector!("domain_to_address"),
-                calldata,
-            },
-            BlockId::Tag(BlockTag::Latest),
-        )
-        .await;
-
-    match call_result {
-        Ok(result) => {
-            logger.info(format!("domain_to_address result: {:?}", result));
-            if result[0] != FieldElement::ZERO {
-                return Some(result[0]);
-            }
-            None
-        }
-        Err(e) => {
-            logger.severe(format!(
-                "Error fetching starknet address for domain : {}",
-                e
-            ));
-            None
-        }
-    }
-}
-
 // Profile picture metadata utils
 pub async fn get_profile_picture(
     config: &Config,
@@ -1,2 +1,4 @@
+pub mod claims;
 pub mod ethereum;
 pub mod solana;
+pub mod source_naming;
@@ -1,2 +1,3 @@
 pub mod claim;
 pub mod claim_ledger;
+pub mod sns;
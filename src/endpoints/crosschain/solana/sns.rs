@@ -0,0 +1,344 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
+
+use axum::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use futures::future::join_all;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::{hash::hashv, pubkey::Pubkey, transaction::Transaction};
+use starknet::core::types::FieldElement;
+
+use crate::{
+    config::{Config, Solana},
+    endpoints::crosschain::source_naming::SourceNamingSystem,
+    errors::ApiError,
+    models::AppState,
+};
+
+// prefix of the hashed names of the SPL name service
+const HASH_PREFIX: &str = "SPL Name Service";
+// timeout of each RPC request, in seconds
+const RPC_TIMEOUT: u64 = 10;
+
+lazy_static::lazy_static! {
+    static ref NAME_PROGRAM_ID: Pubkey =
+        Pubkey::from_str("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX").unwrap();
+    // parent of every .sol domain
+    static ref SOL_TLD_AUTHORITY: Pubkey =
+        Pubkey::from_str("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx").unwrap();
+    static ref NAME_TOKENIZER_ID: Pubkey =
+        Pubkey::from_str("nftD3vbNkNqfj2Sd3HZwbpw4BxxKWr4AjGb9X38JeZk").unwrap();
+    // "sol subdomain"
+    static ref SOL_SUBDOMAIN_STR: FieldElement = FieldElement::from_dec_str("9145722242464647959622012987758").unwrap();
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<Value>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Returns the label of a `.sol` domain, subdomains are not supported
+pub fn parse_sol_domain(domain: &str) -> Result<&str, ApiError> {
+    let name = domain.strip_suffix(".sol").unwrap_or(domain);
+    if name.is_empty() || name.contains('.') {
+        return Err(ApiError::Validation(format!(
+            "Invalid SNS domain: {}",
+            domain
+        )));
+    }
+    Ok(name)
+}
+
+/// Address of the name registry account of a `.sol` domain label
+pub fn get_domain_key(name: &str) -> Pubkey {
+    let hashed_name = hashv(&[HASH_PREFIX.as_bytes(), name.as_bytes()]);
+    let name_class = Pubkey::default();
+    Pubkey::find_program_address(
+        &[
+            hashed_name.as_ref(),
+            name_class.as_ref(),
+            SOL_TLD_AUTHORITY.as_ref(),
+        ],
+        &NAME_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Reads the owner of a name registry account (after its parent) or of an SPL token
+/// account (after its mint), both stored at the same offset.
+pub fn parse_owner(data: &[u8]) -> Option<Pubkey> {
+    let owner: [u8; 32] = data.get(32..64)?.try_into().ok()?;
+    Some(Pubkey::from(owner))
+}
+
+// Owner of the name registry of tokenized domains, the real owner holds the name NFT
+fn tokenizer_central_state() -> Pubkey {
+    Pubkey::find_program_address(&[NAME_TOKENIZER_ID.as_ref()], &NAME_TOKENIZER_ID).0
+}
+
+fn tokenized_name_mint(domain_key: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"tokenized_name", domain_key.as_ref()],
+        &NAME_TOKENIZER_ID,
+    )
+    .0
+}
+
+async fn rpc_call(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    method: &str,
+    params: Value,
+) -> Result<Value, String> {
+    let response = client
+        .post(rpc_url)
+        .json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("{}: {}", method, e))?
+        .json::<RpcResponse>()
+        .await
+        .map_err(|e| format!("{}: invalid response: {}", method, e))?;
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(format!("{}: {} ({})", method, error.message, error.code)),
+        (Some(result), None) => Ok(result),
+        (None, None) => Err(format!("{}: empty response", method)),
+    }
+}
+
+// Data of an account, None if it doesn't exist
+async fn get_account_data(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    account: &Pubkey,
+) -> Result<Option<Vec<u8>>, String> {
+    let result = rpc_call(
+        client,
+        rpc_url,
+        "getAccountInfo",
+        json!([account.to_string(), { "encoding": "base64", "commitment": "finalized" }]),
+    )
+    .await?;
+    let value = match result.get("value") {
+        Some(Value::Null) | None => return Ok(None),
+        Some(value) => value,
+    };
+    let data = value
+        .get("data")
+        .and_then(|data| data.get(0))
+        .and_then(|data| data.as_str())
+        .ok_or_else(|| format!("getAccountInfo: no data for {}", account))?;
+    STANDARD
+        .decode(data)
+        .map(Some)
+        .map_err(|e| format!("getAccountInfo: invalid data for {}: {}", account, e))
+}
+
+// Holder of the name NFT of a tokenized domain
+async fn get_nft_holder(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    domain_key: &Pubkey,
+) -> Result<Option<Pubkey>, String> {
+    let mint = tokenized_name_mint(domain_key);
+    let result = rpc_call(
+        client,
+        rpc_url,
+        "getTokenLargestAccounts",
+        json!([mint.to_string(), { "commitment": "finalized" }]),
+    )
+    .await?;
+    let token_account = result
+        .get("value")
+        .and_then(|value| value.as_array())
+        .and_then(|accounts| {
+            accounts
+                .iter()
+                .find(|account| account.get("amount").and_then(|a| a.as_str()) == Some("1"))
+        })
+        .and_then(|account| account.get("address"))
+        .and_then(|address| address.as_str())
+        .and_then(|address| Pubkey::from_str(address).ok());
+    let token_account = match token_account {
+        Some(token_account) => token_account,
+        None => return Ok(None),
+    };
+    match get_account_data(client, rpc_url, &token_account).await? {
+        Some(data) => Ok(parse_owner(&data)),
+        None => Ok(None),
+    }
+}
+
+// Owner of a domain according to a single RPC endpoint, None if it isn't registered
+async fn get_owner_from(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    domain_key: &Pubkey,
+) -> Result<Option<Pubkey>, String> {
+    let data = match get_account_data(client, rpc_url, domain_key).await? {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    let owner = parse_owner(&data).ok_or_else(|| "Invalid name registry account".to_string())?;
+    if owner == Pubkey::default() {
+        // released name
+        Ok(None)
+    } else if owner == tokenizer_central_state() {
+        get_nft_holder(client, rpc_url, domain_key).await
+    } else {
+        Ok(Some(owner))
+    }
+}
+
+/// Returns the owner of a `.sol` domain, read from its name registry account on every
+/// configured RPC endpoint. Most endpoints must answer and all answers must agree.
+pub async fn get_domain_owner(conf: &Solana, domain: &str) -> Result<Pubkey, ApiError> {
+    let domain_key = get_domain_key(parse_sol_domain(domain)?);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(RPC_TIMEOUT))
+        .build()
+        .unwrap_or_default();
+
+    let rpc_urls: Vec<&String> = std::iter::once(&conf.rpc_url)
+        .chain(conf.fallback_rpc_urls.iter())
+        .collect();
+    let results = join_all(
+        rpc_urls
+            .iter()
+            .map(|rpc_url| get_owner_from(&client, rpc_url, &domain_key)),
+    )
+    .await;
+
+    let mut answers: HashMap<Option<Pubkey>, usize> = HashMap::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(owner) => *answers.entry(owner).or_default() += 1,
+            Err(e) => errors.push(e),
+        }
+    }
+    if answers.len() > 1 {
+        return Err(ApiError::Upstream(format!(
+            "Solana RPC endpoints disagree on the owner of {}",
+            domain
+        )));
+    }
+    match answers.into_iter().next() {
+        Some((owner, count)) if count * 2 > rpc_urls.len() => owner
+            .ok_or_else(|| ApiError::NotFound(format!("SNS domain {} is not registered", domain))),
+        _ => Err(ApiError::Upstream(format!(
+            "Not enough Solana RPC endpoints answered: {}",
+            errors.join(", ")
+        ))),
+    }
+}
+
+/// SNS domains, claimed with an ed25519 signature of the claim message
+pub struct Sns;
+
+/// SNS domains, claimed with a signed Solana transaction whose third instruction holds
+/// the claim message, as Ledger devices can't sign raw messages
+pub struct SnsLedger;
+
+#[async_trait]
+impl SourceNamingSystem for Sns {
+    fn name(&self) -> &'static str {
+        "sns"
+    }
+
+    fn subdomain_str(&self) -> FieldElement {
+        *SOL_SUBDOMAIN_STR
+    }
+
+    fn private_key(&self, conf: &Config) -> FieldElement {
+        conf.solana.private_key
+    }
+
+    fn parse_domain<'a>(&self, domain: &'a str) -> Result<&'a str, ApiError> {
+        parse_sol_domain(domain)
+    }
+
+    async fn get_owner(&self, state: &Arc<AppState>, domain: &str) -> Result<String, ApiError> {
+        let owner = get_domain_owner(&state.conf.solana, domain).await?;
+        Ok(owner.to_string())
+    }
+
+    fn verify_signature(
+        &self,
+        owner: &str,
+        message: &str,
+        signature: &[u8],
+    ) -> Result<(), ApiError> {
+        let signature: [u8; 64] = signature
+            .try_into()
+            .map_err(|_| ApiError::Validation("Invalid signature length".to_string()))?;
+        let owner = Pubkey::from_str(owner)
+            .map_err(|e| ApiError::Internal(format!("Invalid SNS owner: {}", e)))?;
+        VerifyingKey::from_bytes(&owner.to_bytes())
+            .and_then(|key| key.verify(message.as_bytes(), &Signature::from_bytes(&signature)))
+            .map_err(|e| ApiError::Validation(format!("Signature verification failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl SourceNamingSystem for SnsLedger {
+    fn name(&self) -> &'static str {
+        Sns.name()
+    }
+
+    fn subdomain_str(&self) -> FieldElement {
+        Sns.subdomain_str()
+    }
+
+    fn private_key(&self, conf: &Config) -> FieldElement {
+        Sns.private_key(conf)
+    }
+
+    fn parse_domain<'a>(&self, domain: &'a str) -> Result<&'a str, ApiError> {
+        Sns.parse_domain(domain)
+    }
+
+    async fn get_owner(&self, state: &Arc<AppState>, domain: &str) -> Result<String, ApiError> {
+        Sns.get_owner(state, domain).await
+    }
+
+    fn verify_signature(
+        &self,
+        owner: &str,
+        message: &str,
+        signature: &[u8],
+    ) -> Result<(), ApiError> {
+        let invalid = || ApiError::Validation("Invalid signature.".to_string());
+        let transaction: Transaction = bincode::deserialize(signature)
+            .map_err(|_| ApiError::Validation("Invalid serialized transaction".to_string()))?;
+        let owner = Pubkey::from_str(owner)
+            .map_err(|e| ApiError::Internal(format!("Invalid SNS owner: {}", e)))?;
+
+        let inx = transaction
+            .message
+            .instructions
+            .get(2)
+            .ok_or_else(invalid)?;
+        // the transaction must be signed by the owner
+        if transaction.message.account_keys.first() != Some(&owner) {
+            return Err(invalid());
+        }
+        if std::str::from_utf8(&inx.data) != Ok(message) {
+            return Err(invalid());
+        }
+        transaction.verify().map_err(|_| invalid())
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    async_trait,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use starknet::core::{
+    crypto::{ecdsa_sign, pedersen_hash},
+    types::FieldElement,
+};
+use starknet_id::encode;
+
+use crate::{
+    config::Config,
+    endpoints::crosschain::claims::{reserve_claim, store_claim, CrosschainClaim},
+    errors::ApiError,
+    models::AppState,
+    utils::to_hex,
+};
+
+/// A naming system whose domains can be claimed as subdomains on Starknet, e.g.
+/// `domain.sol` as `domain.sol.stark`. It tells who owns a domain and how the owner
+/// signs the claim message, the Starknet signature is built by `handle_claim`.
+#[async_trait]
+pub trait SourceNamingSystem: Send + Sync {
+    /// Name of the naming system in the stored claims
+    fn name(&self) -> &'static str;
+
+    /// Shortstring of the Starknet root domain, signed with each claim
+    fn subdomain_str(&self) -> FieldElement;
+
+    /// Key signing the claims on Starknet
+    fn private_key(&self, conf: &Config) -> FieldElement;
+
+    /// Returns the label claimed on Starknet for a domain of this naming system
+    fn parse_domain<'a>(&self, domain: &'a str) -> Result<&'a str, ApiError>;
+
+    /// Returns the owner of a domain, as written in the claim message
+    async fn get_owner(&self, state: &Arc<AppState>, domain: &str) -> Result<String, ApiError>;
+
+    /// Checks that the claim message was signed by the owner of the domain
+    fn verify_signature(
+        &self,
+        owner: &str,
+        message: &str,
+        signature: &[u8],
+    ) -> Result<(), ApiError>;
+}
+
+pub struct ClaimRequest {
+    pub source_domain: String,
+    pub target_address: FieldElement,
+    // signature of the owner, in the scheme of the naming system
+    pub signature: Vec<u8>,
+    pub max_validity: u64,
+}
+
+/// Message signed by the owner of a domain to claim it on a Starknet address
+pub fn claim_message(
+    owner: &str,
+    source_domain: &str,
+    target_address: &FieldElement,
+    max_validity: u64,
+) -> String {
+    format!(
+        "{} allow claiming {} on starknet on {} at max validity timestamp {}",
+        owner,
+        source_domain,
+        to_hex(target_address),
+        max_validity
+    )
+}
+
+/// Checks the claim against the naming system then returns and stores the Starknet
+/// signature allowing the target address to mint the subdomain.
+pub async fn handle_claim(
+    state: &Arc<AppState>,
+    system: &dyn SourceNamingSystem,
+    endpoint: &str,
+    request: ClaimRequest,
+) -> Response {
+    // verify max_validity is not expired
+    if !is_valid_timestamp(request.max_validity) {
+        return ApiError::Validation("Signature expired".to_string()).into_response();
+    }
+
+    let name_encoded = match system.parse_domain(&request.source_domain).map(encode) {
+        Ok(Ok(name_encoded)) => name_encoded,
+        Ok(Err(_)) => {
+            return ApiError::Validation(format!(
+                "Domain can't be encoded: {}",
+                request.source_domain
+            ))
+            .into_response()
+        }
+        Err(e) => return e.into_response(),
+    };
+
+    let owner = match system.get_owner(state, &request.source_domain).await {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+
+    let message = claim_message(
+        &owner,
+        &request.source_domain,
+        &request.target_address,
+        request.max_validity,
+    );
+    if let Err(e) = system.verify_signature(&owner, &message, &request.signature) {
+        return e.into_response();
+    }
+
+    // a domain can only be claimed for one address at a time
+    let mut claim = CrosschainClaim::new(
+        system.name(),
+        endpoint,
+        &request.source_domain,
+        owner,
+        to_hex(&request.target_address),
+        request.max_validity,
+    );
+    let stark_max_validity = Utc::now() + Duration::hours(1);
+    let stark_max_validity_sec = stark_max_validity.timestamp();
+    if let Err(e) = reserve_claim(state, &claim, stark_max_validity_sec).await {
+        return e.into_response();
+    }
+
+    // Generate starknet signature
+
+    let hash = pedersen_hash(
+        &pedersen_hash(
+            &pedersen_hash(
+                &system.subdomain_str(),
+                &FieldElement::from(stark_max_validity_sec as u64),
+            ),
+            &name_encoded,
+        ),
+        &request.target_address,
+    );
+
+    match ecdsa_sign(&system.private_key(&state.conf), &hash) {
+        Ok(signature) => {
+            claim.signature = Some(vec![to_hex(&signature.r), to_hex(&signature.s)]);
+            claim.max_validity = Some(stark_max_validity_sec);
+            if let Err(e) = store_claim(state, &claim).await {
+                return e.into_response();
+            }
+            (
+                StatusCode::OK,
+                Json(json!({
+                    "r": signature.r,
+                    "s": signature.s,
+                    "max_validity": stark_max_validity_sec
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => ApiError::Internal(format!("Error while generating Starknet signature: {}", e))
+            .into_response(),
+    }
+}
+
+fn is_valid_timestamp(max_validity: u64) -> bool {
+    let now = SystemTime::now();
+
+    if let Ok(duration_since_epoch) = now.duration_since(UNIX_EPOCH) {
+        let current_timestamp = duration_since_epoch.as_secs();
+        current_timestamp < max_validity
+    } else {
+        false
+    }
+}
@@ -1,9 +1,8 @@
 use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
+    address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState, utils::to_hex,
 };
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -12,19 +11,34 @@ use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct StarknetIdData {
     starknet_id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct StarknetIdQuery {
-    verifier: FieldElement,
+    #[param(value_type = String)]
+    verifier: StarknetAddress,
+    #[param(value_type = String)]
     field: FieldElement,
+    #[param(value_type = String)]
     data: FieldElement,
 }
 
+#[utoipa::path(
+    get,
+    path = "/data_to_ids",
+    tag = "starknetid",
+    params(StarknetIdQuery),
+    responses(
+        (status = 200, description = "Success", body = StarknetIdData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/data_to_ids", crate::endpoints::data_to_ids)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -36,7 +50,7 @@ pub async fn handler(
     let document = ids_data
         .find_one(
             doc! {
-                "verifier": to_hex(&query.verifier),
+                "verifier": query.verifier.to_hex(),
                 "field": to_hex(&query.field),
                 "data": to_hex(&query.data),
                 "_cursor.to": null,
@@ -55,9 +69,10 @@ pub async fn handler(
                 let data = StarknetIdData { starknet_id };
                 (StatusCode::OK, headers, Json(data)).into_response()
             } else {
-                get_error("no tokenid associated to this data was found".to_string())
+                ApiError::NotFound("no tokenid associated to this data was found".to_string())
+                    .into_response()
             }
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -0,0 +1,252 @@
+use crate::{errors::ApiError, extractors::Query, models::AppState};
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    options::FindOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+// means "starknet"
+const STARKNET_FIELD: &str = "0x000000000000000000000000000000000000000000000000737461726b6e6574";
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DomainHistoryQuery {
+    domain: String,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HistoryChange {
+    Identity { id: Option<String> },
+    Owner { id: String, owner: Option<String> },
+    Resolver { resolver: Option<String> },
+    RevAddress { rev_address: Option<String> },
+    Expiry { expiry: Option<i64> },
+    Starknet { id: String, address: Option<String> },
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct HistoryEvent {
+    #[serde(flatten)]
+    change: HistoryChange,
+    from_block: i64,
+    // null while the value is still the current one
+    to_block: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/domain_history",
+    tag = "starknetid",
+    params(DomainHistoryQuery),
+    responses(
+        (status = 200, description = "Success", body = [HistoryEvent]),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
+#[route(get, "/domain_history", crate::endpoints::domain_history)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainHistoryQuery>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+
+    let domain_rows = match find_versions(&state, "domains", doc! { "domain": &query.domain }).await
+    {
+        Ok(rows) if rows.is_empty() => {
+            return ApiError::NotFound("No history found for this domain".to_string())
+                .into_response()
+        }
+        Ok(rows) => rows,
+        Err(e) => return ApiError::Database(e.to_string()).into_response(),
+    };
+
+    // periods during which the domain was attached to each identity
+    let id_periods = segments(
+        domain_rows
+            .iter()
+            .map(|(doc, from, to)| (get_string(doc, "id"), *from, *to)),
+    );
+    let mut ids: Vec<String> = id_periods
+        .iter()
+        .filter_map(|(id, _, _)| id.clone())
+        .collect();
+    ids.sort();
+    ids.dedup();
+
+    let (owner_rows, starknet_rows) = futures::join!(
+        find_versions(&state, "id_owners", doc! { "id": { "$in": ids.clone() } }),
+        find_versions(
+            &state,
+            "id_user_data",
+            doc! { "id": { "$in": ids.clone() }, "field": STARKNET_FIELD }
+        ),
+    );
+    let (owner_rows, starknet_rows) = match (owner_rows, starknet_rows) {
+        (Ok(owner_rows), Ok(starknet_rows)) => (owner_rows, starknet_rows),
+        (Err(e), _) | (_, Err(e)) => return ApiError::Database(e.to_string()).into_response(),
+    };
+
+    let mut events = Vec::new();
+    let mut push_changes = |changes: Vec<(HistoryChange, i64, Option<i64>)>| {
+        events.extend(
+            changes
+                .into_iter()
+                .map(|(change, from_block, to_block)| HistoryEvent {
+                    change,
+                    from_block,
+                    to_block,
+                }),
+        )
+    };
+
+    push_changes(
+        id_periods
+            .iter()
+            .map(|(id, from, to)| (HistoryChange::Identity { id: id.clone() }, *from, *to))
+            .collect(),
+    );
+    push_changes(segments(domain_rows.iter().map(|(doc, from, to)| {
+        let resolver = get_string(doc, "resolver");
+        (HistoryChange::Resolver { resolver }, *from, *to)
+    })));
+    push_changes(segments(domain_rows.iter().map(|(doc, from, to)| {
+        let rev_address = get_string(doc, "rev_address");
+        (HistoryChange::RevAddress { rev_address }, *from, *to)
+    })));
+    push_changes(segments(domain_rows.iter().map(|(doc, from, to)| {
+        let expiry = doc.get_i64("expiry").ok();
+        (HistoryChange::Expiry { expiry }, *from, *to)
+    })));
+
+    for id in &ids {
+        // versions are clipped to the periods where the domain belonged to this identity
+        let periods: Vec<(i64, Option<i64>)> = id_periods
+            .iter()
+            .filter(|(period_id, _, _)| period_id.as_ref() == Some(id))
+            .map(|(_, from, to)| (*from, *to))
+            .collect();
+        push_changes(
+            segments(
+                owner_rows
+                    .iter()
+                    .filter(|(doc, _, _)| doc.get_str("id").ok() == Some(id.as_str()))
+                    .map(|(doc, from, to)| {
+                        let owner = get_string(doc, "owner");
+                        let change = HistoryChange::Owner {
+                            id: id.clone(),
+                            owner,
+                        };
+                        (change, *from, *to)
+                    }),
+            )
+            .into_iter()
+            .flat_map(|segment| clip(segment, &periods))
+            .collect(),
+        );
+        push_changes(
+            segments(
+                starknet_rows
+                    .iter()
+                    .filter(|(doc, _, _)| doc.get_str("id").ok() == Some(id.as_str()))
+                    .map(|(doc, from, to)| {
+                        let address = get_string(doc, "data");
+                        let change = HistoryChange::Starknet {
+                            id: id.clone(),
+                            address,
+                        };
+                        (change, *from, *to)
+                    }),
+            )
+            .into_iter()
+            .flat_map(|segment| clip(segment, &periods))
+            .collect(),
+        );
+    }
+
+    events.sort_by_key(|event| event.from_block);
+    (StatusCode::OK, headers, Json(events)).into_response()
+}
+
+// Returns every version of the matching rows with its block range, oldest first
+async fn find_versions(
+    state: &AppState,
+    collection_name: &str,
+    filter: Document,
+) -> mongodb::error::Result<Vec<(Document, i64, Option<i64>)>> {
+    let collection = state.starknetid_db.collection::<Document>(collection_name);
+    let options = FindOptions::builder()
+        .sort(doc! { "_cursor.from": 1 })
+        .build();
+    let mut cursor = collection.find(filter, options).await?;
+
+    let mut versions = Vec::new();
+    while let Some(doc) = cursor.try_next().await? {
+        let range = doc.get_document("_cursor").ok().and_then(|cursor| {
+            let from = get_block(cursor.get("from"))?;
+            Some((from, get_block(cursor.get("to"))))
+        });
+        if let Some((from, to)) = range {
+            versions.push((doc, from, to));
+        }
+    }
+    Ok(versions)
+}
+
+// Merges consecutive versions holding the same value into a single block range
+pub fn segments<T: PartialEq>(
+    versions: impl Iterator<Item = (T, i64, Option<i64>)>,
+) -> Vec<(T, i64, Option<i64>)> {
+    let mut result: Vec<(T, i64, Option<i64>)> = Vec::new();
+    for (value, from, to) in versions {
+        match result.last_mut() {
+            Some(last) if last.0 == value && last.2 == Some(from) => last.2 = to,
+            _ => result.push((value, from, to)),
+        }
+    }
+    result
+}
+
+pub fn overlaps(a: (i64, Option<i64>), b: (i64, Option<i64>)) -> bool {
+    a.0 < b.1.unwrap_or(i64::MAX) && b.0 < a.1.unwrap_or(i64::MAX)
+}
+
+// Parts of a block range within each of the periods, an open range ends with None
+pub fn clip<T: Clone>(
+    (value, from, to): (T, i64, Option<i64>),
+    periods: &[(i64, Option<i64>)],
+) -> Vec<(T, i64, Option<i64>)> {
+    periods
+        .iter()
+        .filter(|period| overlaps((from, to), **period))
+        .map(|(period_from, period_to)| {
+            let end = match (to, *period_to) {
+                (Some(to), Some(period_to)) => Some(to.min(period_to)),
+                (to, period_to) => to.or(period_to),
+            };
+            (value.clone(), from.max(*period_from), end)
+        })
+        .collect()
+}
+
+fn get_block(value: Option<&Bson>) -> Option<i64> {
+    match value {
+        Some(Bson::Int64(block)) => Some(*block),
+        Some(Bson::Int32(block)) => Some(*block as i64),
+        _ => None,
+    }
+}
+
+fn get_string(doc: &Document, key: &str) -> Option<String> {
+    doc.get_str(key).ok().map(|value| value.to_owned())
+}
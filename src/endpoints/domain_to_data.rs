@@ -1,9 +1,12 @@
 use crate::{
+    cache::identity_tags,
+    errors::ApiError,
+    extractors::Query,
+    history::{at_block, requested_block},
     models::{AppState, IdentityData},
-    utils::get_error,
 };
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -12,12 +15,26 @@ use futures::StreamExt;
 use mongodb::bson::{doc, from_bson, Bson, Document};
 use serde::Deserialize;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct DomainQuery {
     domain: String,
+    block: Option<u64>,
+    timestamp: Option<u64>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/domain_to_data",
+    tag = "starknetid",
+    params(DomainQuery),
+    responses(
+        (status = 200, description = "Success", body = IdentityData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/domain_to_data", crate::endpoints::domain_to_data)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -26,33 +43,45 @@ pub async fn handler(
     let mut headers = HeaderMap::new();
     headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
 
+    let block = match requested_block(&state, query.block, query.timestamp).await {
+        Ok(block) => block,
+        Err(e) => return e.into_response(),
+    };
+    // only the latest state is cached
+    let cache_key = format!("domain_to_data:{}", query.domain);
+    if block.is_none() {
+        if let Some(cached) = state.cache.get(&cache_key) {
+            return (StatusCode::OK, headers, Json(cached)).into_response();
+        }
+    }
+
     let collection = state.starknetid_db.collection::<Document>("domains");
 
-    let mut cursor = match collection.aggregate(get_pipeline(query.domain), None).await {
+    let pipeline = match at_block(get_pipeline(query.domain), block) {
+        Ok(pipeline) => pipeline,
+        Err(e) => return e.into_response(),
+    };
+    let mut cursor = match collection.aggregate(pipeline, None).await {
         Ok(cursor) => cursor,
-        Err(_) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                headers,
-                "Failed to retrieve data".to_string(),
-            )
-                .into_response();
-        }
+        Err(e) => return ApiError::Database(e.to_string()).into_response(),
     };
 
     // The aggregation returns a single document
     return if let Some(result) = cursor.next().await {
         match result {
-            Ok(doc) => (
-                StatusCode::OK,
-                headers,
-                Json(from_bson::<IdentityData>(Bson::Document(doc)).expect("Malformed document")),
-            )
-                .into_response(),
-            Err(err) => get_error(format!("Unexpected error: {}", err)),
+            Ok(doc) => match from_bson::<IdentityData>(Bson::Document(doc)) {
+                Ok(data) => {
+                    if block.is_none() {
+                        state.cache.insert(cache_key, &data, &identity_tags(&data));
+                    }
+                    (StatusCode::OK, headers, Json(data)).into_response()
+                }
+                Err(e) => ApiError::Internal(format!("Malformed document: {}", e)).into_response(),
+            },
+            Err(e) => ApiError::Database(e.to_string()).into_response(),
         }
     } else {
-        get_error("Identity not found".to_string())
+        ApiError::NotFound("Identity not found".to_string()).into_response()
     };
 }
 
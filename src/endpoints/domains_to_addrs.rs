@@ -0,0 +1,93 @@
+use crate::{
+    errors::{ApiError, ItemError},
+    extractors::Json,
+    models::AppState,
+    resolving::ResolutionResult,
+};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Debug, ToSchema)]
+#[schema(as = DomainsToAddrsData)]
+pub struct DomainToAddrData {
+    domain: String,
+    addr: Option<String>,
+    domain_expiry: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ItemError>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DomainsToAddrsQuery {
+    pub domains: Vec<String>,
+}
+
+impl DomainsToAddrsQuery {
+    pub fn check_size(&self, max_domains: usize) -> Result<(), ApiError> {
+        if self.domains.len() > max_domains {
+            return Err(ApiError::Validation(format!(
+                "At most {} domains can be resolved at once, got {}",
+                max_domains,
+                self.domains.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl DomainToAddrData {
+    pub fn from_result(domain: String, result: ResolutionResult) -> Self {
+        match result {
+            Ok(resolution) => DomainToAddrData {
+                domain,
+                addr: Some(resolution.addr),
+                domain_expiry: resolution.domain_expiry,
+                error: None,
+            },
+            Err(error) => DomainToAddrData {
+                domain,
+                addr: None,
+                domain_expiry: None,
+                error: Some(ApiError::from(error).into()),
+            },
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/domains_to_addrs",
+    tag = "starknetid",
+    request_body = DomainsToAddrsQuery,
+    responses(
+        (status = 200, description = "Success", body = [DomainToAddrData]),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
+#[route(post, "/domains_to_addrs", crate::endpoints::domains_to_addrs)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<DomainsToAddrsQuery>,
+) -> impl IntoResponse {
+    if let Err(error) = query.check_size(state.conf.batch_resolution.max_domains) {
+        return error.into_response();
+    }
+
+    let resolutions = state
+        .resolvers
+        .resolve_many(&state, &query.domains, None)
+        .await;
+
+    // results are returned in the same order as the input domains
+    let results = query
+        .domains
+        .into_iter()
+        .zip(resolutions)
+        .map(|(domain, result)| DomainToAddrData::from_result(domain, result))
+        .collect::<Vec<_>>();
+
+    (StatusCode::OK, Json(results)).into_response()
+}
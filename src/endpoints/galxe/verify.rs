@@ -1,22 +1,34 @@
 use std::sync::Arc;
 
+use crate::extractors::Json;
 use crate::models::AppState;
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
 use axum_auto_routes::route;
 use futures::StreamExt;
 use mongodb::{bson::doc, bson::Document};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct EmailQuery {
     email: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct SimpleResponse {
     result: &'static str,
 }
 
+#[utoipa::path(
+    post,
+    path = "/galxe/verify",
+    tag = "galxe",
+    request_body = EmailQuery,
+    responses(
+        (status = 200, description = "Success", body = SimpleResponse),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(post, "/galxe/verify", crate::endpoints::galxe::verify)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use chrono::Utc;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, from_document, Document},
+    options::{AggregateOptions, FindOptions},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    address::StarknetAddress,
+    admin::AdminKey,
+    errors::ApiError,
+    extractors::Query,
+    models::AppState,
+    quotes::{altcoin_quotes, StoredQuote},
+};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+// longest time range aggregated into candles, in seconds
+const MAX_AGGREGATED_RANGE: i64 = 31 * 24 * 3600;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuoteHistoryQuery {
+    #[param(value_type = Option<String>)]
+    token: Option<StarknetAddress>,
+    // unix timestamps of the time range, both included. Candles cover at most 31 days,
+    // the last 31 days before `to` by default
+    from: Option<i64>,
+    to: Option<i64>,
+    // in seconds, aggregates the quotes into candles of this duration
+    interval: Option<i64>,
+    // quotes returned without interval, 100 by default, at most 1000
+    limit: Option<i64>,
+}
+
+/// Open, high, low and close quotes of a token signed during an interval
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct QuoteCandle {
+    pub token: String,
+    pub start: i64,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub count: u64,
+}
+
+/// Signed quotes, latest first, or candles when an interval is given
+#[derive(Serialize, ToSchema)]
+pub struct QuoteHistory {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quotes: Option<Vec<StoredQuote>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candles: Option<Vec<QuoteCandle>>,
+}
+
+/// Time range of the candles, the last `MAX_AGGREGATED_RANGE` seconds before `to` (now
+/// by default) when `from` is missing. Longer ranges are refused.
+pub fn aggregated_range(
+    from: Option<i64>,
+    to: Option<i64>,
+    now: i64,
+) -> Result<(i64, i64), ApiError> {
+    let to = to.unwrap_or(now);
+    let from = from.unwrap_or(to.saturating_sub(MAX_AGGREGATED_RANGE));
+    if from > to {
+        return Err(ApiError::Validation("from must be before to".to_string()));
+    }
+    if to.saturating_sub(from) > MAX_AGGREGATED_RANGE {
+        return Err(ApiError::Validation(format!(
+            "The time range of candles is at most {} seconds",
+            MAX_AGGREGATED_RANGE
+        )));
+    }
+    Ok((from, to))
+}
+
+/// Aggregates the quotes matching the filter into candles of `interval` seconds, per
+/// token. Quotes are decimal strings, they are compared as decimals.
+pub fn candles_pipeline(filter: Document, interval: i64) -> Vec<Document> {
+    let value = doc! { "$convert": { "input": "$quote", "to": "decimal", "onError": null } };
+    vec![
+        doc! { "$match": filter },
+        doc! { "$sort": { "created_at": 1 } },
+        doc! { "$group": {
+            "_id": {
+                "token": "$token",
+                "start": { "$subtract": ["$created_at", { "$mod": ["$created_at", interval] }] },
+            },
+            "open": { "$first": "$quote" },
+            "high": { "$max": &value },
+            "low": { "$min": &value },
+            "close": { "$last": "$quote" },
+            "count": { "$sum": 1 },
+        } },
+        doc! { "$sort": { "_id.token": 1, "_id.start": 1 } },
+        doc! { "$project": {
+            "_id": 0,
+            "token": "$_id.token",
+            "start": "$_id.start",
+            "open": 1,
+            "high": { "$toString": "$high" },
+            "low": { "$toString": "$low" },
+            "close": 1,
+            "count": 1,
+        } },
+    ]
+}
+
+#[utoipa::path(
+    get,
+    path = "/get_altcoin_quote_history",
+    tag = "starknetid",
+    params(
+        QuoteHistoryQuery,
+        ("x-api-key" = String, Header, description = "Admin api key")
+    ),
+    responses(
+        (status = 200, description = "Success", body = QuoteHistory),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
+#[route(
+    get,
+    "/get_altcoin_quote_history",
+    crate::endpoints::get_altcoin_quote_history
+)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminKey,
+    Query(query): Query<QuoteHistoryQuery>,
+) -> impl IntoResponse {
+    if matches!(query.interval, Some(interval) if interval <= 0) {
+        return ApiError::Validation("interval must be positive".to_string()).into_response();
+    }
+
+    let mut filter = doc! {};
+    if let Some(token) = &query.token {
+        filter.insert("token", token.to_hex());
+    }
+
+    let history = match query.interval {
+        Some(interval) => {
+            let (from, to) = match aggregated_range(query.from, query.to, Utc::now().timestamp()) {
+                Ok(range) => range,
+                Err(e) => return e.into_response(),
+            };
+            filter.insert("created_at", doc! { "$gte": from, "$lte": to });
+            let documents = match altcoin_quotes(&state)
+                .aggregate(
+                    candles_pipeline(filter, interval),
+                    AggregateOptions::default(),
+                )
+                .await
+            {
+                Ok(cursor) => cursor.try_collect::<Vec<Document>>().await,
+                Err(e) => Err(e),
+            };
+            match documents {
+                Ok(documents) => QuoteHistory {
+                    quotes: None,
+                    candles: Some(
+                        documents
+                            .into_iter()
+                            .filter_map(|document| from_document(document).ok())
+                            .collect(),
+                    ),
+                },
+                Err(e) => return ApiError::Database(e.to_string()).into_response(),
+            }
+        }
+        None => {
+            let mut created_at = doc! {};
+            if let Some(from) = query.from {
+                created_at.insert("$gte", from);
+            }
+            if let Some(to) = query.to {
+                created_at.insert("$lte", to);
+            }
+            if !created_at.is_empty() {
+                filter.insert("created_at", created_at);
+            }
+            let options = FindOptions::builder()
+                .sort(doc! { "created_at": -1 })
+                .limit(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT))
+                .build();
+            let documents = match altcoin_quotes(&state).find(filter, options).await {
+                Ok(cursor) => cursor.try_collect::<Vec<Document>>().await,
+                Err(e) => Err(e),
+            };
+            match documents {
+                Ok(documents) => QuoteHistory {
+                    quotes: Some(
+                        documents
+                            .into_iter()
+                            .filter_map(|document| from_document::<StoredQuote>(document).ok())
+                            .collect(),
+                    ),
+                    candles: None,
+                },
+                Err(e) => return ApiError::Database(e.to_string()).into_response(),
+            }
+        }
+    };
+    (StatusCode::OK, Json(history)).into_response()
+}
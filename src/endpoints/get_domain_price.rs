@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_auto_routes::route;
+use ethers::types::{U256, U512};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use starknet_id::encode;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    address::StarknetAddress,
+    errors::ApiError,
+    extractors::Query,
+    models::{AppState, States},
+    quotes::get_quote,
+    utils::to_hex,
+};
+
+// ETH amounts are in wei
+const ETH_DECIMALS: usize = 18;
+// tax rates are applied in parts per million
+const TAX_RATE_SCALE: u64 = 1_000_000;
+// size of the basic alphabet of the encoding plus its escape character
+const BASIC_ALPHABET_SIZE: u64 = 38;
+// size of the extended alphabet of the encoding
+const BIG_ALPHABET_SIZE: u64 = 2;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DomainPriceQuery {
+    domain: String,
+    days: u16,
+    #[param(value_type = String)]
+    token: StarknetAddress,
+    // state of the buyer in the sales tax table, no tax when missing and a
+    // validation error when unknown
+    tax_state: Option<String>,
+}
+
+/// Checkout amounts of a domain, in the smallest unit of their token
+#[derive(Serialize, ToSchema)]
+pub struct DomainPriceData {
+    domain: String,
+    days: u16,
+    token: String,
+    // price of the domain in wei, from the pricing contract
+    eth_price: String,
+    // amount of the token worth one ETH, signed with r and s
+    quote: String,
+    max_quote_validity: i64,
+    r: String,
+    s: String,
+    price: String,
+    tax_state: Option<String>,
+    tax_rate: f32,
+    tax: String,
+    total: String,
+}
+
+/// Returns the label of a root `.stark` domain
+pub fn parse_stark_domain(domain: &str) -> Result<&str, ApiError> {
+    let label = domain.strip_suffix(".stark").unwrap_or(domain);
+    if label.is_empty() || label.contains('.') || encode(label).is_err() {
+        return Err(ApiError::Validation(format!("Invalid domain: {}", domain)));
+    }
+    Ok(label)
+}
+
+/// Length of a label as the pricing contract counts it, from its encoding. An escaped
+/// character of the encoding takes two digits but counts as one character.
+pub fn domain_length(label: &str) -> Result<usize, ApiError> {
+    let encoded =
+        encode(label).map_err(|_| ApiError::Validation(format!("Invalid domain: {}", label)))?;
+    let mut encoded = U256::from_big_endian(&encoded.to_bytes_be());
+    let mut length = 0;
+    while !encoded.is_zero() {
+        let (rest, digit) = encoded.div_mod(U256::from(BASIC_ALPHABET_SIZE));
+        encoded = if digit == U256::from(BASIC_ALPHABET_SIZE - 1) {
+            rest / U256::from(BIG_ALPHABET_SIZE)
+        } else {
+            rest
+        };
+        length += 1;
+    }
+    Ok(length)
+}
+
+/// Sales tax rate of a state, no tax when the state is missing
+pub fn tax_rate(states: &States, tax_state: Option<&str>) -> Result<f32, ApiError> {
+    match tax_state {
+        Some(tax_state) => states
+            .states
+            .get(tax_state)
+            .map(|state| state.rate)
+            .ok_or_else(|| ApiError::Validation(format!("Unknown tax state: {}", tax_state))),
+        None => Ok(0.0),
+    }
+}
+
+/// Price of a domain in the token and its sales tax, from its ETH price and the
+/// signed quote of the token. Rounded down like the contracts do.
+pub fn price_breakdown(eth_price: U256, quote: U256, tax_rate: f32) -> Option<(U256, U256)> {
+    let price = eth_price.full_mul(quote) / U512::from(U256::exp10(ETH_DECIMALS));
+    let price = U256::try_from(price).ok()?;
+    let tax_rate = (tax_rate as f64 * TAX_RATE_SCALE as f64).round();
+    if !(0.0..=TAX_RATE_SCALE as f64).contains(&tax_rate) {
+        return None;
+    }
+    let tax = price.full_mul(U256::from(tax_rate as u64)) / U512::from(TAX_RATE_SCALE);
+    Some((price, U256::try_from(tax).ok()?))
+}
+
+// Price in wei of a domain of this length for this many days
+async fn get_eth_price(
+    state: &Arc<AppState>,
+    domain_len: usize,
+    days: u16,
+) -> Result<U256, ApiError> {
+    let pricing = state
+        .conf
+        .contracts
+        .pricing
+        .ok_or_else(|| ApiError::Internal("No pricing contract configured".to_string()))?;
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url)
+            .map_err(|e| ApiError::Internal(format!("Invalid rpc_url: {}", e)))?,
+    ));
+    let result = provider
+        .call(
+            FunctionCall {
+                contract_address: pricing,
+                entry_point_selector: selector!("compute_buy_price"),
+                calldata: vec![FieldElement::from(domain_len), FieldElement::from(days)],
+            },
+            BlockId::Tag(BlockTag::Latest),
+        )
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Unable to call the pricing contract: {}", e)))?;
+    // (erc20, price as a u256 low and high)
+    match result.as_slice() {
+        [_, low, high] => {
+            let bytes = [&high.to_bytes_be()[16..], &low.to_bytes_be()[16..]].concat();
+            Ok(U256::from_big_endian(&bytes))
+        }
+        _ => Err(ApiError::Upstream(
+            "Invalid response from the pricing contract".to_string(),
+        )),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/get_domain_price",
+    tag = "starknetid",
+    params(DomainPriceQuery),
+    responses(
+        (status = 200, description = "Success", body = DomainPriceData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
+#[route(get, "/get_domain_price", crate::endpoints::get_domain_price)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainPriceQuery>,
+) -> impl IntoResponse {
+    let label = match parse_stark_domain(&query.domain) {
+        Ok(label) => label,
+        Err(e) => return e.into_response(),
+    };
+    if query.days == 0 {
+        return ApiError::Validation("days must be positive".to_string()).into_response();
+    }
+    let domain_len = match domain_length(label) {
+        Ok(domain_len) => domain_len,
+        Err(e) => return e.into_response(),
+    };
+    let tax_rate = match tax_rate(&state.states, query.tax_state.as_deref()) {
+        Ok(tax_rate) => tax_rate,
+        Err(e) => return e.into_response(),
+    };
+    let token = query.token.felt();
+    if !state.conf.altcoins.data.contains_key(&token) {
+        return ApiError::Validation("Token not supported".to_string()).into_response();
+    }
+
+    let (eth_price, quote) = match tokio::try_join!(
+        get_eth_price(&state, domain_len, query.days),
+        get_quote(&state, &token)
+    ) {
+        Ok(prices) => prices,
+        Err(e) => return e.into_response(),
+    };
+
+    let (price, tax) = match price_breakdown(eth_price, quote.quote, tax_rate) {
+        Some(breakdown) => breakdown,
+        None => {
+            return ApiError::Internal("Unable to compute the domain price".to_string())
+                .into_response()
+        }
+    };
+
+    (
+        StatusCode::OK,
+        Json(DomainPriceData {
+            domain: format!("{}.stark", label),
+            days: query.days,
+            token: to_hex(&token),
+            eth_price: eth_price.to_string(),
+            quote: quote.quote.to_string(),
+            max_quote_validity: quote.max_quote_validity,
+            r: to_hex(&quote.r),
+            s: to_hex(&quote.s),
+            price: price.to_string(),
+            tax_state: query.tax_state,
+            tax_rate,
+            tax: tax.to_string(),
+            total: price.saturating_add(tax).to_string(),
+        }),
+    )
+        .into_response()
+}
@@ -1,4 +1,4 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{errors::ApiError, models::AppState};
 use axum::{
     extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -9,20 +9,30 @@ use futures::StreamExt;
 use mongodb::bson::{doc, Document};
 use serde::Serialize;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
-struct IdDetails {
+#[derive(Serialize, ToSchema)]
+pub struct IdDetails {
     addr: String,
     domain: String,
     id: String,
     expiry: i64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ExpiringDomains {
     ids: Vec<IdDetails>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/get_expiring_domains",
+    tag = "starknetid",
+    responses(
+        (status = 200, description = "Success", body = ExpiringDomains),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/get_expiring_domains", crate::endpoints::get_expiring_domains)]
 pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let mut headers = HeaderMap::new();
@@ -75,13 +85,11 @@ pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
                             });
                         }
                     }
-                    Err(_) => {
-                        get_error("Error while parsing document".to_string());
-                    }
+                    Err(_) => continue,
                 }
             }
             (StatusCode::OK, Json(ExpiringDomains { ids })).into_response()
         }
-        Err(_) => get_error("Failed to retrive data from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -1,9 +1,13 @@
 use crate::{
+    cache::identity_tags,
+    errors::ApiError,
+    extractors::Query,
+    history::{at_block, requested_block},
     models::{AppState, IdentityData},
-    utils::{get_error, to_hex},
+    utils::to_hex,
 };
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -13,12 +17,27 @@ use mongodb::bson::{doc, from_bson, Bson, Document};
 use serde::Deserialize;
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct IdQuery {
+    #[param(value_type = String)]
     id: FieldElement,
+    block: Option<u64>,
+    timestamp: Option<u64>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/id_to_data",
+    tag = "starknetid",
+    params(IdQuery),
+    responses(
+        (status = 200, description = "Success", body = IdentityData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/id_to_data", crate::endpoints::id_to_data)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -27,36 +46,45 @@ pub async fn handler(
     let mut headers = HeaderMap::new();
     headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
 
+    let block = match requested_block(&state, query.block, query.timestamp).await {
+        Ok(block) => block,
+        Err(e) => return e.into_response(),
+    };
+    // only the latest state is cached
+    let cache_key = format!("id_to_data:{}", to_hex(&query.id));
+    if block.is_none() {
+        if let Some(cached) = state.cache.get(&cache_key) {
+            return (StatusCode::OK, headers, Json(cached)).into_response();
+        }
+    }
+
     let collection = state.starknetid_db.collection::<Document>("id_owners");
 
-    let mut cursor = match collection
-        .aggregate(get_pipeline(to_hex(&query.id)), None)
-        .await
-    {
+    let pipeline = match at_block(get_pipeline(to_hex(&query.id)), block) {
+        Ok(pipeline) => pipeline,
+        Err(e) => return e.into_response(),
+    };
+    let mut cursor = match collection.aggregate(pipeline, None).await {
         Ok(cursor) => cursor,
-        Err(_) => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                headers,
-                "Failed to retrieve data".to_string(),
-            )
-                .into_response();
-        }
+        Err(e) => return ApiError::Database(e.to_string()).into_response(),
     };
 
     // The aggregation returns a single document
     return if let Some(result) = cursor.next().await {
         match result {
-            Ok(doc) => (
-                StatusCode::OK,
-                headers,
-                Json(from_bson::<IdentityData>(Bson::Document(doc)).expect("Malformed document")),
-            )
-                .into_response(),
-            Err(err) => get_error(format!("Unexpected error: {}", err)),
+            Ok(doc) => match from_bson::<IdentityData>(Bson::Document(doc)) {
+                Ok(data) => {
+                    if block.is_none() {
+                        state.cache.insert(cache_key, &data, &identity_tags(&data));
+                    }
+                    (StatusCode::OK, headers, Json(data)).into_response()
+                }
+                Err(e) => ApiError::Internal(format!("Malformed document: {}", e)).into_response(),
+            },
+            Err(e) => ApiError::Database(e.to_string()).into_response(),
         }
     } else {
-        get_error("Identity not found".to_string())
+        ApiError::NotFound("Identity not found".to_string()).into_response()
     };
 }
 
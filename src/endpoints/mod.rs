@@ -8,10 +8,14 @@ pub mod addrs_to_domains;
 pub mod campaigns;
 pub mod crosschain;
 pub mod data_to_ids;
+pub mod domain_history;
 pub mod domain_to_addr;
 pub mod domain_to_data;
+pub mod domains_to_addrs;
 pub mod galxe;
 pub mod get_altcoin_quote;
+pub mod get_altcoin_quote_history;
+pub mod get_domain_price;
 pub mod get_expiring_domains;
 pub mod id_to_data;
 pub mod referral;
@@ -1,9 +1,5 @@
-use crate::{models::AppState, utils::get_error};
-use axum::{
-    extract::{Json, State},
-    http::StatusCode,
-    response::IntoResponse,
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Json, models::AppState};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
 use axum_auto_routes::route;
 use chrono::Utc;
 use mongodb::{
@@ -11,14 +7,25 @@ use mongodb::{
     options::UpdateOptions,
 };
 use serde::Deserialize;
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AddClickQuery {
-    sponsor_addr: FieldElement,
+    #[schema(value_type = String)]
+    sponsor_addr: StarknetAddress,
 }
 
+#[utoipa::path(
+    post,
+    path = "/referral/add_click",
+    tag = "referral",
+    request_body = AddClickQuery,
+    responses(
+        (status = 200, description = "Success", body = String),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(post, "/referral/add_click", crate::endpoints::referral::add_click)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -35,7 +42,7 @@ pub async fn handler(
     let result = sponsor_usage
         .update_one(
             doc! {
-                "sponsor_addr": query.sponsor_addr.to_string(),
+                "sponsor_addr": query.sponsor_addr.to_hex(),
                 "day": today_bson,
             },
             doc! {
@@ -52,6 +59,6 @@ pub async fn handler(
             Json("Sponsor usage updated successfully".to_string()),
         )
             .into_response(),
-        Err(_) => get_error("Error while updating database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
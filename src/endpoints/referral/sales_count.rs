@@ -1,6 +1,6 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -10,19 +10,33 @@ use futures::StreamExt;
 use mongodb::bson::{doc, Bson, DateTime as BsonDateTime};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[schema(as = SalesCountData)]
 pub struct Data {
     counts: Vec<i64>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct IdQuery {
-    sponsor: String,
+    #[param(value_type = String)]
+    sponsor: StarknetAddress,
     since_date: i64,
     spacing: i64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/referral/sales_count",
+    tag = "referral",
+    params(IdQuery),
+    responses(
+        (status = 200, description = "Success", body = Data),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/referral/sales_count", crate::endpoints::referral::sales_count)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -46,7 +60,7 @@ pub async fn handler(
         let documents = referral_revenues
             .find(
                 doc! {
-                    "sponsor_addr": &query.sponsor,
+                    "sponsor_addr": { "$in": query.sponsor.stored_forms() },
                     "amount": { "$gt": 0 },
                     "timestamp": {
                         "$gt": BsonDateTime::from_millis(start_time.timestamp() * 1000),
@@ -69,7 +83,7 @@ pub async fn handler(
                 }
             }
             Err(e) => {
-                return get_error(format!("Error while fetching from database: {:?}", e));
+                return ApiError::Database(e.to_string()).into_response();
             }
         }
 
@@ -1,9 +1,6 @@
-use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -11,20 +8,32 @@ use axum_auto_routes::route;
 use futures::TryStreamExt;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct GetMetaHashData {
     meta_hash: String,
     tax_rate: f32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GetMetaHashQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
+#[utoipa::path(
+    get,
+    path = "/renewal/get_metahash",
+    tag = "renewal",
+    params(GetMetaHashQuery),
+    responses(
+        (status = 200, description = "Success", body = GetMetaHashData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/renewal/get_metahash", crate::endpoints::renewal::get_metahash)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -36,7 +45,7 @@ pub async fn handler(
 
     let pipeline = vec![
         doc! {"$match": {
-            "payer": to_hex(&query.addr),
+            "payer": query.addr.to_hex(),
             "$or": [
                 { "_cursor.to": { "$exists": false } },
                 { "_cursor.to": Bson::Null },
@@ -67,7 +76,7 @@ pub async fn handler(
         Ok(cursor) => match cursor.try_collect::<Vec<mongodb::bson::Document>>().await {
             Ok(documents) => {
                 if documents.is_empty() {
-                    return get_error("No documents found".to_string());
+                    return ApiError::NotFound("No documents found".to_string()).into_response();
                 }
 
                 let mut headers = HeaderMap::new();
@@ -90,10 +99,10 @@ pub async fn handler(
                         return (StatusCode::OK, headers, Json(res)).into_response();
                     }
                 }
-                get_error("No metahash found".to_string())
+                ApiError::NotFound("No metahash found".to_string()).into_response()
             }
-            Err(_) => get_error("Error while fetching from database".to_string()),
+            Err(e) => ApiError::Database(e.to_string()).into_response(),
         },
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -1,9 +1,6 @@
-use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::StatusCode,
     response::{IntoResponse, Json},
 };
@@ -12,18 +9,30 @@ use futures::StreamExt;
 use mongodb::{bson::doc, options::AggregateOptions};
 use regex::Regex;
 use serde::Deserialize;
-use starknet::core::types::FieldElement;
 use std::{collections::HashSet, sync::Arc};
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct StarknetIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
 lazy_static::lazy_static! {
     static ref DOMAIN_REGEX: Regex = Regex::new(r"^[^.]+\.stark$").unwrap();
 }
 
+#[utoipa::path(
+    get,
+    path = "/renewal/get_non_subscribed_domains",
+    tag = "renewal",
+    params(StarknetIdQuery),
+    responses(
+        (status = 200, description = "Success", body = [String]),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     get,
     "/renewal/get_non_subscribed_domains",
@@ -36,12 +45,12 @@ pub async fn handler(
     let id_owners = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("id_owners");
-    let addr = to_hex(&query.addr);
+    let addr = query.addr.to_hex();
 
     let pipeline = vec![
         doc! {
             "$match": doc! {
-                "owner": to_hex(&query.addr),
+                "owner": query.addr.to_hex(),
                 "_cursor.to": null
             }
         },
@@ -183,6 +192,6 @@ pub async fn handler(
             let results: Vec<String> = domains_set.into_iter().collect(); // Convert HashSet back to Vec to match your function's expected return type
             (StatusCode::OK, Json(results)).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
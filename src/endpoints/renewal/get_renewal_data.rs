@@ -1,9 +1,6 @@
-use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -11,20 +8,32 @@ use axum_auto_routes::route;
 use futures::TryStreamExt;
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct StarknetIdData {
     starknet_id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct StarknetIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
     domain: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/renewal/get_renewal_data",
+    tag = "renewal",
+    params(StarknetIdQuery),
+    responses(
+        (status = 200, description = "Success"),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     get,
     "/renewal/get_renewal_data",
@@ -62,7 +71,7 @@ pub async fn handler(
         }
         (StatusCode::OK, headers, Json(combined_results)).into_response()
     } else {
-        get_error("Error while fetching from database or no results found".to_string())
+        ApiError::NotFound("No renewal data found".to_string()).into_response()
     }
 }
 
@@ -78,7 +87,7 @@ async fn find_renewal_data(
     let mut cursor = collection
         .find(
             doc! {
-                "renewer_address": to_hex(&query.addr),
+                "renewer_address": query.addr.to_hex(),
                 "domain": &query.domain,
                 "$or": [
                     { "_cursor.to": { "$exists": false } },
@@ -1,9 +1,6 @@
-use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::StatusCode,
     response::{IntoResponse, Json},
 };
@@ -14,10 +11,13 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::{collections::HashMap, sync::Arc};
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct StarknetIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
 }
 
 lazy_static::lazy_static! {
@@ -39,6 +39,16 @@ pub struct Subscriptions {
     pub altcoin_subscriptions: Option<Vec<Subscription>>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/renewal/get_subscription_info",
+    tag = "renewal",
+    params(StarknetIdQuery),
+    responses(
+        (status = 200, description = "Success"),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     get,
     "/renewal/get_subscription_info",
@@ -51,7 +61,7 @@ pub async fn handler(
     let id_owners = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("id_owners");
-    let addr = to_hex(&query.addr);
+    let addr = query.addr.to_hex();
 
     let pipeline = vec![
         doc! {
@@ -257,6 +267,6 @@ pub async fn handler(
             }
             (StatusCode::OK, Json(results)).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -1,9 +1,6 @@
-use crate::{
-    models::AppState,
-    utils::{get_error, to_hex},
-};
+use crate::{address::StarknetAddress, errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::StatusCode,
     response::{IntoResponse, Json},
 };
@@ -11,24 +8,27 @@ use axum_auto_routes::route;
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FetchNftsQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddress,
     cursor: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct StarkscanApiResult {
     data: Vec<StarkscanNftProps>,
     next_url: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct StarkscanNftProps {
     animation_url: Option<String>,
+    #[schema(value_type = Option<Object>)]
     attributes: Option<Value>,
     contract_address: String,
     description: Option<String>,
@@ -45,6 +45,16 @@ pub struct StarkscanNftProps {
     minted_at_timestamp: i64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/starkscan/fetch_nfts",
+    tag = "starkscan",
+    params(FetchNftsQuery),
+    responses(
+        (status = 200, description = "Success", body = StarkscanApiResult),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/starkscan/fetch_nfts", crate::endpoints::starkscan::fetch_nfts)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -53,7 +63,7 @@ pub async fn handler(
     let base_url = format!(
         "{}/nfts?owner_address={}",
         state.conf.starkscan.api_url,
-        to_hex(&query.addr)
+        query.addr.to_hex()
     );
     let url = query.cursor.as_ref().map_or(base_url.clone(), |cursor| {
         format!("{}&cursor={}", base_url, cursor)
@@ -70,16 +80,20 @@ pub async fn handler(
         Ok(response) => match response.text().await {
             Ok(text) => match serde_json::from_str::<StarkscanApiResult>(&text) {
                 Ok(res) => (StatusCode::OK, Json(res)).into_response(),
-                Err(e) => get_error(format!(
+                Err(e) => ApiError::Upstream(format!(
                     "Failed to deserialize result from Starkscan API: {} for response: {}",
                     e, text
-                )),
+                ))
+                .into_response(),
             },
-            Err(e) => get_error(format!(
+            Err(e) => ApiError::Upstream(format!(
                 "Failed to get JSON response while fetching user NFT data: {}",
                 e
-            )),
+            ))
+            .into_response(),
         },
-        Err(e) => get_error(format!("Failed to fetch user NFTs from API: {}", e)),
+        Err(e) => {
+            ApiError::Upstream(format!("Failed to fetch user NFTs from API: {}", e)).into_response()
+        }
     }
 }
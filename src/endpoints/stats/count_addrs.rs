@@ -1,6 +1,6 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     Json,
@@ -10,17 +10,29 @@ use futures::StreamExt;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CountAddrsData {
     count: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CountAddrsQuery {
     since: i64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats/count_addrs",
+    tag = "stats",
+    params(CountAddrsQuery),
+    responses(
+        (status = 200, description = "Success", body = CountAddrsData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/stats/count_addrs", crate::endpoints::stats::count_addrs)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -58,12 +70,15 @@ pub async fn handler(
                         let response_data = CountAddrsData { count };
                         (StatusCode::OK, headers, Json(response_data)).into_response()
                     }
-                    Err(e) => get_error(format!("Error while processing the document: {:?}", e)),
+                    Err(e) => {
+                        ApiError::Database(format!("Error while processing the document: {:?}", e))
+                            .into_response()
+                    }
                 }
             } else {
-                get_error("No documents found".to_string())
+                ApiError::NotFound("No documents found".to_string()).into_response()
             }
         }
-        Err(e) => get_error(format!("Error while fetching from database: {:?}", e)),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -1,6 +1,7 @@
+use crate::extractors::Query;
 use crate::models::AppState;
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     Json,
@@ -11,6 +12,7 @@ use mongodb::bson::{self, doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct CountClubDomainsData {
@@ -18,11 +20,22 @@ pub struct CountClubDomainsData {
     count: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CountClubDomainsQuery {
     since: i64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats/count_club_domains",
+    tag = "stats",
+    params(CountClubDomainsQuery),
+    responses(
+        (status = 200, description = "Success"),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     get,
     "/stats/count_club_domains",
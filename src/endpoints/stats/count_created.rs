@@ -1,6 +1,6 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     Json,
@@ -10,20 +10,32 @@ use futures::StreamExt;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CountCreatedData {
     from: i64,
     count: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CountCreatedQuery {
     begin: i64,
     end: i64,
     segments: i64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats/count_created",
+    tag = "stats",
+    params(CountCreatedQuery),
+    responses(
+        (status = 200, description = "Success", body = [CountCreatedData]),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/stats/count_created", crate::endpoints::stats::count_created)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -106,6 +118,6 @@ pub async fn handler(
 
         (StatusCode::OK, headers, Json(result)).into_response()
     } else {
-        get_error("delta must be greater than 3600 seconds".to_string())
+        ApiError::Validation("delta must be greater than 3600 seconds".to_string()).into_response()
     }
 }
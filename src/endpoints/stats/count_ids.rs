@@ -1,6 +1,6 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{errors::ApiError, extractors::Query, models::AppState};
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::IntoResponse,
     Json,
@@ -9,17 +9,30 @@ use axum_auto_routes::route;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[schema(as = CountIdsData)]
 pub struct CountDomainsData {
     count: u64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CountDomainsQuery {
     since: i64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats/count_ids",
+    tag = "stats",
+    params(CountDomainsQuery),
+    responses(
+        (status = 200, description = "Success", body = CountDomainsData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/stats/count_ids", crate::endpoints::stats::count_ids)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -47,6 +60,6 @@ pub async fn handler(
             let response_data = CountDomainsData { count };
             (StatusCode::OK, headers, Json(response_data)).into_response()
         }
-        Err(e) => get_error(format!("Error while fetching from database: {:?}", e)),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
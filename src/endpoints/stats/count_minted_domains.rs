@@ -1,4 +1,4 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{errors::ApiError, models::AppState};
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -9,12 +9,22 @@ use axum_auto_routes::route;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CountMintedDomainsData {
     count: u64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats/count_minted_domains",
+    tag = "stats",
+    responses(
+        (status = 200, description = "Success", body = CountMintedDomainsData),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/stats/count_minted_domains", crate::endpoints::stats::count_minted_domains)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -40,6 +50,6 @@ pub async fn handler(
             let response_data = CountMintedDomainsData { count };
             (StatusCode::OK, headers, Json(response_data)).into_response()
         }
-        Err(e) => get_error(format!("Error while fetching from database: {:?}", e)),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
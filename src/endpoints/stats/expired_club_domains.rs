@@ -1,4 +1,4 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{errors::ApiError, models::AppState};
 use axum::{
     extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -20,6 +20,15 @@ pub struct CountClubDomainsData {
     count: i32,
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats/expired_club_domains",
+    tag = "stats",
+    responses(
+        (status = 200, description = "Success"),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(
     get,
     "/stats/expired_club_domains",
@@ -105,10 +114,10 @@ pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
                 }
             }
             if output.is_empty() {
-                return get_error("No documents found".to_string());
+                return ApiError::NotFound("No documents found".to_string()).into_response();
             }
             (StatusCode::OK, headers, Json(output)).into_response()
         }
-        Err(e) => get_error(format!("Error while fetching from database: {:?}", e)),
+        Err(e) => ApiError::Database(e.to_string()).into_response(),
     }
 }
@@ -1,9 +1,10 @@
 use crate::{
+    extractors::Query,
     models::AppState,
     utils::{fetch_img_url, to_hex, to_u256},
 };
 use axum::{
-    extract::{Query, State},
+    extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Json},
 };
@@ -14,8 +15,9 @@ use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::{collections::HashMap, sync::Arc};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct TokenURI {
     name: String,
     description: String,
@@ -24,14 +26,16 @@ pub struct TokenURI {
     attributes: Option<Vec<Attribute>>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct Attribute {
     trait_type: String,
     value: Vec<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TokenIdQuery {
+    #[param(value_type = String)]
     id: FieldElement,
 }
 
@@ -48,6 +52,16 @@ const NFT_PP_CONTRACT: &'static str =
 const NFT_PP_ID: &'static str =
     "0x00000000000000000000000000000000000000000000006e66745f70705f6964";
 
+#[utoipa::path(
+    get,
+    path = "/uri",
+    tag = "starknetid",
+    params(TokenIdQuery),
+    responses(
+        (status = 200, description = "Success", body = TokenURI),
+        (status = "default", description = "Error", body = crate::errors::ErrorBody)
+    )
+)]
 #[route(get, "/uri", crate::endpoints::uri)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::resolving::ResolutionError;
+
+/// Error returned by every endpoint, serialized as `{ code, message, details }`.
+///
+/// Clients should rely on `code`, `message` is a human readable summary and
+/// `details` holds the underlying error when there is one.
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound(String),
+    Validation(String),
+    // missing or wrong credentials on a restricted endpoint
+    Unauthorized(String),
+    // an external service (RPC, offchain resolver, price api...) failed, with the error
+    Upstream(String),
+    // the offchain resolver of the domain returned an expired hint
+    ExpiredHint,
+    // the offchain resolver hint isn't signed by the resolver key
+    BadSignature,
+    // no uri of the offchain resolver answered, with the error
+    ResolverUnreachable(String),
+    // the database could not be queried, with the error
+    Database(String),
+    // unexpected failure on our side, with the error
+    Internal(String),
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    code: &'static str,
+    message: String,
+    details: Option<String>,
+}
+
+/// Error of one item of a batch endpoint, serialized as `{ code, message }`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ItemError {
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Validation(_) => "validation_error",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Upstream(_) => "upstream_error",
+            ApiError::ExpiredHint => "expired_hint",
+            ApiError::BadSignature => "bad_signature",
+            ApiError::ResolverUnreachable(_) => "resolver_unreachable",
+            ApiError::Database(_) => "database_unavailable",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Upstream(_)
+            | ApiError::ExpiredHint
+            | ApiError::BadSignature
+            | ApiError::ResolverUnreachable(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Database(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn body(&self) -> ErrorBody {
+        let (message, details) = match self {
+            ApiError::NotFound(message)
+            | ApiError::Validation(message)
+            | ApiError::Unauthorized(message) => (message.as_str(), None),
+            ApiError::Upstream(details) => ("Upstream service failed", Some(details.as_str())),
+            ApiError::ExpiredHint => ("Offchain resolver hint expired", None),
+            ApiError::BadSignature => ("Offchain resolver hint has a bad signature", None),
+            ApiError::ResolverUnreachable(details) => {
+                ("Offchain resolver unreachable", Some(details.as_str()))
+            }
+            ApiError::Database(details) => ("Database unavailable", Some(details.as_str())),
+            ApiError::Internal(details) => ("Internal error", Some(details.as_str())),
+        };
+        ErrorBody {
+            code: self.code(),
+            message: message.to_string(),
+            details: details.map(|details| details.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let body = self.body();
+        match &body.details {
+            Some(details) => write!(f, "{}: {}", body.message, details),
+            None => write!(f, "{}", body.message),
+        }
+    }
+}
+
+impl From<ApiError> for ItemError {
+    fn from(error: ApiError) -> Self {
+        ItemError {
+            code: error.code(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(self.body())).into_response()
+    }
+}
+
+impl From<mongodb::error::Error> for ApiError {
+    fn from(error: mongodb::error::Error) -> Self {
+        ApiError::Database(error.to_string())
+    }
+}
+
+impl From<ResolutionError> for ApiError {
+    fn from(error: ResolutionError) -> Self {
+        match error {
+            ResolutionError::NotFound(message) => ApiError::NotFound(message),
+            ResolutionError::Invalid(message) => ApiError::Validation(message),
+            ResolutionError::Database(message) => ApiError::Database(message),
+            ResolutionError::Failed(message) => ApiError::Upstream(message),
+            ResolutionError::ExpiredHint => ApiError::ExpiredHint,
+            ResolutionError::BadSignature => ApiError::BadSignature,
+            ResolutionError::ResolverUnreachable(message) => ApiError::ResolverUnreachable(message),
+        }
+    }
+}
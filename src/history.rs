@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use lazy_static::lazy_static;
+use mongodb::bson::{doc, Bson, Document};
+use reqwest::Url;
+use starknet::{
+    core::types::{BlockId, BlockStatus, MaybePendingBlockWithTxHashes},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{errors::ApiError, models::AppState};
+
+// block timestamps kept at most, the cache is emptied past this
+const MAX_CACHED_TIMESTAMPS: usize = 100_000;
+
+lazy_static! {
+    static ref BLOCK_TIMESTAMPS: BlockTimestamps = BlockTimestamps::default();
+}
+
+/// Timestamps of the blocks fetched by the timestamp lookups. Only blocks accepted on
+/// L1 are kept, the more recent ones can still be reorganized and are fetched again.
+#[derive(Default)]
+pub struct BlockTimestamps(Mutex<HashMap<u64, u64>>);
+
+impl BlockTimestamps {
+    pub fn get(&self, block: u64) -> Option<u64> {
+        self.0.lock().unwrap().get(&block).copied()
+    }
+
+    pub fn insert(&self, block: u64, timestamp: u64, status: BlockStatus) {
+        if status != BlockStatus::AcceptedOnL1 {
+            return;
+        }
+        let mut timestamps = self.0.lock().unwrap();
+        if timestamps.len() >= MAX_CACHED_TIMESTAMPS {
+            timestamps.clear();
+        }
+        timestamps.insert(block, timestamp);
+    }
+}
+
+/// Returns the block requested through the `block` or `timestamp` query parameters,
+/// `None` meaning the latest indexed state.
+///
+/// The indexer closes the rows written by reorganized blocks, so the state at a block
+/// which is not final yet follows the reorgs like the latest state does.
+pub async fn requested_block(
+    state: &Arc<AppState>,
+    block: Option<u64>,
+    timestamp: Option<u64>,
+) -> Result<Option<u64>, ApiError> {
+    match (block, timestamp) {
+        (Some(_), Some(_)) => Err(ApiError::Validation(
+            "Only one of block and timestamp can be specified".to_string(),
+        )),
+        (Some(block), None) => block_bound(block).map(|_| Some(block)),
+        (None, Some(timestamp)) => block_at_timestamp(state, timestamp).await.map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Block number as stored in the `_cursor` bounds of the indexed rows
+pub fn block_bound(block: u64) -> Result<i64, ApiError> {
+    i64::try_from(block).map_err(|_| ApiError::Validation(format!("Invalid block: {}", block)))
+}
+
+/// Rewrites the `_cursor.to` filters of a pipeline so that it returns the rows
+/// which were live at the given block instead of the current ones.
+pub fn at_block(pipeline: Vec<Document>, block: Option<u64>) -> Result<Vec<Document>, ApiError> {
+    match block {
+        Some(block) => {
+            let block = block_bound(block)?;
+            Ok(pipeline
+                .into_iter()
+                .map(|stage| rewrite_document(stage, block))
+                .collect())
+        }
+        None => Ok(pipeline),
+    }
+}
+
+/// Same as `at_block` for a single `find` filter
+pub fn filter_at_block(filter: Document, block: Option<u64>) -> Result<Document, ApiError> {
+    match block {
+        Some(block) => Ok(rewrite_document(filter, block_bound(block)?)),
+        None => Ok(filter),
+    }
+}
+
+fn rewrite_document(document: Document, block: i64) -> Document {
+    let mut rewritten = Document::new();
+    for (key, value) in document {
+        if key == "_cursor.to" && is_live_filter(&value) {
+            // a row is live on [from, to), to is unset while the row is still live
+            rewritten.insert(key, doc! { "$not": { "$lte": block } });
+            rewritten.insert("_cursor.from", doc! { "$lte": block });
+        } else {
+            rewritten.insert(key, rewrite_bson(value, block));
+        }
+    }
+    rewritten
+}
+
+fn rewrite_bson(value: Bson, block: i64) -> Bson {
+    match value {
+        Bson::Document(document) => Bson::Document(rewrite_document(document, block)),
+        Bson::Array(values) => Bson::Array(
+            values
+                .into_iter()
+                .map(|value| rewrite_bson(value, block))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+// matches both `"_cursor.to": null` and `"_cursor.to": { "$exists": false }`
+fn is_live_filter(value: &Bson) -> bool {
+    match value {
+        Bson::Null => true,
+        Bson::Document(document) => {
+            document.len() == 1 && matches!(document.get_bool("$exists"), Ok(false))
+        }
+        _ => false,
+    }
+}
+
+// Binary search of the last block mined at or before the timestamp
+async fn block_at_timestamp(state: &Arc<AppState>, timestamp: u64) -> Result<u64, ApiError> {
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    if block_timestamp(&provider, 0).await? > timestamp {
+        return Err(ApiError::Validation(
+            "No block was mined before this timestamp".to_string(),
+        ));
+    }
+    let mut low = 0;
+    let mut high = provider
+        .block_number()
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Unable to fetch the latest block: {}", e)))?;
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+        if block_timestamp(&provider, middle).await? <= timestamp {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Ok(low)
+}
+
+async fn block_timestamp(
+    provider: &JsonRpcClient<HttpTransport>,
+    block: u64,
+) -> Result<u64, ApiError> {
+    if let Some(timestamp) = BLOCK_TIMESTAMPS.get(block) {
+        return Ok(timestamp);
+    }
+    match provider
+        .get_block_with_tx_hashes(BlockId::Number(block))
+        .await
+    {
+        Ok(MaybePendingBlockWithTxHashes::Block(block_data)) => {
+            BLOCK_TIMESTAMPS.insert(block, block_data.timestamp, block_data.status);
+            Ok(block_data.timestamp)
+        }
+        Ok(MaybePendingBlockWithTxHashes::PendingBlock(block)) => Ok(block.timestamp),
+        Err(e) => Err(ApiError::Upstream(format!(
+            "Unable to fetch block {}: {}",
+            block, e
+        ))),
+    }
+}
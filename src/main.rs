@@ -1,10 +1,21 @@
 #![recursion_limit = "256"]
 
+mod address;
+mod admin;
+mod cache;
 mod config;
 mod ecdsa_sign;
 mod endpoints;
+mod errors;
+mod extractors;
+mod history;
 mod logger;
 mod models;
+mod offchain_gateway;
+mod openapi;
+mod price;
+mod price_oracle;
+mod quotes;
 mod resolving;
 mod tax;
 mod utils;
@@ -20,7 +31,17 @@ use utils::WithState;
 
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::resolving::update_offchain_resolvers;
+use crate::{
+    cache::{create_cursor_indexes, invalidate_indexed_changes, ResponseCache},
+    endpoints::crosschain::ethereum::{
+        record_handlers::RecordHandlers,
+        social_names::{create_social_names_indexes, refresh_social_names},
+    },
+    offchain_gateway::OffchainGateway,
+    price_oracle::Oracle,
+    quotes::{create_altcoin_quotes_index, refresh_quotes, QuoteCache},
+    resolving::{update_offchain_resolvers, ResolverChain},
+};
 
 lazy_static::lazy_static! {
     pub static ref ROUTE_REGISTRY: Mutex<Vec<Box<dyn WithState>>> = Mutex::new(Vec::new());
@@ -32,6 +53,7 @@ mod tests;
 #[tokio::main]
 async fn main() {
     let conf = config::load();
+    address::configure(&conf.addresses);
     let logger = logger::Logger::new(&conf.watchtower);
 
     // Testing logger when server started
@@ -59,6 +81,38 @@ async fn main() {
         return;
     }
 
+    let record_handlers = match RecordHandlers::new(&conf) {
+        Ok(record_handlers) => record_handlers,
+        Err(e) => {
+            logger.severe(format!("error: unable to load record handlers: {:?}", e));
+            return;
+        }
+    };
+
+    let price_oracle = match Oracle::new(&conf) {
+        Ok(price_oracle) => price_oracle,
+        Err(e) => {
+            logger.severe(format!("error: unable to load price sources: {:?}", e));
+            return;
+        }
+    };
+
+    let offchain_gateway = match OffchainGateway::new(&conf.offchain_resolving) {
+        Ok(offchain_gateway) => offchain_gateway,
+        Err(e) => {
+            logger.severe(format!("error: invalid offchain resolving settings: {}", e));
+            return;
+        }
+    };
+
+    let quotes = match QuoteCache::new(&conf.price_oracle) {
+        Ok(quotes) => quotes,
+        Err(e) => {
+            logger.severe(format!("error: invalid price oracle settings: {}", e));
+            return;
+        }
+    };
+
     let shared_state = Arc::new(models::AppState {
         conf: conf.clone(),
         starknetid_db: Client::with_options(starknetid_client_options)
@@ -73,6 +127,12 @@ async fn main() {
         states,
         dynamic_offchain_resolvers: Arc::new(Mutex::new(HashMap::new())),
         logger: logger.clone(),
+        resolvers: ResolverChain::default(),
+        offchain_gateway,
+        cache: ResponseCache::new(&conf.cache),
+        record_handlers,
+        price_oracle,
+        quotes,
     });
     // we will know by looking at the log number which db has an issue
     for db in [&shared_state.starknetid_db, &shared_state.sales_db] {
@@ -84,11 +144,22 @@ async fn main() {
         }
     }
 
-    // refresh offchain resolvers from indexed data
+    if let Err(e) = create_altcoin_quotes_index(&shared_state).await {
+        logger.warning(format!("Unable to create the altcoin_quotes index: {}", e));
+    }
+    if let Err(e) = create_social_names_indexes(&shared_state).await {
+        logger.warning(format!("Unable to create the social_names indexes: {}", e));
+    }
+    if let Err(e) = create_cursor_indexes(&shared_state).await {
+        logger.warning(format!("Unable to create the indexed changes indexes: {}", e));
+    }
+
+    // refresh offchain resolvers and cached responses from indexed data
     let refresh_state = shared_state.clone();
     tokio::spawn(async move {
         loop {
             update_offchain_resolvers(&refresh_state).await;
+            invalidate_indexed_changes(&refresh_state).await;
             sleep(Duration::from_millis(
                 (conf.variables.refresh_delay * 1000.0) as u64,
             ))
@@ -96,6 +167,32 @@ async fn main() {
         }
     });
 
+    // refresh expired social usernames of the text records
+    let social_names_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            refresh_social_names(&social_names_state).await;
+            sleep(Duration::from_millis(
+                (social_names_state.conf.variables.refresh_delay * 1000.0) as u64,
+            ))
+            .await;
+        }
+    });
+
+    // keep the altcoin prices and signed quotes fresh
+    if conf.price_oracle.refresh_delay > 0.0 {
+        let quotes_state = shared_state.clone();
+        tokio::spawn(async move {
+            loop {
+                refresh_quotes(&quotes_state).await;
+                sleep(Duration::from_millis(
+                    (quotes_state.conf.price_oracle.refresh_delay * 1000.0) as u64,
+                ))
+                .await;
+            }
+        });
+    }
+
     let cors = CorsLayer::new().allow_headers(Any).allow_origin(Any);
     let app = ROUTE_REGISTRY
         .lock()
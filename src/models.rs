@@ -5,15 +5,22 @@ use mongodb::{
 use starknet::core::types::FieldElement;
 
 use crate::{
+    cache::ResponseCache,
     config::{Config, OffchainResolver},
+    endpoints::crosschain::ethereum::record_handlers::RecordHandlers,
     utils::to_hex,
     logger::Logger, 
+    offchain_gateway::OffchainGateway,
+    price_oracle::Oracle,
+    quotes::QuoteCache,
+    resolving::ResolverChain,
 };
 use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
 };
+use utoipa::ToSchema;
 
 pub struct AppState {
     pub conf: Config,
@@ -23,6 +30,12 @@ pub struct AppState {
     pub states: States,
     pub dynamic_offchain_resolvers: Arc<Mutex<HashMap<String, OffchainResolver>>>,
     pub logger : Logger,
+    pub resolvers: ResolverChain,
+    pub offchain_gateway: OffchainGateway,
+    pub cache: ResponseCache,
+    pub record_handlers: RecordHandlers,
+    pub price_oracle: Oracle,
+    pub quotes: QuoteCache,
 }
 
 fn serialize_felt<S>(field_element: &FieldElement, serializer: S) -> Result<S::Ok, S::Error>
@@ -71,11 +84,13 @@ impl<'a> Serialize for SerializedFelt<'a> {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct IdentityData {
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub id: FieldElement,
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub owner: FieldElement,
     pub main: bool,
     pub creation_date: u64,
@@ -102,7 +117,7 @@ where
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct Domain {
     pub domain: String,
     pub migrated: bool,
@@ -110,38 +125,49 @@ pub struct Domain {
     pub creation_date: u64,
     pub expiry: Option<u64>,
     #[serde(serialize_with = "serialize_opt_felt")]
+    #[schema(value_type = Option<String>)]
     pub resolver: Option<FieldElement>,
     #[serde(serialize_with = "serialize_opt_felt")]
+    #[schema(value_type = Option<String>)]
     pub legacy_address: Option<FieldElement>,
     #[serde(serialize_with = "serialize_opt_felt")]
+    #[schema(value_type = Option<String>)]
     pub rev_address: Option<FieldElement>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct UserData {
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub field: FieldElement,
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub data: FieldElement,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct VerifierData {
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub verifier: FieldElement,
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub field: FieldElement,
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub data: FieldElement,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct ExtendedVerifierData {
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub verifier: FieldElement,
     #[serde(serialize_with = "serialize_felt")]
+    #[schema(value_type = String)]
     pub field: FieldElement,
     #[serde(serialize_with = "serialize_vec_felt")]
+    #[schema(value_type = Vec<String>)]
     pub extended_data: Vec<FieldElement>,
 }
 
@@ -157,7 +183,7 @@ pub struct States {
     pub states: HashMap<String, State>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct OffchainResolverHint {
     pub address: FieldElement,
     pub r: FieldElement,
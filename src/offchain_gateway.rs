@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    config::{seconds_setting, OffchainResolver, OffchainResolving},
+    models::OffchainResolverHint,
+    resolving::ResolutionError,
+};
+
+// a cached hint is not reused when it expires in less than this amount of seconds
+const HINT_VALIDITY_MARGIN: u64 = 10;
+
+#[derive(Default)]
+struct UriHealth {
+    consecutive_failures: u32,
+    skipped_until: Option<Instant>,
+}
+
+/// Queries offchain resolvers gateways, trying each uri of a resolver in turn.
+/// A uri failing too many times in a row is skipped for a while (circuit breaking)
+/// and valid hints are cached until their max_validity.
+pub struct OffchainGateway {
+    client: reqwest::Client,
+    failure_threshold: u32,
+    cooldown: Duration,
+    uri_health: Mutex<HashMap<String, UriHealth>>,
+    hints: Mutex<HashMap<String, OffchainResolverHint>>,
+}
+
+impl OffchainGateway {
+    pub fn new(settings: &OffchainResolving) -> Result<Self, String> {
+        Ok(OffchainGateway {
+            client: reqwest::Client::builder()
+                .timeout(seconds_setting(
+                    "offchain_resolving.timeout",
+                    settings.timeout,
+                )?)
+                .build()
+                .map_err(|e| format!("unable to build the offchain resolving client: {}", e))?,
+            failure_threshold: settings.failure_threshold.max(1),
+            cooldown: seconds_setting("offchain_resolving.cooldown", settings.cooldown)?,
+            uri_health: Mutex::new(HashMap::new()),
+            hints: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub async fn get_hint(
+        &self,
+        offchain_resolver: &OffchainResolver,
+        domain: &str,
+    ) -> Result<OffchainResolverHint, ResolutionError> {
+        if let Some(hint) = self.cached_hint(domain) {
+            return Ok(hint);
+        }
+
+        let mut errors = Vec::new();
+        for uri in &offchain_resolver.uri {
+            if self.is_skipped(uri) {
+                errors.push(format!("{}: skipped after repeated failures", uri));
+                continue;
+            }
+            let url = format!("{}{}", uri, domain);
+            let response = match self
+                .client
+                .get(&url)
+                .header("accept", "application/json")
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_server_error() => {
+                    self.record_failure(uri);
+                    errors.push(format!("{}: status {}", uri, response.status()));
+                    continue;
+                }
+                Ok(response) => response,
+                Err(e) => {
+                    self.record_failure(uri);
+                    errors.push(format!("{}: {}", uri, e));
+                    continue;
+                }
+            };
+            let text = match response.text().await {
+                Ok(text) => text,
+                Err(e) => {
+                    self.record_failure(uri);
+                    errors.push(format!("{}: {}", uri, e));
+                    continue;
+                }
+            };
+            self.record_success(uri);
+
+            // the gateway answered, a response which is not a hint is its final answer
+            let hint = serde_json::from_str::<OffchainResolverHint>(&text)
+                .map_err(|_| ResolutionError::Failed(text))?;
+            self.cache_hint(domain, &hint);
+            return Ok(hint);
+        }
+
+        Err(ResolutionError::ResolverUnreachable(errors.join(", ")))
+    }
+
+    pub fn forget_hint(&self, domain: &str) {
+        self.hints.lock().unwrap().remove(domain);
+    }
+
+    fn cached_hint(&self, domain: &str) -> Option<OffchainResolverHint> {
+        let now = now_secs();
+        let mut hints = self.hints.lock().unwrap();
+        match hints.get(domain) {
+            Some(hint) if hint.max_validity > now + HINT_VALIDITY_MARGIN => Some(hint.clone()),
+            Some(_) => {
+                hints.remove(domain);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn cache_hint(&self, domain: &str, hint: &OffchainResolverHint) {
+        let now = now_secs();
+        if hint.max_validity <= now + HINT_VALIDITY_MARGIN {
+            return;
+        }
+        let mut hints = self.hints.lock().unwrap();
+        // drop expired hints so the cache does not grow forever
+        hints.retain(|_, cached| cached.max_validity > now);
+        hints.insert(domain.to_string(), hint.clone());
+    }
+
+    fn is_skipped(&self, uri: &str) -> bool {
+        match self.uri_health.lock().unwrap().get(uri) {
+            Some(UriHealth {
+                skipped_until: Some(until),
+                ..
+            }) => Instant::now() < *until,
+            _ => false,
+        }
+    }
+
+    fn record_failure(&self, uri: &str) {
+        let mut uri_health = self.uri_health.lock().unwrap();
+        let health = uri_health.entry(uri.to_string()).or_default();
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= self.failure_threshold {
+            health.skipped_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+
+    fn record_success(&self, uri: &str) {
+        self.uri_health.lock().unwrap().remove(uri);
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,115 @@
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use axum_auto_routes::route;
+use utoipa::OpenApi;
+
+use crate::{endpoints, errors, models, price_oracle, quotes};
+
+/// OpenAPI document of every endpoint, used to generate the typed API clients.
+/// A new endpoint must be added to `paths` and its types to `schemas`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Starknet ID API"),
+    paths(
+        endpoints::addr_has_rev::handler,
+        endpoints::addr_to_available_ids::handler,
+        endpoints::addr_to_domain::handler,
+        endpoints::addr_to_external_domains::handler,
+        endpoints::addr_to_full_ids::handler,
+        endpoints::addr_to_token_id::handler,
+        endpoints::addrs_to_domains::handler,
+        endpoints::campaigns::get_free_domain::handler,
+        endpoints::crosschain::claims::handler,
+        endpoints::crosschain::ethereum::claim::handler,
+        endpoints::crosschain::ethereum::resolve::handler,
+        endpoints::crosschain::solana::claim::handler,
+        endpoints::crosschain::solana::claim_ledger::handler,
+        endpoints::data_to_ids::handler,
+        endpoints::domain_history::handler,
+        endpoints::domain_to_addr::handler,
+        endpoints::domain_to_data::handler,
+        endpoints::domains_to_addrs::handler,
+        endpoints::galxe::verify::handler,
+        endpoints::get_altcoin_quote::handler,
+        endpoints::get_altcoin_quote_history::handler,
+        endpoints::get_domain_price::handler,
+        endpoints::get_expiring_domains::handler,
+        endpoints::id_to_data::handler,
+        endpoints::referral::add_click::handler,
+        endpoints::referral::click_count::handler,
+        endpoints::referral::revenue::handler,
+        endpoints::referral::sales_count::handler,
+        endpoints::renewal::get_metahash::handler,
+        endpoints::renewal::get_non_subscribed_domains::handler,
+        endpoints::renewal::get_renewal_data::handler,
+        endpoints::renewal::get_subscription_info::handler,
+        endpoints::starkscan::fetch_nfts::handler,
+        endpoints::stats::count_addrs::handler,
+        endpoints::stats::count_club_domains::handler,
+        endpoints::stats::count_created::handler,
+        endpoints::stats::count_domains::handler,
+        endpoints::stats::count_ids::handler,
+        endpoints::stats::count_minted_domains::handler,
+        endpoints::stats::count_renewed::handler,
+        endpoints::stats::expired_club_domains::handler,
+        endpoints::uri::handler,
+    ),
+    components(schemas(
+        endpoints::addr_has_rev::AddrToDomainData,
+        endpoints::addr_to_available_ids::AvailableIds,
+        endpoints::addr_to_domain::AddrToDomainData,
+        endpoints::addr_to_external_domains::DomainData,
+        endpoints::addr_to_full_ids::FullId,
+        endpoints::addr_to_full_ids::FullIdResponse,
+        endpoints::addr_to_token_id::TokenIdData,
+        endpoints::addrs_to_domains::AddrToDomainData,
+        endpoints::addrs_to_domains::AddrToDomainsQuery,
+        endpoints::crosschain::claims::CrosschainClaim,
+        endpoints::crosschain::ethereum::claim::EnsClaimQuery,
+        endpoints::crosschain::ethereum::resolve::ResolveQuery,
+        endpoints::crosschain::solana::claim::SigQuery,
+        endpoints::crosschain::solana::claim_ledger::SigQuery,
+        endpoints::data_to_ids::StarknetIdData,
+        endpoints::domain_history::HistoryChange,
+        endpoints::domain_history::HistoryEvent,
+        endpoints::domain_to_addr::DomainToAddrData,
+        endpoints::domains_to_addrs::DomainToAddrData,
+        endpoints::domains_to_addrs::DomainsToAddrsQuery,
+        endpoints::galxe::verify::EmailQuery,
+        endpoints::galxe::verify::SimpleResponse,
+        endpoints::get_altcoin_quote_history::QuoteCandle,
+        endpoints::get_altcoin_quote_history::QuoteHistory,
+        endpoints::get_domain_price::DomainPriceData,
+        endpoints::get_expiring_domains::IdDetails,
+        endpoints::get_expiring_domains::ExpiringDomains,
+        endpoints::referral::add_click::AddClickQuery,
+        endpoints::referral::click_count::Data,
+        endpoints::referral::revenue::Data,
+        endpoints::referral::sales_count::Data,
+        endpoints::renewal::get_metahash::GetMetaHashData,
+        endpoints::starkscan::fetch_nfts::StarkscanApiResult,
+        endpoints::starkscan::fetch_nfts::StarkscanNftProps,
+        endpoints::stats::count_addrs::CountAddrsData,
+        endpoints::stats::count_created::CountCreatedData,
+        endpoints::stats::count_domains::CountDomainsData,
+        endpoints::stats::count_ids::CountDomainsData,
+        endpoints::stats::count_minted_domains::CountMintedDomainsData,
+        endpoints::stats::count_renewed::CountRenewedData,
+        endpoints::uri::TokenURI,
+        endpoints::uri::Attribute,
+        errors::ErrorBody,
+        errors::ItemError,
+        models::IdentityData,
+        models::Domain,
+        models::UserData,
+        models::VerifierData,
+        models::ExtendedVerifierData,
+        price_oracle::SourcePrice,
+        quotes::StoredQuote,
+    ))
+)]
+pub struct ApiDoc;
+
+#[route(get, "/openapi.json", crate::openapi)]
+pub async fn handler() -> impl IntoResponse {
+    (StatusCode::OK, Json(ApiDoc::openapi())).into_response()
+}
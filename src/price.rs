@@ -0,0 +1,152 @@
+use std::{fmt, str::FromStr};
+
+use ethers::types::{U256, U512};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Decimals of the fixed point prices
+pub const PRICE_DECIMALS: usize = 36;
+// 10^77 is the largest power of ten fitting in a U256
+const MAX_EXP10: usize = 77;
+
+fn exp10(exponent: usize) -> Option<U256> {
+    (exponent <= MAX_EXP10).then(|| U256::exp10(exponent))
+}
+
+// a * b / c without intermediate overflow, rounded down
+fn mul_div(a: U256, b: U256, c: U256) -> Option<U256> {
+    if c.is_zero() {
+        return None;
+    }
+    U256::try_from(a.full_mul(b) / U512::from(c)).ok()
+}
+
+/// Price of a token in ETH, as a fixed point number with `PRICE_DECIMALS` decimals so
+/// that quotes are computed on integers only.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Price(U256);
+
+impl Price {
+    pub fn from_raw(raw: U256) -> Self {
+        Price(raw)
+    }
+
+    pub fn raw(&self) -> U256 {
+        self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Price of `value / 10^decimals`, e.g. an oracle answer and its decimals
+    pub fn from_scaled(value: U256, decimals: usize) -> Option<Self> {
+        if decimals <= PRICE_DECIMALS {
+            value
+                .checked_mul(exp10(PRICE_DECIMALS - decimals)?)
+                .map(Price)
+        } else {
+            Some(Price(value / exp10(decimals - PRICE_DECIMALS)?))
+        }
+    }
+
+    /// Parses a positive decimal number such as `0.00041` or `4.1e-4`, decimals beyond
+    /// `PRICE_DECIMALS` are truncated.
+    pub fn from_decimal_str(value: &str) -> Option<Self> {
+        let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+            None => (value, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{}{}", integer, fraction);
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let digits = digits.trim_start_matches('0');
+        let value = if digits.is_empty() {
+            U256::zero()
+        } else {
+            U256::from_dec_str(digits).ok()?
+        };
+        // the value is digits / 10^scale
+        let scale = fraction.len() as i64 - exponent;
+        if scale < 0 {
+            let value = value.checked_mul(exp10(usize::try_from(-scale).ok()?)?)?;
+            Price::from_scaled(value, 0)
+        } else if scale as usize > PRICE_DECIMALS + MAX_EXP10 {
+            Some(Price::default())
+        } else {
+            Price::from_scaled(value, scale as usize)
+        }
+    }
+
+    /// Price of this token in another one, e.g. a USD price divided by the USD price of
+    /// ETH. Rounded down, None when dividing by zero.
+    pub fn checked_div(self, other: Price) -> Option<Self> {
+        mul_div(self.0, exp10(PRICE_DECIMALS)?, other.0).map(Price)
+    }
+
+    /// Mean of two prices, rounded down
+    pub fn mean(self, other: Price) -> Self {
+        let two = U256::from(2);
+        Price(self.0 / two + other.0 / two + (self.0 % two + other.0 % two) / two)
+    }
+
+    /// Distance between two prices in basis points of this price, None for a zero price
+    pub fn deviation_bps(self, other: Price) -> Option<U256> {
+        let difference = if self.0 > other.0 {
+            self.0 - other.0
+        } else {
+            other.0 - self.0
+        };
+        mul_div(difference, 10000.into(), self.0)
+    }
+
+    /// Amount of a token with `decimals` decimals, in its smallest unit, worth one ETH:
+    /// `10^decimals / price` rounded down. None for a zero price or an amount
+    /// overflowing a U256.
+    pub fn quote_wei(self, decimals: usize) -> Option<U256> {
+        mul_div(exp10(decimals)?, exp10(PRICE_DECIMALS)?, self.0)
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let one = U256::exp10(PRICE_DECIMALS);
+        let (integer, fraction) = (self.0 / one, self.0 % one);
+        if fraction.is_zero() {
+            return write!(f, "{}", integer);
+        }
+        let digits = fraction.to_string();
+        let fraction = "0".repeat(PRICE_DECIMALS - digits.len()) + &digits;
+        write!(f, "{}.{}", integer, fraction.trim_end_matches('0'))
+    }
+}
+
+impl FromStr for Price {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Price::from_decimal_str(value).ok_or_else(|| format!("Invalid price: {}", value))
+    }
+}
+
+// prices are written as decimal strings, JSON numbers can't hold them
+impl Serialize for Price {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
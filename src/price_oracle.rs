@@ -0,0 +1,416 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use axum::async_trait;
+use ethers::types::U256;
+use futures::future::join_all;
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{value::RawValue, Value};
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, FieldElement, FunctionCall},
+        utils::cairo_short_string_to_felt,
+    },
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use utoipa::ToSchema;
+
+use crate::{
+    config::{seconds_setting, Config, PriceOracle, PriceSource},
+    endpoints::crosschain::ethereum::record_handlers::extract_json_path,
+    errors::ApiError,
+    price::Price,
+};
+
+// prices are quoted against ETH
+const ETH_ADDRESS: &str = "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+// DataType::SpotEntry of the Pragma oracle
+const PRAGMA_SPOT_ENTRY: FieldElement = FieldElement::ZERO;
+// in seconds, Pragma prices updated longer ago are refused
+const PRAGMA_MAX_AGE: f64 = 600.0;
+
+/// Price of a token in ETH, according to one source
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct SourcePrice {
+    pub source: String,
+    #[schema(value_type = String)]
+    pub price: Price,
+}
+
+/// Median price of a token in ETH and the sources it was computed from
+#[derive(Clone, Debug)]
+pub struct OraclePrice {
+    pub price: Price,
+    pub sources: Vec<SourcePrice>,
+}
+
+/// A source of token prices, returns None for the tokens it doesn't quote
+#[async_trait]
+pub trait PriceFeed: Send + Sync {
+    async fn price(&self, client: &Client, token: &FieldElement) -> Result<Option<Price>>;
+}
+
+#[derive(Deserialize, Debug)]
+struct AvnuApiResult {
+    address: FieldElement,
+    // raw JSON number as written by the API, serde_json would round it to a f64
+    #[serde(rename = "currentPrice")]
+    current_price: Box<RawValue>,
+}
+
+/// Reads the price of a token from an AVNU `/tokens/short` response, without going
+/// through a float. None when the token isn't listed.
+pub fn parse_avnu_price(text: &str, token: &FieldElement) -> Result<Option<Price>> {
+    let results = serde_json::from_str::<Vec<AvnuApiResult>>(text).with_context(|| {
+        format!(
+            "Failed to deserialize result from AVNU API for response: {}",
+            text
+        )
+    })?;
+    match results.iter().find(|result| &result.address == token) {
+        Some(result) => {
+            let price = result.current_price.get().trim_matches('"');
+            Price::from_decimal_str(price)
+                .map(Some)
+                .ok_or_else(|| anyhow!("Invalid AVNU price {}", price))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Current prices of the AVNU API
+pub struct AvnuFeed {
+    api_url: String,
+}
+
+#[async_trait]
+impl PriceFeed for AvnuFeed {
+    async fn price(&self, client: &Client, token: &FieldElement) -> Result<Option<Price>> {
+        let url = format!("{}/tokens/short?in={}", self.api_url, ETH_ADDRESS);
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to fetch quote from AVNU api")?;
+        let text = response
+            .text()
+            .await
+            .context("Failed to get JSON response while fetching token quote")?;
+        parse_avnu_price(&text, token)
+    }
+}
+
+/// Median spot prices of the Pragma oracle contract, read through a Starknet RPC
+pub struct PragmaFeed {
+    provider: JsonRpcClient<HttpTransport>,
+    contract: FieldElement,
+    eth_pair: FieldElement,
+    pairs: HashMap<FieldElement, FieldElement>,
+    max_age: Duration,
+}
+
+/// Reads the price of a `get_data_median` response, refusing prices updated more than
+/// `max_age` before `now` so that a stalled feed doesn't count in the median.
+pub fn parse_pragma_response(
+    result: &[FieldElement],
+    now: u64,
+    max_age: Duration,
+) -> Result<Price> {
+    // PragmaPricesResponse starts with the price, its decimals and its update time
+    let (price, decimals, last_updated) = match result {
+        [price, decimals, last_updated, ..] => (*price, *decimals, *last_updated),
+        _ => bail!("Invalid response from the Pragma oracle"),
+    };
+    let price = u128::try_from(price).context("Invalid Pragma price")?;
+    let decimals = u32::try_from(decimals).context("Invalid Pragma decimals")?;
+    let last_updated = u64::try_from(last_updated).context("Invalid Pragma timestamp")?;
+    if price == 0 {
+        bail!("No Pragma price for this pair");
+    }
+    if now.saturating_sub(last_updated) > max_age.as_secs() {
+        bail!("Stale Pragma price, last updated at {}", last_updated);
+    }
+    Price::from_scaled(U256::from(price), decimals as usize)
+        .ok_or_else(|| anyhow!("Invalid Pragma decimals {}", decimals))
+}
+
+impl PragmaFeed {
+    async fn spot_price(&self, pair: FieldElement) -> Result<Price> {
+        let result = self
+            .provider
+            .call(
+                FunctionCall {
+                    contract_address: self.contract,
+                    entry_point_selector: selector!("get_data_median"),
+                    calldata: vec![PRAGMA_SPOT_ENTRY, pair],
+                },
+                BlockId::Tag(BlockTag::Latest),
+            )
+            .await
+            .context("Failed to call the Pragma oracle")?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        parse_pragma_response(&result, now, self.max_age)
+    }
+}
+
+#[async_trait]
+impl PriceFeed for PragmaFeed {
+    async fn price(&self, _client: &Client, token: &FieldElement) -> Result<Option<Price>> {
+        let pair = match self.pairs.get(token) {
+            Some(pair) => *pair,
+            None => return Ok(None),
+        };
+        let (price, eth_price) =
+            tokio::try_join!(self.spot_price(pair), self.spot_price(self.eth_pair))?;
+        Ok(price.checked_div(eth_price))
+    }
+}
+
+/// Prices read from a JSON HTTP API, such as the ticker of an exchange. `{pair}` is
+/// replaced in the url by the pair of the token, then by the ETH pair.
+pub struct HttpJsonFeed {
+    url: String,
+    json_path: String,
+    eth_pair: String,
+    pairs: HashMap<FieldElement, String>,
+}
+
+impl HttpJsonFeed {
+    async fn pair_price(&self, client: &Client, pair: &str) -> Result<Price> {
+        let response = client
+            .get(self.url.replace("{pair}", pair))
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .context("Failed to send request")?;
+        if response.status() != StatusCode::OK {
+            bail!("API returned non-OK status: {}", response.status());
+        }
+        let json: Value = response
+            .json()
+            .await
+            .context("Failed to parse JSON response")?;
+        extract_json_path(&json, &self.json_path)
+            .and_then(|price| Price::from_decimal_str(&price))
+            .filter(|price| !price.is_zero())
+            .ok_or_else(|| anyhow!("No price found at {} for {}", self.json_path, pair))
+    }
+}
+
+#[async_trait]
+impl PriceFeed for HttpJsonFeed {
+    async fn price(&self, client: &Client, token: &FieldElement) -> Result<Option<Price>> {
+        let pair = match self.pairs.get(token) {
+            Some(pair) => pair,
+            None => return Ok(None),
+        };
+        let (price, eth_price) = tokio::try_join!(
+            self.pair_price(client, pair),
+            self.pair_price(client, &self.eth_pair)
+        )?;
+        Ok(price.checked_div(eth_price))
+    }
+}
+
+/// Median of the prices, None when there is none
+pub fn median(prices: &[Price]) -> Option<Price> {
+    let mut prices = prices.to_vec();
+    prices.sort();
+    let middle = prices.len() / 2;
+    match prices.len() {
+        0 => None,
+        len if len % 2 == 0 => Some(prices[middle - 1].mean(prices[middle])),
+        _ => Some(prices[middle]),
+    }
+}
+
+/// Combines the prices of the sources into their median. Fails when fewer than
+/// `min_sources` answered or when a price is more than `max_deviation` percent away
+/// from the median.
+pub fn aggregate(
+    sources: Vec<SourcePrice>,
+    min_sources: usize,
+    max_deviation: f64,
+) -> Result<OraclePrice, String> {
+    if sources.len() < min_sources.max(1) {
+        return Err(format!(
+            "Only {} price sources answered, {} required",
+            sources.len(),
+            min_sources.max(1)
+        ));
+    }
+    let prices: Vec<Price> = sources.iter().map(|source| source.price).collect();
+    let price = median(&prices).unwrap_or_default();
+    let max_deviation_bps = U256::from((max_deviation * 100.0).round().max(0.0) as u64);
+    for source in &sources {
+        let deviation_bps = price
+            .deviation_bps(source.price)
+            .ok_or_else(|| "Invalid median price".to_string())?;
+        if deviation_bps > max_deviation_bps {
+            return Err(format!(
+                "Price sources disagree: {} is {} basis points away from the median",
+                source.source, deviation_bps
+            ));
+        }
+    }
+    Ok(OraclePrice { price, sources })
+}
+
+/// Queries every configured price source at once and returns the median price of a
+/// token, as long as enough sources answered and they agree with each other.
+pub struct Oracle {
+    client: Client,
+    timeout: Duration,
+    min_sources: usize,
+    max_deviation: f64,
+    feeds: Vec<(String, Box<dyn PriceFeed>)>,
+}
+
+impl Oracle {
+    pub fn new(conf: &Config) -> Result<Self> {
+        let settings: &PriceOracle = &conf.price_oracle;
+        let mut oracle = Oracle {
+            client: Client::new(),
+            timeout: seconds_setting("price_oracle.timeout", settings.timeout)
+                .map_err(|e| anyhow!(e))?,
+            min_sources: settings.min_sources,
+            max_deviation: settings.max_deviation,
+            feeds: Vec::new(),
+        };
+        if settings.sources.is_empty() {
+            oracle.register(
+                "avnu",
+                Box::new(AvnuFeed {
+                    api_url: conf.altcoins.avnu_api.clone(),
+                }),
+            );
+        }
+        for (name, source) in &settings.sources {
+            let feed = build_feed(conf, source)
+                .with_context(|| format!("Invalid price source {}", name))?;
+            oracle.register(name, feed);
+        }
+        Ok(oracle)
+    }
+
+    pub fn register(&mut self, name: &str, feed: Box<dyn PriceFeed>) {
+        self.feeds.push((name.to_string(), feed));
+    }
+
+    pub async fn get_price(&self, token: &FieldElement) -> Result<OraclePrice, ApiError> {
+        let results = join_all(self.feeds.iter().map(|(name, feed)| async move {
+            let result = tokio::time::timeout(self.timeout, feed.price(&self.client, token))
+                .await
+                .map_err(|_| anyhow!("timed out"))
+                .and_then(|result| result);
+            (name, result)
+        }))
+        .await;
+
+        let mut sources = Vec::new();
+        let mut errors = Vec::new();
+        for (name, result) in results {
+            match result {
+                Ok(Some(price)) if !price.is_zero() => sources.push(SourcePrice {
+                    source: name.clone(),
+                    price,
+                }),
+                Ok(Some(_)) => errors.push(format!("{}: zero price", name)),
+                Ok(None) => {}
+                Err(e) => errors.push(format!("{}: {:?}", name, e)),
+            }
+        }
+        if sources.is_empty() && errors.is_empty() {
+            return Err(ApiError::NotFound("Token address not found".to_string()));
+        }
+        aggregate(sources, self.min_sources, self.max_deviation).map_err(|e| {
+            if errors.is_empty() {
+                ApiError::Upstream(e)
+            } else {
+                ApiError::Upstream(format!("{} ({})", e, errors.join(", ")))
+            }
+        })
+    }
+}
+
+// Pairs of a source keyed by token address instead of token name
+fn token_pairs(conf: &Config, source: &PriceSource) -> Result<HashMap<FieldElement, String>> {
+    source
+        .pairs
+        .iter()
+        .map(|(symbol, pair)| {
+            conf.altcoins
+                .symbols
+                .iter()
+                .find(|(_, name)| *name == symbol)
+                .map(|(address, _)| (*address, pair.clone()))
+                .ok_or_else(|| anyhow!("Unknown token {}", symbol))
+        })
+        .collect()
+}
+
+// Builds the feed named in the config of a price source
+fn build_feed(conf: &Config, source: &PriceSource) -> Result<Box<dyn PriceFeed>> {
+    let feed: Box<dyn PriceFeed> = match source.source.as_str() {
+        "Avnu" => Box::new(AvnuFeed {
+            api_url: source
+                .api_url
+                .clone()
+                .unwrap_or_else(|| conf.altcoins.avnu_api.clone()),
+        }),
+        "Pragma" => {
+            let rpc_url = source.rpc_url.as_ref().unwrap_or(&conf.variables.rpc_url);
+            let contract = source
+                .contract
+                .ok_or_else(|| anyhow!("Pragma requires a contract"))?;
+            let eth_pair = source
+                .eth_pair
+                .as_ref()
+                .ok_or_else(|| anyhow!("Pragma requires an eth_pair"))?;
+            let to_felt = |pair: &str| {
+                cairo_short_string_to_felt(pair).with_context(|| format!("Invalid pair {}", pair))
+            };
+            Box::new(PragmaFeed {
+                provider: JsonRpcClient::new(HttpTransport::new(
+                    Url::parse(rpc_url).context("Invalid rpc_url")?,
+                )),
+                contract,
+                eth_pair: to_felt(eth_pair)?,
+                pairs: token_pairs(conf, source)?
+                    .into_iter()
+                    .map(|(token, pair)| Ok((token, to_felt(&pair)?)))
+                    .collect::<Result<_>>()?,
+                max_age: seconds_setting(
+                    "price_oracle.sources.max_age",
+                    source.max_age.unwrap_or(PRAGMA_MAX_AGE),
+                )
+                .map_err(|e| anyhow!(e))?,
+            })
+        }
+        "HttpJson" => Box::new(HttpJsonFeed {
+            url: source
+                .url
+                .clone()
+                .ok_or_else(|| anyhow!("HttpJson requires an url"))?,
+            json_path: source
+                .json_path
+                .clone()
+                .ok_or_else(|| anyhow!("HttpJson requires a json_path"))?,
+            eth_pair: source
+                .eth_pair
+                .clone()
+                .ok_or_else(|| anyhow!("HttpJson requires an eth_pair"))?,
+            pairs: token_pairs(conf, source)?,
+        }),
+        source => bail!("Unknown price source {}", source),
+    };
+    Ok(feed)
+}
@@ -0,0 +1,352 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use ethers::types::U256;
+use futures::future::join_all;
+use mongodb::{
+    bson::{doc, to_document, Document},
+    Collection, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use starknet::core::{
+    crypto::{ecdsa_sign, pedersen_hash},
+    types::FieldElement,
+};
+
+use crate::{
+    config::{seconds_setting, AltcoinData, PriceOracle},
+    errors::ApiError,
+    models::AppState,
+    price::Price,
+    price_oracle::{OraclePrice, SourcePrice},
+    utils::to_hex,
+};
+use utoipa::ToSchema;
+
+lazy_static::lazy_static! {
+    static ref QUOTE_STR: FieldElement = FieldElement::from_dec_str("724720344857006587549020016926517802128122613457935427138661").unwrap();
+}
+
+/// Amount of a token worth one ETH, signed for the altcoin purchases
+#[derive(Clone, Debug)]
+pub struct SignedQuote {
+    pub token: FieldElement,
+    // in the smallest unit of the token
+    pub quote: U256,
+    pub max_quote_validity: i64,
+    pub r: FieldElement,
+    pub s: FieldElement,
+    // price the quote was computed from
+    pub price: OraclePrice,
+    // unix timestamp the quote was signed at
+    pub signed_at: i64,
+}
+
+/// A signed quote as stored in the `altcoin_quotes` collection of the sales database,
+/// to know which rate was signed for a purchase.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct StoredQuote {
+    pub token: String,
+    // in the smallest unit of the token, as a decimal string
+    pub quote: String,
+    pub max_quote_validity: i64,
+    pub r: String,
+    pub s: String,
+    // median price of the token in ETH
+    pub price: String,
+    pub sources: Vec<SourcePrice>,
+    pub created_at: i64,
+}
+
+impl From<&SignedQuote> for StoredQuote {
+    fn from(quote: &SignedQuote) -> Self {
+        StoredQuote {
+            token: to_hex(&quote.token),
+            quote: quote.quote.to_string(),
+            max_quote_validity: quote.max_quote_validity,
+            r: to_hex(&quote.r),
+            s: to_hex(&quote.s),
+            price: quote.price.price.to_string(),
+            sources: quote.price.sources.clone(),
+            created_at: quote.signed_at,
+        }
+    }
+}
+
+pub fn altcoin_quotes(state: &Arc<AppState>) -> Collection<Document> {
+    state.sales_db.collection::<Document>("altcoin_quotes")
+}
+
+/// Creates the index of the quote history queries, which filter by token and date
+pub async fn create_altcoin_quotes_index(state: &Arc<AppState>) -> mongodb::error::Result<()> {
+    let index = IndexModel::builder()
+        .keys(doc! { "token": 1, "created_at": 1 })
+        .build();
+    altcoin_quotes(state).create_index(index, None).await?;
+    Ok(())
+}
+
+/// Stores a signed quote, it must not be returned if this fails
+async fn store_signed_quote(state: &Arc<AppState>, quote: &SignedQuote) -> Result<(), ApiError> {
+    let document = to_document(&StoredQuote::from(quote))
+        .map_err(|e| ApiError::Internal(format!("Unable to serialize quote: {}", e)))?;
+    altcoin_quotes(state).insert_one(document, None).await?;
+    Ok(())
+}
+
+/// Latest prices fetched in the background and latest signed quote of each token, so
+/// that requests neither wait for the price sources nor sign a new quote each time.
+pub struct QuoteCache {
+    price_ttl: Duration,
+    requote_threshold_bps: U256,
+    requote_margin: i64,
+    prices: Mutex<HashMap<FieldElement, (OraclePrice, Instant)>>,
+    quotes: Mutex<HashMap<FieldElement, SignedQuote>>,
+    // held while the price of a token is fetched or its quote signed, so that concurrent
+    // requests wait for the result instead of doing the same work
+    token_locks: Mutex<HashMap<FieldElement, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl QuoteCache {
+    pub fn new(settings: &PriceOracle) -> Result<Self, String> {
+        Ok(QuoteCache {
+            // a background price is not used anymore once an update has been missed
+            price_ttl: seconds_setting("price_oracle.refresh_delay", settings.refresh_delay * 2.0)?,
+            requote_threshold_bps: U256::from(
+                (settings.requote_threshold * 100.0).round().max(0.0) as u64,
+            ),
+            requote_margin: settings.requote_margin,
+            prices: Mutex::new(HashMap::new()),
+            quotes: Mutex::new(HashMap::new()),
+            token_locks: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn token_lock(&self, token: &FieldElement) -> Arc<tokio::sync::Mutex<()>> {
+        self.token_locks
+            .lock()
+            .unwrap()
+            .entry(*token)
+            .or_default()
+            .clone()
+    }
+
+    /// The cached price of a token, or the price fetched by `fetch`. Concurrent calls
+    /// for the same token share a single fetch.
+    pub async fn price_or_fetch(
+        &self,
+        token: &FieldElement,
+        fetch: impl Future<Output = Result<OraclePrice, ApiError>>,
+    ) -> Result<OraclePrice, ApiError> {
+        if let Some(price) = self.cached_price(token) {
+            return Ok(price);
+        }
+        let lock = self.token_lock(token);
+        let _guard = lock.lock().await;
+        // fetched by another call while this one was waiting
+        if let Some(price) = self.cached_price(token) {
+            return Ok(price);
+        }
+        let price = fetch.await?;
+        self.store_price(*token, price.clone());
+        Ok(price)
+    }
+
+    /// Replaces the cached price of a token by the price fetched by `fetch`, calls of
+    /// `price_or_fetch` wait for it instead of fetching the price too.
+    pub async fn refresh_price(
+        &self,
+        token: &FieldElement,
+        fetch: impl Future<Output = Result<OraclePrice, ApiError>>,
+    ) -> Result<OraclePrice, ApiError> {
+        let lock = self.token_lock(token);
+        let _guard = lock.lock().await;
+        let price = fetch.await?;
+        self.store_price(*token, price.clone());
+        Ok(price)
+    }
+
+    /// The cached quote of a token when it can be reused at this price, or the quote
+    /// signed by `sign`. Concurrent calls for the same token share a single signature.
+    pub async fn quote_or_sign(
+        &self,
+        token: &FieldElement,
+        price: Price,
+        now: i64,
+        sign: impl Future<Output = Result<SignedQuote, ApiError>>,
+    ) -> Result<SignedQuote, ApiError> {
+        if let Some(quote) = self.reusable_quote(token, price, now) {
+            return Ok(quote);
+        }
+        let lock = self.token_lock(token);
+        let _guard = lock.lock().await;
+        // signed by another call while this one was waiting
+        if let Some(quote) = self.reusable_quote(token, price, now) {
+            return Ok(quote);
+        }
+        let quote = sign.await?;
+        self.store_quote(quote.clone());
+        Ok(quote)
+    }
+
+    fn cached_price(&self, token: &FieldElement) -> Option<OraclePrice> {
+        match self.prices.lock().unwrap().get(token) {
+            Some((price, fetched_at)) if fetched_at.elapsed() < self.price_ttl => {
+                Some(price.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn store_price(&self, token: FieldElement, price: OraclePrice) {
+        self.prices
+            .lock()
+            .unwrap()
+            .insert(token, (price, Instant::now()));
+    }
+
+    /// The cached quote of a token, unless it expires soon or the price moved too much
+    pub fn reusable_quote(
+        &self,
+        token: &FieldElement,
+        price: Price,
+        now: i64,
+    ) -> Option<SignedQuote> {
+        let quotes = self.quotes.lock().unwrap();
+        let quote = quotes.get(token)?;
+        if quote.max_quote_validity - now <= self.requote_margin {
+            return None;
+        }
+        let deviation_bps = quote.price.price.deviation_bps(price)?;
+        (deviation_bps <= self.requote_threshold_bps).then(|| quote.clone())
+    }
+
+    pub fn store_quote(&self, quote: SignedQuote) {
+        self.quotes.lock().unwrap().insert(quote.token, quote);
+    }
+}
+
+/// Amount of the token, in its smallest unit, worth one ETH at this price. The quote is
+/// computed on integers and checked against the bounds of the token.
+pub fn quote_in_range(price: Price, altcoin_data: &AltcoinData) -> Result<U256, ApiError> {
+    let decimals = altcoin_data.decimals as usize;
+    let quote = price.quote_wei(decimals).ok_or_else(|| {
+        ApiError::Upstream(format!("Unable to compute a quote for price {}", price))
+    })?;
+    let unit = U256::exp10(decimals);
+    // check if quote is within the valid range
+    if quote < U256::from(altcoin_data.min_price).saturating_mul(unit)
+        || quote > U256::from(altcoin_data.max_price).saturating_mul(unit)
+    {
+        return Err(ApiError::Upstream("Quote out of range".to_string()));
+    }
+    Ok(quote)
+}
+
+/// Signs the quote of a token at this price, valid for `max_quote_validity` seconds
+pub fn sign_quote(
+    private_key: &FieldElement,
+    altcoin_data: &AltcoinData,
+    price: OraclePrice,
+    now: i64,
+) -> Result<SignedQuote, ApiError> {
+    let quote = quote_in_range(price.price, altcoin_data)?;
+    let max_quote_validity = now + altcoin_data.max_quote_validity;
+    let message_hash = pedersen_hash(
+        &pedersen_hash(
+            &pedersen_hash(
+                &altcoin_data.address,
+                &FieldElement::from_dec_str(&quote.to_string()).map_err(|e| {
+                    ApiError::Internal(format!("Quote doesn't fit in a felt: {}", e))
+                })?,
+            ),
+            &FieldElement::from(max_quote_validity as u64),
+        ),
+        &QUOTE_STR,
+    );
+    let signature = ecdsa_sign(private_key, &message_hash).map_err(|e| {
+        ApiError::Internal(format!("Error while generating Starknet signature: {}", e))
+    })?;
+    Ok(SignedQuote {
+        token: altcoin_data.address,
+        quote,
+        max_quote_validity,
+        r: signature.r,
+        s: signature.s,
+        price,
+        signed_at: now,
+    })
+}
+
+// Reuses the cached quote of the token when possible, signs and stores a new one
+// otherwise
+async fn quote_at_price(
+    state: &Arc<AppState>,
+    altcoin_data: &AltcoinData,
+    price: OraclePrice,
+) -> Result<SignedQuote, ApiError> {
+    let now = Utc::now().timestamp();
+    let current_price = price.price;
+    state
+        .quotes
+        .quote_or_sign(&altcoin_data.address, current_price, now, async {
+            let quote = sign_quote(&state.conf.altcoins.private_key, altcoin_data, price, now)?;
+            store_signed_quote(state, &quote).await?;
+            Ok(quote)
+        })
+        .await
+}
+
+/// Returns a signed quote of a whitelisted token, from the latest background price
+/// when there is one.
+pub async fn get_quote(
+    state: &Arc<AppState>,
+    token: &FieldElement,
+) -> Result<SignedQuote, ApiError> {
+    let altcoin_data = state
+        .conf
+        .altcoins
+        .data
+        .get(token)
+        .ok_or_else(|| ApiError::Validation("Token not supported".to_string()))?;
+    let price = state
+        .quotes
+        .price_or_fetch(token, state.price_oracle.get_price(token))
+        .await?;
+    quote_at_price(state, altcoin_data, price).await
+}
+
+/// Fetches the price of every whitelisted token and signs their quote again when the
+/// cached one can't be reused anymore.
+pub async fn refresh_quotes(state: &Arc<AppState>) {
+    join_all(
+        state
+            .conf
+            .altcoins
+            .data
+            .iter()
+            .map(|(token, altcoin_data)| async move {
+                let result = match state
+                    .quotes
+                    .refresh_price(token, state.price_oracle.get_price(token))
+                    .await
+                {
+                    Ok(price) => quote_at_price(state, altcoin_data, price).await.map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    state.logger.warning(format!(
+                        "Unable to refresh the quote of {}: {:?}",
+                        to_hex(token),
+                        e
+                    ));
+                }
+            }),
+    )
+    .await;
+}
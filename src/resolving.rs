@@ -1,13 +1,382 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use futures::StreamExt;
+use axum::async_trait;
+use futures::{future::join_all, StreamExt};
 use mongodb::{
     bson::{doc, Bson, Document},
     options::AggregateOptions,
     Collection,
 };
+use reqwest::Url;
+use starknet::{
+    core::{
+        crypto::{ecdsa_verify, pedersen_hash, Signature},
+        types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    },
+    macros::{selector, short_string},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use starknet_id::encode;
+
+use crate::{
+    config::OffchainResolver,
+    history::{at_block, filter_at_block},
+    models::{AppState, OffchainResolverHint},
+    utils::{clean_string, extract_prefix_and_root, to_hex},
+};
+
+lazy_static::lazy_static! {
+    static ref OFFCHAIN_RESOLVING_STR: FieldElement = short_string!("ccip_demo resolving");
+    static ref STARKNET_FIELD: FieldElement = short_string!("starknet");
+}
+
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub addr: String,
+    pub domain_expiry: Option<i64>,
+    // timestamp after which the resolution must be checked again, the expiry of the
+    // hint of offchain resolutions
+    pub valid_until: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ResolutionError {
+    NotFound(String),
+    ExpiredHint,
+    BadSignature,
+    ResolverUnreachable(String),
+    // the domain or the request can't be resolved as is
+    Invalid(String),
+    Database(String),
+    Failed(String),
+}
+
+impl fmt::Display for ResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolutionError::NotFound(message) => write!(f, "{}", message),
+            ResolutionError::ExpiredHint => write!(f, "expired hint"),
+            ResolutionError::BadSignature => write!(f, "bad signature"),
+            ResolutionError::ResolverUnreachable(message) => {
+                write!(f, "resolver unreachable: {}", message)
+            }
+            ResolutionError::Invalid(message)
+            | ResolutionError::Database(message)
+            | ResolutionError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+pub type ResolutionResult = Result<Resolution, ResolutionError>;
+
+/// A way of resolving a domain to an address.
+///
+/// A resolver only returns results for the domains it is responsible for, the other
+/// ones are handed over to the next resolver of the `ResolverChain`.
+#[async_trait]
+pub trait Resolver: Send + Sync {
+    async fn resolve(
+        &self,
+        state: &Arc<AppState>,
+        domains: &[String],
+        block: Option<u64>,
+    ) -> HashMap<String, ResolutionResult>;
+}
+
+/// Chain of responsibility shared by every forward resolution endpoint
+pub struct ResolverChain {
+    resolvers: Vec<Box<dyn Resolver>>,
+}
+
+impl ResolverChain {
+    pub fn new(resolvers: Vec<Box<dyn Resolver>>) -> Self {
+        ResolverChain { resolvers }
+    }
+
+    pub async fn resolve(
+        &self,
+        state: &Arc<AppState>,
+        domain: &str,
+        block: Option<u64>,
+    ) -> ResolutionResult {
+        self.resolve_many(state, &[domain.to_string()], block)
+            .await
+            .remove(0)
+    }
+
+    /// Resolves several domains at once, results are returned in the input order.
+    /// When a block is given, domains are resolved as they were at that block.
+    pub async fn resolve_many(
+        &self,
+        state: &Arc<AppState>,
+        domains: &[String],
+        block: Option<u64>,
+    ) -> Vec<ResolutionResult> {
+        let mut results: HashMap<String, ResolutionResult> = HashMap::new();
+        let mut remaining: Vec<String> = domains.to_vec();
+        remaining.sort();
+        remaining.dedup();
+        for resolver in &self.resolvers {
+            if remaining.is_empty() {
+                break;
+            }
+            let resolved = resolver.resolve(state, &remaining, block).await;
+            remaining.retain(|domain| !resolved.contains_key(domain));
+            results.extend(resolved);
+        }
+
+        domains
+            .iter()
+            .map(|domain| {
+                results
+                    .get(domain)
+                    .cloned()
+                    .unwrap_or_else(|| Err(not_found()))
+            })
+            .collect()
+    }
+}
+
+impl Default for ResolverChain {
+    fn default() -> Self {
+        ResolverChain::new(vec![
+            Box::new(CustomResolver),
+            Box::new(OffchainHintResolver),
+            Box::new(NativeResolver),
+            Box::new(OnchainResolver),
+        ])
+    }
+}
+
+// Resolves subdomains of the root domains listed in the config custom_resolvers
+pub struct CustomResolver;
+
+#[async_trait]
+impl Resolver for CustomResolver {
+    async fn resolve(
+        &self,
+        state: &Arc<AppState>,
+        domains: &[String],
+        block: Option<u64>,
+    ) -> HashMap<String, ResolutionResult> {
+        let futures = domains.iter().filter_map(|domain| {
+            let (prefix, root_domain) = extract_prefix_and_root(domain.clone());
+            let resolver = state.conf.reversed_resolvers.get(&root_domain)?;
+            Some(async move {
+                let result = resolve_custom(state, resolver, &prefix, block).await;
+                (domain.clone(), result)
+            })
+        });
+        join_all(futures).await.into_iter().collect()
+    }
+}
+
+// Resolves subdomains of root domains using an offchain resolver, from the config or indexed
+pub struct OffchainHintResolver;
+
+#[async_trait]
+impl Resolver for OffchainHintResolver {
+    async fn resolve(
+        &self,
+        state: &Arc<AppState>,
+        domains: &[String],
+        block: Option<u64>,
+    ) -> HashMap<String, ResolutionResult> {
+        let futures = domains.iter().filter_map(|domain| {
+            let (prefix, root_domain) = extract_prefix_and_root(domain.clone());
+            let offchain_resolver = get_offchain_resolver(prefix, root_domain, state)?;
+            Some(async move {
+                // hints are only valid for the current state of the offchain resolver
+                let result = match block {
+                    Some(_) => Err(ResolutionError::Invalid(
+                        "Offchain domains can't be resolved at a past block".to_string(),
+                    )),
+                    None => resolve_offchain(state, &offchain_resolver, domain).await,
+                };
+                (domain.clone(), result)
+            })
+        });
+        join_all(futures).await.into_iter().collect()
+    }
+}
 
-use crate::{config::OffchainResolver, models::AppState, utils::clean_string};
+// Resolves domains from the indexed domains, id_user_data and id_owners collections
+pub struct NativeResolver;
+
+#[async_trait]
+impl Resolver for NativeResolver {
+    async fn resolve(
+        &self,
+        state: &Arc<AppState>,
+        domains: &[String],
+        block: Option<u64>,
+    ) -> HashMap<String, ResolutionResult> {
+        match resolve_native(state, domains.to_vec(), block).await {
+            Ok(resolutions) => resolutions
+                .into_iter()
+                .map(|(domain, resolution)| (domain, Ok(resolution)))
+                .collect(),
+            // a failure is reported for every domain instead of falling through
+            Err(e) => domains
+                .iter()
+                .map(|domain| (domain.clone(), Err(e.clone())))
+                .collect(),
+        }
+    }
+}
+
+// Asks the naming contract directly, used for domains which are not indexed yet
+pub struct OnchainResolver;
+
+#[async_trait]
+impl Resolver for OnchainResolver {
+    async fn resolve(
+        &self,
+        state: &Arc<AppState>,
+        domains: &[String],
+        block: Option<u64>,
+    ) -> HashMap<String, ResolutionResult> {
+        let provider = JsonRpcClient::new(HttpTransport::new(
+            Url::parse(&state.conf.variables.rpc_url).unwrap(),
+        ));
+        let provider = &provider;
+        let block_id = match block {
+            Some(block) => BlockId::Number(block),
+            None => BlockId::Tag(BlockTag::Latest),
+        };
+        let futures = domains.iter().map(|domain| async move {
+            let result = match encode_domain(domain) {
+                Ok(encoded_domain) => {
+                    match domain_to_address(provider, state, encoded_domain, vec![], block_id)
+                        .await
+                    {
+                        Ok(addr) if addr != FieldElement::ZERO => Ok(Resolution {
+                            addr: to_hex(&addr),
+                            domain_expiry: None,
+                            valid_until: None,
+                        }),
+                        Ok(_) => Err(not_found()),
+                        Err(e) => Err(ResolutionError::Failed(e)),
+                    }
+                }
+                Err(e) => Err(ResolutionError::Invalid(e)),
+            };
+            (domain.clone(), result)
+        });
+        join_all(futures).await.into_iter().collect()
+    }
+}
+
+fn not_found() -> ResolutionError {
+    ResolutionError::NotFound("No document found for the given domain".to_string())
+}
+
+/// Checks an offchain resolver hint the same way the resolver contract does:
+/// max_validity must be in the future and the signature must come from the resolver key.
+/// Hints of resolvers without a known public key can't be checked and are rejected.
+pub fn verify_hint(
+    hint: &OffchainResolverHint,
+    offchain_resolver: &OffchainResolver,
+    encoded_domain: &[FieldElement],
+) -> Result<(), ResolutionError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    if hint.max_validity <= now {
+        return Err(ResolutionError::ExpiredHint);
+    }
+
+    let public_key = offchain_resolver
+        .public_key
+        .ok_or(ResolutionError::BadSignature)?;
+    let message_hash = hint_message_hash(hint, encoded_domain);
+    match ecdsa_verify(
+        &public_key,
+        &message_hash,
+        &Signature {
+            r: hint.r,
+            s: hint.s,
+        },
+    ) {
+        Ok(true) => Ok(()),
+        _ => Err(ResolutionError::BadSignature),
+    }
+}
+
+/// Message signed by offchain resolvers gateways. The resolver contract only receives the
+/// subdomain part of the domain, the root domain holding the resolver is not hashed.
+pub fn hint_message_hash(
+    hint: &OffchainResolverHint,
+    encoded_domain: &[FieldElement],
+) -> FieldElement {
+    // the root domain is made of the last label (the .stark suffix is already removed)
+    let subdomain = &encoded_domain[..encoded_domain.len().saturating_sub(1)];
+    pedersen_hash(
+        &pedersen_hash(
+            &pedersen_hash(
+                &pedersen_hash(
+                    &OFFCHAIN_RESOLVING_STR,
+                    &FieldElement::from(hint.max_validity),
+                ),
+                &hash_domain(subdomain),
+            ),
+            &STARKNET_FIELD,
+        ),
+        &hint.address,
+    )
+}
+
+// same as the hash_domain function of the resolver contracts
+fn hash_domain(domain: &[FieldElement]) -> FieldElement {
+    match domain.split_last() {
+        Some((last, rest)) => pedersen_hash(last, &hash_domain(rest)),
+        None => FieldElement::ZERO,
+    }
+}
+
+/// Encodes a .stark domain into the felts expected by the naming contract
+pub fn encode_domain(domain: &str) -> Result<Vec<FieldElement>, String> {
+    let trimmed_domain = domain.strip_suffix(".stark").unwrap_or(domain);
+    trimmed_domain
+        .split('.')
+        .map(|part| encode(part).map_err(|_| format!("Unable to encode domain part: {}", part)))
+        .collect()
+}
+
+async fn domain_to_address(
+    provider: &JsonRpcClient<HttpTransport>,
+    state: &Arc<AppState>,
+    encoded_domain: Vec<FieldElement>,
+    hint: Vec<FieldElement>,
+    block_id: BlockId,
+) -> Result<FieldElement, String> {
+    let mut calldata: Vec<FieldElement> = vec![FieldElement::from(encoded_domain.len())];
+    calldata.extend(encoded_domain);
+    calldata.push(FieldElement::from(hint.len()));
+    calldata.extend(hint);
+
+    let result = provider
+        .call(
+            FunctionCall {
+                contract_address: state.conf.contracts.naming,
+                entry_point_selector: selector!("domain_to_address"),
+                calldata,
+            },
+            block_id,
+        )
+        .await
+        .map_err(|e| format!("{}", e))?;
+    result
+        .first()
+        .copied()
+        .ok_or_else(|| "Empty result from naming contract".to_string())
+}
 
 pub async fn get_custom_resolver(domains: &Collection<Document>, domain: &str,state: &Arc<AppState>) -> Option<String> {
     let logger = &state.logger;
@@ -97,6 +466,7 @@ pub async fn update_offchain_resolvers(state: &Arc<AppState>) {
                 "resolver_contract": "$resolver_contract",
                 "uri": "$uri",
                 "active": "$active",
+                "public_key": "$public_key",
                 "domains": "$domainData.domain",
             }
         },
@@ -131,38 +501,11 @@ pub async fn update_offchain_resolvers(state: &Arc<AppState>) {
                         // values in config file override onchain events
                         match (&state.conf).offchain_resolvers.get(domain) {
                             Some(_) => continue,
-                            None => {
-                                let mut resolver_map =
-                                    state.dynamic_offchain_resolvers.lock().unwrap();
-                                match resolver_map.get(domain) {
-                                    Some(existing_resolvers) => {
-                                        // there is already a resolver for this domain
-                                        let new_uri =
-                                            clean_string(doc.get_str("uri").unwrap_or_default());
-                                        // we check the uri is not already in the list
-                                        if !existing_resolvers.uri.contains(&new_uri) {
-                                            if let Some(existing_resolver) =
-                                                resolver_map.get_mut(domain)
-                                            {
-                                                existing_resolver.uri.push(new_uri);
-                                            }
-                                        }
-                                    }
-                                    None => {
-                                        // there is no resolver for this domain yet
-                                        let resolver = OffchainResolver {
-                                            resolver_address: doc
-                                                .get_str("resolver_contract")
-                                                .unwrap_or_default()
-                                                .to_owned(),
-                                            uri: vec![clean_string(
-                                                doc.get_str("uri").unwrap_or_default(),
-                                            )],
-                                        };
-                                        resolver_map.insert(domain.to_owned(), resolver);
-                                    }
-                                }
-                            }
+                            None => register_offchain_resolver(
+                                &mut state.dynamic_offchain_resolvers.lock().unwrap(),
+                                domain,
+                                &doc,
+                            ),
                         }
                     }
                 }
@@ -174,6 +517,46 @@ pub async fn update_offchain_resolvers(state: &Arc<AppState>) {
     }
 }
 
+/// Adds an onchain registered offchain resolver of a domain to the dynamic resolvers,
+/// from a document of the `update_offchain_resolvers` pipeline
+pub fn register_offchain_resolver(
+    resolver_map: &mut HashMap<String, OffchainResolver>,
+    domain: &str,
+    doc: &Document,
+) {
+    let uri = clean_string(doc.get_str("uri").unwrap_or_default());
+    let public_key = doc
+        .get_str("public_key")
+        .ok()
+        .and_then(|key| FieldElement::from_hex_be(key).ok());
+    match resolver_map.get_mut(domain) {
+        Some(existing_resolver) => {
+            // there is already a resolver for this domain, we check the uri is not
+            // already in the list and keep the latest key
+            if !existing_resolver.uri.contains(&uri) {
+                existing_resolver.uri.push(uri);
+            }
+            if public_key.is_some() {
+                existing_resolver.public_key = public_key;
+            }
+        }
+        None => {
+            // there is no resolver for this domain yet
+            resolver_map.insert(
+                domain.to_owned(),
+                OffchainResolver {
+                    resolver_address: doc
+                        .get_str("resolver_contract")
+                        .unwrap_or_default()
+                        .to_owned(),
+                    public_key,
+                    uri: vec![uri],
+                },
+            );
+        }
+    }
+}
+
 pub fn get_offchain_resolver(
     prefix: String,
     root_domain: String,
@@ -196,3 +579,237 @@ pub fn get_offchain_resolver(
                 .cloned()
         })
 }
+
+async fn resolve_custom(
+    state: &Arc<AppState>,
+    resolver: &str,
+    prefix: &str,
+    block: Option<u64>,
+) -> ResolutionResult {
+    let custom_resolutions = state
+        .starknetid_db
+        .collection::<mongodb::bson::Document>("custom_resolutions");
+    let filter = filter_at_block(
+        doc! {
+            "domain_slice" : prefix,
+            "resolver" : resolver,
+            // means "starknet"
+            "field" : "0x000000000000000000000000000000000000000000000000737461726b6e6574",
+            "_cursor.to": null,
+        },
+        block,
+    )
+    .map_err(|e| ResolutionError::Invalid(e.to_string()))?;
+    match custom_resolutions.find_one(filter, None).await {
+        Ok(Some(doc)) => match doc.get_str("value") {
+            Ok(value) => Ok(Resolution {
+                addr: value.to_string(),
+                domain_expiry: None,
+                valid_until: None,
+            }),
+            Err(_) => Err(ResolutionError::NotFound("no target found".to_string())),
+        },
+        _ => Err(ResolutionError::NotFound("no target found".to_string())),
+    }
+}
+
+async fn resolve_offchain(
+    state: &Arc<AppState>,
+    offchain_resolver: &OffchainResolver,
+    domain: &str,
+) -> ResolutionResult {
+    let hints = state
+        .offchain_gateway
+        .get_hint(offchain_resolver, domain)
+        .await?;
+    let encoded_domain = encode_domain(domain).map_err(ResolutionError::Invalid)?;
+
+    // check the hint before spending an RPC call on it
+    if let Err(e) = verify_hint(&hints, offchain_resolver, &encoded_domain) {
+        state.offchain_gateway.forget_hint(domain);
+        return Err(e);
+    }
+
+    // Call the naming contract with the hints
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    let hint = vec![
+        hints.address,
+        hints.r,
+        hints.s,
+        FieldElement::from(hints.max_validity),
+    ];
+    let addr = domain_to_address(
+        &provider,
+        state,
+        encoded_domain,
+        hint,
+        BlockId::Tag(BlockTag::Latest),
+    )
+    .await
+        .map_err(ResolutionError::Failed)?;
+
+    // if call is successful we return the address
+    Ok(Resolution {
+        addr: to_hex(&addr),
+        domain_expiry: None,
+        valid_until: Some(hints.max_validity),
+    })
+}
+
+/// Resolves natively indexed domains with a single aggregation.
+/// Domains without any matching document are absent from the returned map.
+async fn resolve_native(
+    state: &Arc<AppState>,
+    domains: Vec<String>,
+    block: Option<u64>,
+) -> Result<HashMap<String, Resolution>, ResolutionError> {
+    let collection = state
+        .starknetid_db
+        .collection::<mongodb::bson::Document>("domains");
+
+    let pipeline = at_block(native_pipeline(domains), block)
+        .map_err(|e| ResolutionError::Invalid(e.to_string()))?;
+    let mut cursor = collection
+        .aggregate(pipeline, AggregateOptions::default())
+        .await
+        .map_err(|e| {
+            ResolutionError::Database(format!("Error accessing the database: {}", e))
+        })?;
+
+    let mut resolutions = HashMap::new();
+    while let Some(result) = cursor.next().await {
+        let doc = result
+            .map_err(|e| ResolutionError::Database(format!("Error calling the db: {}", e)))?;
+        if let Ok(domain) = doc.get_str("domain") {
+            resolutions
+                .entry(domain.to_owned())
+                .or_insert_with(|| Resolution {
+                    addr: doc.get_str("addr").unwrap_or_default().to_owned(),
+                    domain_expiry: doc.get_i64("domain_expiry").ok(),
+                    valid_until: None,
+                });
+        }
+    }
+    Ok(resolutions)
+}
+
+fn native_pipeline(domains: Vec<String>) -> Vec<Document> {
+    vec![
+        doc! {
+            "$match": doc! {
+                "_cursor.to": null,
+                "resolver" : null,
+                "domain": { "$in": domains },
+            }
+        },
+        doc! {
+            "$lookup": doc! {
+                "from": "id_user_data",
+                "let": doc! {
+                    "userId": "$id"
+                },
+                "pipeline": [
+                    doc! {
+                        "$match": doc! {
+                            "_cursor.to": doc! {
+                                "$exists": false
+                            },
+                            "field": "0x000000000000000000000000000000000000000000000000737461726b6e6574",
+                            "$expr": doc! {
+                                "$eq": [
+                                    "$id",
+                                    "$$userId"
+                                ]
+                            }
+                        }
+                    }
+                ],
+                "as": "userData"
+            }
+        },
+        doc! {
+            "$unwind": doc! {
+                "path": "$userData",
+                "preserveNullAndEmptyArrays": true
+            }
+        },
+        doc! {
+            "$lookup": doc! {
+                "from": "id_owners",
+                "let": doc! {
+                    "userId": "$id"
+                },
+                "pipeline": [
+                    doc! {
+                        "$match": doc! {
+                            "$or": [
+                                doc! {
+                                    "_cursor.to": doc! {
+                                        "$exists": false
+                                    }
+                                },
+                                doc! {
+                                    "_cursor.to": null
+                                }
+                            ],
+                            "$expr": doc! {
+                                "$eq": [
+                                    "$id",
+                                    "$$userId"
+                                ]
+                            }
+                        }
+                    }
+                ],
+                "as": "ownerData"
+            }
+        },
+        doc! {
+            "$unwind": doc! {
+                "path": "$ownerData",
+                "preserveNullAndEmptyArrays": true
+            }
+        },
+        doc! {
+            "$project": doc! {
+                "domain": 1,
+                "addr": doc! {
+                    "$cond": doc! {
+                        "if": doc! {
+                            "$and": [
+                                doc! {
+                                    "$ifNull": [
+                                        "$legacy_address",
+                                        false
+                                    ]
+                                },
+                                doc! {
+                                    "$ne": [
+                                        "$legacy_address",
+                                        "0x0000000000000000000000000000000000000000000000000000000000000000"
+                                    ]
+                                }
+                            ]
+                        },
+                        "then": "$legacy_address",
+                        "else": doc! {
+                            "$cond": doc! {
+                                "if": doc! {
+                                    "$ifNull": [
+                                        "$userData.data",
+                                        false
+                                    ]
+                                },
+                                "then": "$userData.data",
+                                "else": "$ownerData.owner"
+                            }
+                        }
+                    }
+                },
+                "domain_expiry": "$expiry"
+            }
+        },
+    ]
+}
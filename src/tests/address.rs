@@ -0,0 +1,102 @@
+use crate::{
+    address::{checksum, StarknetAddress},
+    config::Addresses,
+};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod starknet_address {
+    use super::*;
+
+    const PADDED: &str = "0x02fd23d9182193775423497fc0c472e156c57c69e4089a1967fb288a2d84e914";
+
+    #[test]
+    fn test_any_padding_is_normalized() {
+        for value in [
+            "0x2fd23d9182193775423497fc0c472e156c57c69e4089a1967fb288a2d84e914",
+            PADDED,
+            "0x0002fd23d9182193775423497fc0c472e156c57c69e4089a1967fb288a2d84e914",
+        ] {
+            let address: StarknetAddress = value.parse().unwrap();
+            assert_eq!(address.to_hex(), PADDED);
+        }
+        let address: StarknetAddress = "1".parse().unwrap();
+        assert_eq!(address.felt(), FieldElement::ONE);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let address: StarknetAddress = PADDED.parse().unwrap();
+        assert_eq!(
+            checksum(&address.felt()),
+            "2Fd23d9182193775423497fc0c472E156C57C69E4089A1967fb288A2d84e914"
+        );
+        assert!(
+            "0x02Fd23d9182193775423497fc0c472E156C57C69E4089A1967fb288A2d84e914"
+                .parse::<StarknetAddress>()
+                .is_ok()
+        );
+        assert!(
+            "0x02fD23d9182193775423497fc0c472E156C57C69E4089A1967fb288A2d84e914"
+                .parse::<StarknetAddress>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_stored_forms() {
+        let address: StarknetAddress = "0x7b".parse().unwrap();
+        assert_eq!(
+            address.stored_forms(),
+            vec![
+                "0x000000000000000000000000000000000000000000000000000000000000007b".to_string(),
+                "123".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_addresses_are_rejected() {
+        for value in [
+            "0x",
+            "0xzz",
+            "not an address",
+            // above the contract address range
+            "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00",
+            // longer than 32 bytes
+            "0x102fd23d9182193775423497fc0c472e156c57c69e4089a1967fb288a2d84e914",
+            // ethereum address
+            "0xd8da6bf26964af9d7eed9e03e53415d37aa96045",
+        ] {
+            assert!(value.parse::<StarknetAddress>().is_err(), "{}", value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod configured_rules {
+    use super::*;
+
+    #[test]
+    fn test_bound_of_the_network() {
+        let rules = Addresses {
+            max_address: FieldElement::from(0x1000_u64),
+            checksum: true,
+        };
+        assert!(StarknetAddress::parse_with("0xfff", &rules).is_ok());
+        assert!(StarknetAddress::parse_with("0x1000", &rules).is_err());
+        assert!(StarknetAddress::parse_with("4096", &rules).is_err());
+    }
+
+    #[test]
+    fn test_checksum_can_be_disabled() {
+        // mixed case with a wrong checksum
+        let value = "0x2fD23d9182193775423497fc0c472E156C57C69E4089A1967fb288A2d84e914";
+        assert!(StarknetAddress::parse_with(value, &Addresses::default()).is_err());
+        let rules = Addresses {
+            checksum: false,
+            ..Addresses::default()
+        };
+        assert!(StarknetAddress::parse_with(value, &rules).is_ok());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{admin::check_admin_key, config::Admin, errors::ApiError};
+use axum::http::{HeaderMap, HeaderValue};
+
+fn headers(api_key: Option<&'static str>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(api_key) = api_key {
+        headers.insert("x-api-key", HeaderValue::from_static(api_key));
+    }
+    headers
+}
+
+fn admin(api_key: &str) -> Admin {
+    Admin {
+        api_key: api_key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod check_admin_key {
+    use super::*;
+
+    #[test]
+    fn test_valid_key() {
+        assert!(check_admin_key(&admin("s3cret"), &headers(Some("s3cret"))).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_or_missing_key_is_rejected() {
+        for provided in [
+            None,
+            Some(""),
+            Some("s3cre"),
+            Some("s3cret "),
+            Some("S3CRET"),
+        ] {
+            assert!(
+                matches!(
+                    check_admin_key(&admin("s3cret"), &headers(provided)),
+                    Err(ApiError::Unauthorized(_))
+                ),
+                "{:?}",
+                provided
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_key_disables_admin_endpoints() {
+        for provided in [None, Some("")] {
+            assert!(matches!(
+                check_admin_key(&admin(""), &headers(provided)),
+                Err(ApiError::Unauthorized(_))
+            ));
+        }
+    }
+}
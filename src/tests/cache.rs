@@ -0,0 +1,153 @@
+use crate::{
+    cache::{cursor_indexes, custom_resolution_tags, ResponseCache},
+    config::Cache,
+};
+use mongodb::bson::doc;
+use serde_json::json;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod response_cache {
+    use super::*;
+
+    fn cache(max_entries: usize) -> ResponseCache {
+        ResponseCache::new(&Cache {
+            ttl: 60.0,
+            max_entries,
+        })
+    }
+
+    #[test]
+    fn test_cached_value_is_returned() {
+        let cache = cache(10);
+        let value = json!({ "addr": "0x1", "domain_expiry": null });
+        cache.insert(
+            "domain_to_addr:th0rgal.stark".to_string(),
+            &value,
+            &["th0rgal.stark"],
+        );
+        assert_eq!(cache.get("domain_to_addr:th0rgal.stark"), Some(value));
+        assert_eq!(cache.get("domain_to_addr:fricoben.stark"), None);
+    }
+
+    #[test]
+    fn test_expired_value_is_dropped() {
+        let cache = ResponseCache::new(&Cache {
+            ttl: 0.0,
+            max_entries: 10,
+        });
+        cache.insert("key".to_string(), &json!(1), &["th0rgal.stark"]);
+        assert_eq!(cache.get("key"), None);
+    }
+
+    #[test]
+    fn test_entries_expire_with_their_validity() {
+        let cache = cache(10);
+        // offchain resolution of an expired hint
+        cache.insert_until(
+            "a".to_string(),
+            &json!(1),
+            &["a.notion.stark"],
+            Some(now() - 1),
+        );
+        assert_eq!(cache.get("a"), None);
+        cache.insert_until(
+            "b".to_string(),
+            &json!(2),
+            &["b.notion.stark"],
+            Some(now() + 30),
+        );
+        assert_eq!(cache.get("b"), Some(json!(2)));
+        cache.insert_until("c".to_string(), &json!(3), &["c.stark"], None);
+        assert_eq!(cache.get("c"), Some(json!(3)));
+    }
+
+    #[test]
+    fn test_invalidation_matches_padded_addresses() {
+        let cache = cache(10);
+        cache.insert("addr_to_domain:a".to_string(), &json!(1), &["0x0123"]);
+        cache.insert("addr_to_domain:b".to_string(), &json!(2), &["0x0456"]);
+        let tags: HashSet<String> =
+            ["0x0000000000000000000000000000000000000000000000000000000000000123".to_string()]
+                .into();
+        cache.invalidate(&tags);
+        assert_eq!(cache.get("addr_to_domain:a"), None);
+        assert_eq!(cache.get("addr_to_domain:b"), Some(json!(2)));
+    }
+
+    #[test]
+    fn test_oldest_entry_is_evicted() {
+        let cache = cache(2);
+        cache.insert("a".to_string(), &json!(1), &["a.stark"]);
+        cache.insert("b".to_string(), &json!(2), &["b.stark"]);
+        cache.insert("c".to_string(), &json!(3), &["c.stark"]);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(json!(2)));
+        assert_eq!(cache.get("c"), Some(json!(3)));
+    }
+
+    #[test]
+    fn test_disabled_cache_stores_nothing() {
+        let cache = cache(0);
+        cache.insert("a".to_string(), &json!(1), &["a.stark"]);
+        assert_eq!(cache.get("a"), None);
+    }
+}
+
+#[cfg(test)]
+mod custom_resolution_tags {
+    use super::*;
+
+    #[test]
+    fn test_subdomains_of_every_root_domain() {
+        let custom_resolvers = HashMap::from([
+            (
+                "0x0123".to_string(),
+                vec!["braavos.stark".to_string(), "xplorer.stark".to_string()],
+            ),
+            ("0x0456".to_string(), vec!["other.stark".to_string()]),
+        ]);
+        let row = doc! {
+            "domain_slice": "alice.",
+            "resolver": "0x0000000000000000000000000000000000000000000000000000000000000123",
+            "value": "0x0789",
+        };
+        let mut tags = custom_resolution_tags(&row, &custom_resolvers);
+        tags.sort();
+        assert_eq!(tags, vec!["alice.braavos.stark", "alice.xplorer.stark"]);
+    }
+
+    #[test]
+    fn test_unknown_resolver() {
+        let row = doc! { "domain_slice": "alice.", "resolver": "0x0123", "value": "0x0789" };
+        assert!(custom_resolution_tags(&row, &HashMap::new()).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod cursor_indexes {
+    use super::*;
+
+    #[test]
+    fn test_change_lookups_are_indexed() {
+        let keys: Vec<_> = cursor_indexes()
+            .into_iter()
+            .map(|index| index.keys)
+            .collect();
+        // both bounds of the $or of invalidate_indexed_changes need an index
+        assert_eq!(
+            keys,
+            vec![doc! { "_cursor.from": 1 }, doc! { "_cursor.to": 1 }]
+        );
+    }
+}
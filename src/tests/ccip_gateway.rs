@@ -0,0 +1,508 @@
+use super::app_state;
+use crate::{
+    config::Config,
+    endpoints::crosschain::ethereum::resolve,
+    models::AppState,
+    resolving::{Resolution, ResolutionError, ResolutionResult, Resolver, ResolverChain},
+};
+use axum::{async_trait, extract::State, routing::post, Json, Router};
+use ethabi::{ParamType, Token};
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Signature, H160, H256},
+    utils::keccak256,
+};
+use serde_json::{json, Value};
+use starknet::{
+    core::{types::FieldElement, utils::get_selector_from_name},
+    macros::felt,
+};
+use starknet_id::encode;
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, TcpListener},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+// well known test key, never used on a real network
+const SIGNER_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+// offchain resolver contract sending the CCIP-Read request
+const SENDER: &str = "0x2a8d3a1f0f0b4f2b2a3c0c0b0f5d6e7f8a9b0c1d";
+const EVM_ADDRESS: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+const NAMING: FieldElement = felt!("0x6e616d696e67");
+const STARKNETID: FieldElement = felt!("0x737461726b6e65746964");
+const MULTICALL: FieldElement = felt!("0x6d756c746963616c6c");
+
+// ENSIP-11 coin type of Starknet
+const STARKNET_COIN_TYPE: u64 = 2147492652;
+
+/// Starknet JSON-RPC server answering `starknet_call` from a table of
+/// (contract, selector) results and recording every call it receives.
+#[derive(Clone, Default)]
+struct MockRpc {
+    results: Arc<HashMap<(FieldElement, FieldElement), Vec<FieldElement>>>,
+    calls: Arc<Mutex<Vec<(FieldElement, Vec<FieldElement>)>>>,
+}
+
+impl MockRpc {
+    fn new(results: Vec<(FieldElement, &str, Vec<FieldElement>)>) -> Self {
+        MockRpc {
+            results: Arc::new(
+                results
+                    .into_iter()
+                    .map(|(contract, entrypoint, result)| {
+                        let selector = get_selector_from_name(entrypoint).unwrap();
+                        ((contract, selector), result)
+                    })
+                    .collect(),
+            ),
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn called_selectors(&self) -> Vec<FieldElement> {
+        let calls = self.calls.lock().unwrap();
+        calls.iter().map(|(selector, _)| *selector).collect()
+    }
+}
+
+async fn rpc_handler(State(rpc): State<MockRpc>, Json(request): Json<Value>) -> Json<Value> {
+    let id = request["id"].clone();
+    let call = match find_function_call(&request["params"]) {
+        Some(call) => call,
+        None => return Json(rpc_error(id, -32601, "Method not found")),
+    };
+    let felt = |value: &Value| FieldElement::from_hex_be(value.as_str().unwrap_or_default());
+    let (contract, selector) = match (
+        felt(&call["contract_address"]),
+        felt(&call["entry_point_selector"]),
+    ) {
+        (Ok(contract), Ok(selector)) => (contract, selector),
+        _ => return Json(rpc_error(id, -32602, "Invalid params")),
+    };
+    let calldata = call["calldata"]
+        .as_array()
+        .map(|calldata| {
+            calldata
+                .iter()
+                .filter_map(|value| felt(value).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    rpc.calls.lock().unwrap().push((selector, calldata));
+
+    match rpc.results.get(&(contract, selector)) {
+        Some(result) => Json(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": result.iter().map(|felt| format!("{:#x}", felt)).collect::<Vec<_>>(),
+        })),
+        None => Json(rpc_error(id, 21, "Requested entrypoint does not exist")),
+    }
+}
+
+// `starknet_call` params are either positional or named depending on the client
+fn find_function_call(params: &Value) -> Option<&Value> {
+    match params {
+        Value::Object(object) if object.contains_key("entry_point_selector") => Some(params),
+        Value::Object(object) => object.values().find_map(find_function_call),
+        Value::Array(values) => values.iter().find_map(find_function_call),
+        _ => None,
+    }
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn serve(router: Router) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service())
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+/// Resolver answering every domain with the same result
+struct FixedResolver(ResolutionResult);
+
+#[async_trait]
+impl Resolver for FixedResolver {
+    async fn resolve(
+        &self,
+        _state: &Arc<AppState>,
+        domains: &[String],
+        _block: Option<u64>,
+    ) -> HashMap<String, ResolutionResult> {
+        domains
+            .iter()
+            .map(|domain| (domain.clone(), self.0.clone()))
+            .collect()
+    }
+}
+
+// Runs the gateway against the mock rpc, the databases are never reached by these calls
+async fn start_gateway(rpc: &MockRpc) -> SocketAddr {
+    start_gateway_with(rpc, ResolverChain::default()).await
+}
+
+async fn start_gateway_with(rpc: &MockRpc, resolvers: ResolverChain) -> SocketAddr {
+    let rpc_addr = serve(
+        Router::new()
+            .route("/", post(rpc_handler))
+            .with_state(rpc.clone()),
+    );
+
+    let mut conf = Config::default();
+    conf.variables.rpc_url = format!("http://{}", rpc_addr);
+    conf.evm.private_key = SIGNER_KEY.to_string();
+    conf.contracts.naming = NAMING;
+    conf.contracts.starknetid = STARKNETID;
+    conf.contracts.argent_multicall = MULTICALL;
+
+    serve(
+        Router::new()
+            .route("/crosschain/ethereum/resolve", post(resolve::handler))
+            .with_state(app_state(conf, resolvers).await),
+    )
+}
+
+// `resolve(bytes name, bytes data)` calldata sent by the offchain resolver contract
+fn resolve_calldata(name: &str, call: Vec<u8>) -> Vec<u8> {
+    let mut dns_name = Vec::new();
+    for label in name.split('.') {
+        dns_name.push(label.len() as u8);
+        dns_name.extend(label.as_bytes());
+    }
+    dns_name.push(0);
+    [
+        hex::decode("9061b923").unwrap(),
+        ethers::abi::encode(&[Token::Bytes(dns_name), Token::Bytes(call)]),
+    ]
+    .concat()
+}
+
+fn resolver_call(selector: &str, params: &[Token]) -> Vec<u8> {
+    [hex::decode(selector).unwrap(), ethers::abi::encode(params)].concat()
+}
+
+fn node() -> Token {
+    Token::FixedBytes(vec![0x11; 32])
+}
+
+async fn post_resolve(gateway: SocketAddr, calldata: &[u8]) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("http://{}/crosschain/ethereum/resolve", gateway))
+        .json(&json!({
+            "data": format!("0x{}", hex::encode(calldata)),
+            "sender": SENDER,
+        }))
+        .send()
+        .await
+        .unwrap()
+}
+
+/// Checks a gateway answer the way `OffchainResolver.resolveWithProof` does and
+/// returns the resolver result it carries.
+async fn verify_response(response: reqwest::Response, calldata: &[u8]) -> Vec<u8> {
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    let data = hex::decode(body["data"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+
+    let decoded = ethers::abi::decode(
+        &[ParamType::Bytes, ParamType::Uint(64), ParamType::Bytes],
+        &data,
+    )
+    .unwrap();
+    let result = decoded[0].clone().into_bytes().unwrap();
+    let expires = decoded[1].clone().into_uint().unwrap().as_u64();
+    let signature = decoded[2].clone().into_bytes().unwrap();
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    assert!(expires > now, "response is already expired");
+    assert!(expires <= now + 3600, "response is valid for too long");
+
+    // SignatureVerifier.makeSignatureHash
+    let message = [
+        vec![0x19, 0x00],
+        SENDER.parse::<H160>().unwrap().as_bytes().to_vec(),
+        expires.to_be_bytes().to_vec(),
+        keccak256(calldata).to_vec(),
+        keccak256(&result).to_vec(),
+    ]
+    .concat();
+    let signature = Signature::try_from(signature.as_slice()).unwrap();
+    let signer = signature.recover(H256::from(keccak256(message))).unwrap();
+    assert_eq!(signer, LocalWallet::from_str(SIGNER_KEY).unwrap().address());
+
+    result
+}
+
+fn evm_address_felt() -> FieldElement {
+    FieldElement::from_hex_be(EVM_ADDRESS).unwrap()
+}
+
+fn domain_to_id(id: FieldElement) -> (FieldElement, &'static str, Vec<FieldElement>) {
+    (NAMING, "domain_to_id", vec![id])
+}
+
+#[cfg(test)]
+mod ccip_gateway {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_addr_is_signed_for_the_resolver() {
+        let rpc = MockRpc::new(vec![
+            domain_to_id(felt!("0x2a")),
+            // argent multicall: block number, results count, then each result
+            (
+                MULTICALL,
+                "aggregate",
+                vec![
+                    FieldElement::ONE,
+                    FieldElement::TWO,
+                    FieldElement::ONE,
+                    evm_address_felt(),
+                    FieldElement::ONE,
+                    FieldElement::ZERO,
+                ],
+            ),
+        ]);
+        let gateway = start_gateway(&rpc).await;
+
+        let calldata = resolve_calldata("th0rgal.snid.eth", resolver_call("3b3b57de", &[node()]));
+        let result = verify_response(post_resolve(gateway, &calldata).await, &calldata).await;
+
+        assert_eq!(
+            ethers::abi::decode(&[ParamType::Address], &result).unwrap(),
+            vec![Token::Address(EVM_ADDRESS.parse().unwrap())]
+        );
+        let calls = rpc.calls.lock().unwrap().clone();
+        assert_eq!(
+            calls[0],
+            (
+                get_selector_from_name("domain_to_id").unwrap(),
+                vec![FieldElement::ONE, encode("th0rgal").unwrap()]
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_addr_multichain_reads_user_data() {
+        let rpc = MockRpc::new(vec![
+            domain_to_id(felt!("0x2a")),
+            (STARKNETID, "get_user_data", vec![evm_address_felt()]),
+        ]);
+        let gateway = start_gateway(&rpc).await;
+
+        // optimism coin type, not configured in evm_networks
+        let calldata = resolve_calldata(
+            "th0rgal.snid.eth",
+            resolver_call("f1cb7e06", &[node(), Token::Uint(0x8000000a_u64.into())]),
+        );
+        let result = verify_response(post_resolve(gateway, &calldata).await, &calldata).await;
+
+        assert_eq!(
+            ethers::abi::decode(&[ParamType::Bytes], &result).unwrap(),
+            vec![Token::Bytes(
+                hex::decode(EVM_ADDRESS.trim_start_matches("0x")).unwrap()
+            )]
+        );
+        assert!(rpc
+            .called_selectors()
+            .contains(&get_selector_from_name("get_user_data").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_addr_multichain_resolves_starknet_addresses() {
+        let rpc = MockRpc::new(vec![domain_to_id(felt!("0x2a"))]);
+        let resolvers = ResolverChain::new(vec![Box::new(FixedResolver(Ok(Resolution {
+            addr: "0x0123".to_string(),
+            domain_expiry: None,
+            valid_until: None,
+        })))]);
+        let gateway = start_gateway_with(&rpc, resolvers).await;
+
+        let calldata = resolve_calldata(
+            "th0rgal.snid.eth",
+            resolver_call(
+                "f1cb7e06",
+                &[node(), Token::Uint(STARKNET_COIN_TYPE.into())],
+            ),
+        );
+        let result = verify_response(post_resolve(gateway, &calldata).await, &calldata).await;
+
+        // the address is padded to 32 bytes
+        assert_eq!(
+            ethers::abi::decode(&[ParamType::Bytes], &result).unwrap(),
+            vec![Token::Bytes(felt!("0x0123").to_bytes_be().to_vec())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_addr_multichain_surfaces_resolver_errors() {
+        let rpc = MockRpc::new(vec![domain_to_id(felt!("0x2a"))]);
+        let resolvers = ResolverChain::new(vec![Box::new(FixedResolver(Err(
+            ResolutionError::ResolverUnreachable("gateway down".to_string()),
+        )))]);
+        let gateway = start_gateway_with(&rpc, resolvers).await;
+
+        let calldata = resolve_calldata(
+            "th0rgal.snid.eth",
+            resolver_call(
+                "f1cb7e06",
+                &[node(), Token::Uint(STARKNET_COIN_TYPE.into())],
+            ),
+        );
+        let response = post_resolve(gateway, &calldata).await;
+
+        assert_eq!(response.status(), 502);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "resolver_unreachable");
+    }
+
+    #[tokio::test]
+    async fn test_addr_multichain_without_starknet_address() {
+        let rpc = MockRpc::new(vec![domain_to_id(felt!("0x2a"))]);
+        let resolvers = ResolverChain::new(vec![Box::new(FixedResolver(Err(
+            ResolutionError::NotFound("no address".to_string()),
+        )))]);
+        let gateway = start_gateway_with(&rpc, resolvers).await;
+
+        let calldata = resolve_calldata(
+            "th0rgal.snid.eth",
+            resolver_call(
+                "f1cb7e06",
+                &[node(), Token::Uint(STARKNET_COIN_TYPE.into())],
+            ),
+        );
+        let response = post_resolve(gateway, &calldata).await;
+
+        assert_eq!(response.status(), 404);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_multicall_returns_signed_bytes_array() {
+        let rpc = MockRpc::new(vec![
+            domain_to_id(felt!("0x2a")),
+            (STARKNETID, "get_user_data", vec![evm_address_felt()]),
+        ]);
+        let gateway = start_gateway(&rpc).await;
+
+        let calls = vec![
+            Token::Bytes(resolver_call(
+                "f1cb7e06",
+                &[node(), Token::Uint(0x8000000a_u64.into())],
+            )),
+            // no unbounded user data is mocked, this record is not found
+            Token::Bytes(resolver_call(
+                "59d1d43c",
+                &[node(), Token::String("description".to_string())],
+            )),
+        ];
+        let calldata = resolve_calldata(
+            "th0rgal.snid.eth",
+            resolver_call("ac9650d8", &[Token::Array(calls)]),
+        );
+        let result = verify_response(post_resolve(gateway, &calldata).await, &calldata).await;
+
+        let results = ethers::abi::decode(&[ParamType::Array(Box::new(ParamType::Bytes))], &result)
+            .unwrap()[0]
+            .clone()
+            .into_array()
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        let addr = results[0].clone().into_bytes().unwrap();
+        assert_eq!(
+            ethers::abi::decode(&[ParamType::Bytes], &addr).unwrap(),
+            vec![Token::Bytes(
+                hex::decode(EVM_ADDRESS.trim_start_matches("0x")).unwrap()
+            )]
+        );
+        assert_eq!(results[1], Token::Bytes(Vec::new()));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_identity_is_not_signed() {
+        let rpc = MockRpc::new(vec![domain_to_id(FieldElement::ZERO)]);
+        let gateway = start_gateway(&rpc).await;
+
+        let calldata = resolve_calldata("nobody.snid.eth", resolver_call("3b3b57de", &[node()]));
+        let response = post_resolve(gateway, &calldata).await;
+
+        assert_eq!(response.status(), 404);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_forward_names_have_no_name() {
+        let rpc = MockRpc::new(vec![domain_to_id(felt!("0x2a"))]);
+        let gateway = start_gateway(&rpc).await;
+
+        let calldata = resolve_calldata("th0rgal.snid.eth", resolver_call("691f3431", &[node()]));
+        let result = verify_response(post_resolve(gateway, &calldata).await, &calldata).await;
+
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reverse_names_need_a_verifier() {
+        let rpc = MockRpc::new(vec![]);
+        let gateway = start_gateway(&rpc).await;
+
+        // self-set evm-address user data is never used to answer reverse names
+        let name = format!("{}.addr.reverse", EVM_ADDRESS.trim_start_matches("0x"));
+        let calldata = resolve_calldata(&name, resolver_call("691f3431", &[node()]));
+        let response = post_resolve(gateway, &calldata).await;
+
+        assert_eq!(response.status(), 404);
+        assert!(rpc.called_selectors().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_sender_is_rejected() {
+        let rpc = MockRpc::new(vec![
+            domain_to_id(felt!("0x2a")),
+            (STARKNETID, "get_user_data", vec![evm_address_felt()]),
+        ]);
+        let gateway = start_gateway(&rpc).await;
+
+        let calldata = resolve_calldata(
+            "th0rgal.snid.eth",
+            resolver_call("f1cb7e06", &[node(), Token::Uint(0x8000000a_u64.into())]),
+        );
+        let response = reqwest::Client::new()
+            .post(format!("http://{}/crosschain/ethereum/resolve", gateway))
+            .json(&json!({
+                "data": format!("0x{}", hex::encode(&calldata)),
+                "sender": "not an address",
+            }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 422);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["code"], "validation_error");
+    }
+
+    #[tokio::test]
+    async fn test_malformed_calldata_is_rejected() {
+        let rpc = MockRpc::new(vec![]);
+        let gateway = start_gateway(&rpc).await;
+
+        let response = post_resolve(gateway, &hex::decode("9061b92300").unwrap()).await;
+
+        assert_eq!(response.status(), 422);
+        assert!(rpc.called_selectors().is_empty());
+    }
+}
@@ -0,0 +1,28 @@
+use crate::endpoints::crosschain::claims::reservation_key;
+
+#[cfg(test)]
+mod reservation_key {
+    use super::*;
+
+    #[test]
+    fn test_domains_are_case_insensitive() {
+        assert_eq!(reservation_key("sns", "Foo.sol"), "sns:foo.sol");
+        assert_eq!(
+            reservation_key("sns", "FOO.SOL"),
+            reservation_key("sns", "foo.sol")
+        );
+    }
+
+    #[test]
+    fn test_trailing_dot_and_spaces_are_dropped() {
+        assert_eq!(reservation_key("ens", " vitalik.eth. "), "ens:vitalik.eth");
+    }
+
+    #[test]
+    fn test_sources_are_kept_apart() {
+        assert_ne!(
+            reservation_key("sns", "foo.sol"),
+            reservation_key("ens", "foo.sol")
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::endpoints::domain_history::{clip, overlaps, segments};
+
+#[cfg(test)]
+mod segments {
+    use super::*;
+
+    #[test]
+    fn test_consecutive_versions_are_merged() {
+        let versions = vec![
+            ("0x1", 10, Some(20)),
+            ("0x1", 20, Some(30)),
+            ("0x2", 30, None),
+        ];
+        assert_eq!(
+            segments(versions.into_iter()),
+            vec![("0x1", 10, Some(30)), ("0x2", 30, None)]
+        );
+    }
+
+    #[test]
+    fn test_gaps_and_changes_are_kept() {
+        // the same value after a gap, and a value coming back after another one
+        let versions = vec![
+            ("0x1", 10, Some(20)),
+            ("0x1", 25, Some(30)),
+            ("0x2", 30, Some(40)),
+            ("0x1", 40, None),
+        ];
+        assert_eq!(segments(versions.clone().into_iter()), versions);
+        assert!(segments(Vec::<(&str, i64, Option<i64>)>::new().into_iter()).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod overlaps {
+    use super::*;
+
+    #[test]
+    fn test_closed_ranges() {
+        assert!(overlaps((10, Some(20)), (15, Some(30))));
+        assert!(overlaps((10, Some(40)), (15, Some(30))));
+        // ranges are [from, to), touching ranges don't overlap
+        assert!(!overlaps((10, Some(20)), (20, Some(30))));
+        assert!(!overlaps((30, Some(40)), (10, Some(20))));
+    }
+
+    #[test]
+    fn test_open_ranges() {
+        assert!(overlaps((10, None), (15, Some(30))));
+        assert!(overlaps((10, None), (5, None)));
+        assert!(!overlaps((30, None), (10, Some(30))));
+    }
+}
+
+#[cfg(test)]
+mod clip {
+    use super::*;
+
+    #[test]
+    fn test_events_are_clipped_to_the_periods() {
+        // owned since block 0, attached to the domain on [10, 20) and [30, ...)
+        let periods = vec![(10, Some(20)), (30, None)];
+        assert_eq!(
+            clip(("0x1", 0, None), &periods),
+            vec![("0x1", 10, Some(20)), ("0x1", 30, None)]
+        );
+        assert_eq!(
+            clip(("0x1", 15, Some(35)), &periods),
+            vec![("0x1", 15, Some(20)), ("0x1", 30, Some(35))]
+        );
+    }
+
+    #[test]
+    fn test_events_outside_the_periods_are_dropped() {
+        let periods = vec![(10, Some(20))];
+        assert!(clip(("0x1", 20, Some(30)), &periods).is_empty());
+        assert!(clip(("0x1", 0, Some(10)), &periods).is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{
+    endpoints::get_domain_price::{domain_length, parse_stark_domain, price_breakdown, tax_rate},
+    errors::ApiError,
+    models::{State, States},
+};
+use ethers::types::U256;
+use std::collections::HashMap;
+
+fn wei(value: &str) -> U256 {
+    U256::from_dec_str(value).unwrap()
+}
+
+#[cfg(test)]
+mod parse_stark_domain {
+    use super::*;
+
+    #[test]
+    fn test_valid_domains() {
+        assert_eq!(parse_stark_domain("fricoben.stark").unwrap(), "fricoben");
+        assert_eq!(parse_stark_domain("fricoben").unwrap(), "fricoben");
+    }
+
+    #[test]
+    fn test_invalid_domains() {
+        assert!(parse_stark_domain(".stark").is_err());
+        assert!(parse_stark_domain("").is_err());
+        assert!(parse_stark_domain("sub.fricoben.stark").is_err());
+    }
+}
+
+fn states() -> States {
+    States {
+        states: HashMap::from([(
+            "CA".to_string(),
+            State {
+                rate: 0.0725,
+                type_: "state".to_string(),
+            },
+        )]),
+    }
+}
+
+#[cfg(test)]
+mod domain_length {
+    use super::*;
+
+    #[test]
+    fn test_basic_alphabet() {
+        assert_eq!(domain_length("fricoben").unwrap(), 8);
+        assert_eq!(domain_length("b").unwrap(), 1);
+        assert_eq!(domain_length("0-9").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_escaped_last_character() {
+        // a trailing "a" is escaped so that the encoding does not end with a zero
+        assert_eq!(domain_length("a").unwrap(), 1);
+        assert_eq!(domain_length("aaa").unwrap(), 3);
+        assert_eq!(domain_length("benaa").unwrap(), 5);
+    }
+
+    #[test]
+    fn test_invalid_label() {
+        assert!(matches!(
+            domain_length("Fricoben"),
+            Err(ApiError::Validation(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tax_rate {
+    use super::*;
+
+    #[test]
+    fn test_known_state() {
+        assert_eq!(tax_rate(&states(), Some("CA")).unwrap(), 0.0725);
+    }
+
+    #[test]
+    fn test_no_state() {
+        assert_eq!(tax_rate(&states(), None).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_unknown_state() {
+        assert!(matches!(
+            tax_rate(&states(), Some("XX")),
+            Err(ApiError::Validation(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod price_breakdown {
+    use super::*;
+
+    #[test]
+    fn test_without_tax() {
+        // 0.0024 ETH at 2500 STRK per ETH
+        let (price, tax) =
+            price_breakdown(wei("2400000000000000"), wei("2500000000000000000000"), 0.0).unwrap();
+        assert_eq!(price, wei("6000000000000000000"));
+        assert_eq!(tax, U256::zero());
+    }
+
+    #[test]
+    fn test_with_tax() {
+        let (price, tax) = price_breakdown(
+            wei("2400000000000000"),
+            wei("2500000000000000000000"),
+            0.077,
+        )
+        .unwrap();
+        assert_eq!(price, wei("6000000000000000000"));
+        assert_eq!(tax, wei("462000000000000000"));
+    }
+
+    #[test]
+    fn test_rounded_down() {
+        // 6 decimals token, 1 / 3 of a unit is dropped
+        let (price, _) = price_breakdown(wei("1000000000000000"), wei("3333333333"), 0.0).unwrap();
+        assert_eq!(price, wei("3333333"));
+    }
+
+    #[test]
+    fn test_invalid_tax_rate() {
+        assert!(price_breakdown(U256::one(), U256::one(), -0.1).is_none());
+        assert!(price_breakdown(U256::one(), U256::one(), 1.5).is_none());
+    }
+}
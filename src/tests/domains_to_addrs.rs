@@ -0,0 +1,80 @@
+use crate::{
+    endpoints::domains_to_addrs::{DomainToAddrData, DomainsToAddrsQuery},
+    errors::ApiError,
+    resolving::{Resolution, ResolutionError},
+};
+use serde_json::{json, Value};
+
+fn query(count: usize) -> DomainsToAddrsQuery {
+    DomainsToAddrsQuery {
+        domains: (0..count).map(|i| format!("domain{}.stark", i)).collect(),
+    }
+}
+
+fn serialized(data: DomainToAddrData) -> Value {
+    serde_json::to_value(data).unwrap()
+}
+
+#[cfg(test)]
+mod check_size {
+    use super::*;
+
+    #[test]
+    fn test_batches_up_to_the_cap() {
+        assert!(query(0).check_size(100).is_ok());
+        assert!(query(100).check_size(100).is_ok());
+    }
+
+    #[test]
+    fn test_larger_batches_are_refused() {
+        let error = query(101).check_size(100).unwrap_err();
+        assert!(matches!(error, ApiError::Validation(_)));
+        assert_eq!(error.status(), 422);
+    }
+}
+
+#[cfg(test)]
+mod item_errors {
+    use super::*;
+
+    #[test]
+    fn test_resolved_domain_has_no_error() {
+        let data = DomainToAddrData::from_result(
+            "alice.stark".to_string(),
+            Ok(Resolution {
+                addr: "0x123".to_string(),
+                domain_expiry: Some(1700000000),
+                valid_until: None,
+            }),
+        );
+        assert_eq!(
+            serialized(data),
+            json!({ "domain": "alice.stark", "addr": "0x123", "domain_expiry": 1700000000 })
+        );
+    }
+
+    #[test]
+    fn test_errors_have_a_code_and_a_message() {
+        let data = DomainToAddrData::from_result(
+            "alice.stark".to_string(),
+            Err(ResolutionError::NotFound("no address found".to_string())),
+        );
+        assert_eq!(
+            serialized(data),
+            json!({
+                "domain": "alice.stark",
+                "addr": null,
+                "domain_expiry": null,
+                "error": { "code": "not_found", "message": "no address found" },
+            })
+        );
+
+        let data = DomainToAddrData::from_result(
+            "alice.notion.stark".to_string(),
+            Err(ResolutionError::ResolverUnreachable("timeout".to_string())),
+        );
+        let error = &serialized(data)["error"];
+        assert_eq!(error["code"], "resolver_unreachable");
+        assert!(error["message"].as_str().unwrap().contains("timeout"));
+    }
+}
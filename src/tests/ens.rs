@@ -0,0 +1,239 @@
+use crate::{
+    config::Config,
+    endpoints::crosschain::{
+        ethereum::ens::{get_ens_owner, namehash, normalize_name, parse_eth_domain, verify_eip191},
+        source_naming::claim_message,
+    },
+    errors::ApiError,
+};
+use axum::{extract::State, routing::post, Json, Router};
+use ethers::{
+    abi::{self, Token},
+    signers::{LocalWallet, Signer},
+    types::Address,
+    utils::{id, keccak256},
+};
+use serde_json::{json, Value};
+use starknet::macros::felt;
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+};
+
+// well known test key, never used on a real network
+const SIGNER_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const REGISTRANT: &str = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045";
+const HOLDER: &str = "0x2a8d3a1f0f0b4f2b2a3c0c0b0f5d6e7f8a9b0c1d";
+
+enum CallResult {
+    Address(Address),
+    Revert,
+    RpcError,
+}
+
+// results of the `eth_call` answered by the mock Ethereum RPC, by (contract, calldata)
+type MockCalls = Arc<HashMap<(Address, Vec<u8>), CallResult>>;
+
+async fn rpc_handler(State(calls): State<MockCalls>, Json(request): Json<Value>) -> Json<Value> {
+    let id = request["id"].clone();
+    let tx = &request["params"][0];
+    let contract: Address = tx["to"].as_str().unwrap_or_default().parse().unwrap();
+    let data = tx["data"]
+        .as_str()
+        .or_else(|| tx["input"].as_str())
+        .unwrap_or_default();
+    let data = hex::decode(data.trim_start_matches("0x")).unwrap();
+    Json(match calls.get(&(contract, data)) {
+        Some(CallResult::Address(address)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": format!("0x{}", hex::encode(abi::encode(&[Token::Address(*address)]))),
+        }),
+        Some(CallResult::Revert) => rpc_error(id, 3, "execution reverted"),
+        Some(CallResult::RpcError) => rpc_error(id, -32005, "limit exceeded"),
+        None => json!({ "jsonrpc": "2.0", "id": id, "result": "0x" }),
+    })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn owner_of(contract: Address, token_id: [u8; 32]) -> (Address, Vec<u8>) {
+    let data = [
+        id("ownerOf(uint256)").to_vec(),
+        abi::encode(&[Token::FixedBytes(token_id.to_vec())]),
+    ]
+    .concat();
+    (contract, data)
+}
+
+// Config reading ENS from a mock RPC answering these calls
+fn mock_ens(calls: Vec<((Address, Vec<u8>), CallResult)>) -> Config {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr: SocketAddr = listener.local_addr().unwrap();
+    let router = Router::new()
+        .route("/", post(rpc_handler))
+        .with_state(Arc::new(calls.into_iter().collect::<HashMap<_, _>>()));
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service())
+            .await
+            .unwrap();
+    });
+    let mut conf = Config::default();
+    conf.ens.rpc_url = format!("http://{}", addr);
+    conf
+}
+
+#[cfg(test)]
+mod parse_eth_domain {
+    use super::*;
+
+    #[test]
+    fn test_valid_domains() {
+        assert_eq!(parse_eth_domain("vitalik.eth").unwrap(), "vitalik");
+    }
+
+    #[test]
+    fn test_invalid_domains() {
+        assert!(parse_eth_domain("vitalik").is_err());
+        assert!(parse_eth_domain(".eth").is_err());
+        assert!(parse_eth_domain("pay.vitalik.eth").is_err());
+        assert!(parse_eth_domain("vitalik.sol").is_err());
+        assert!(parse_eth_domain("vita lik.eth").is_err());
+    }
+}
+
+#[cfg(test)]
+mod namehash {
+    use super::*;
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(namehash("").unwrap(), [0u8; 32]);
+        assert_eq!(
+            hex::encode(namehash("eth").unwrap()),
+            "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+        );
+        assert_eq!(
+            hex::encode(namehash("vitalik.eth").unwrap()),
+            "ee6c4522aab0003e8d14cd40a6af439055fd2577951148c14b6cea9a53475835"
+        );
+    }
+
+    #[test]
+    fn test_names_are_normalized() {
+        assert_eq!(normalize_name("Vitalik.ETH").unwrap(), "vitalik.eth");
+        assert_eq!(normalize_name("_dao.eth").unwrap(), "_dao.eth");
+        assert_eq!(
+            namehash("Vitalik.ETH").unwrap(),
+            namehash("vitalik.eth").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_labels() {
+        for name in [
+            "vitalik..eth",
+            "vitalik.eth.",
+            "vita lik.eth",
+            "vitalik!.eth",
+            "da_o.eth",
+            "xn--vitalik.eth",
+        ] {
+            assert!(matches!(namehash(name), Err(ApiError::Validation(_))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod eip191 {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_verify_signature() {
+        let wallet: LocalWallet = SIGNER_KEY.parse().unwrap();
+        let owner = format!("{:?}", wallet.address());
+        let message = claim_message(&owner, "vitalik.eth", &felt!("0x123"), 1700000000);
+        let signature = wallet.sign_message(&message).await.unwrap().to_vec();
+
+        assert!(verify_eip191(&owner, &message, &signature).is_ok());
+        // signed for another target address
+        let other = claim_message(&owner, "vitalik.eth", &felt!("0x456"), 1700000000);
+        assert!(verify_eip191(&owner, &other, &signature).is_err());
+        assert!(verify_eip191(&owner, &message, &signature[..64]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod get_ens_owner {
+    use super::*;
+
+    fn registrar_owner(result: CallResult) -> ((Address, Vec<u8>), CallResult) {
+        let conf = Config::default();
+        (
+            owner_of(conf.ens.registrar, keccak256("vitalik".as_bytes())),
+            result,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_registrant_is_the_owner() {
+        let conf = mock_ens(vec![registrar_owner(CallResult::Address(
+            REGISTRANT.parse().unwrap(),
+        ))]);
+        let owner = get_ens_owner(&conf, "vitalik.eth").await.unwrap();
+        assert_eq!(format!("{:?}", owner), REGISTRANT);
+        // the owner of a mixed-case name is the one of its normalized name
+        let owner = get_ens_owner(&conf, "Vitalik.ETH").await.unwrap();
+        assert_eq!(format!("{:?}", owner), REGISTRANT);
+    }
+
+    #[tokio::test]
+    async fn test_wrapped_names_are_owned_by_the_token_holder() {
+        let wrapper = Config::default().ens.name_wrapper;
+        let conf = mock_ens(vec![
+            registrar_owner(CallResult::Address(wrapper)),
+            (
+                owner_of(wrapper, namehash("vitalik.eth").unwrap()),
+                CallResult::Address(HOLDER.parse().unwrap()),
+            ),
+        ]);
+        let owner = get_ens_owner(&conf, "vitalik.eth").await.unwrap();
+        assert_eq!(format!("{:?}", owner), HOLDER);
+
+        // expired wrapped names have no holder
+        let conf = mock_ens(vec![
+            registrar_owner(CallResult::Address(wrapper)),
+            (
+                owner_of(wrapper, namehash("vitalik.eth").unwrap()),
+                CallResult::Address(Address::zero()),
+            ),
+        ]);
+        let result = get_ens_owner(&conf, "vitalik.eth").await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expired_names_have_no_owner() {
+        // the registrar reverts once the name expired
+        let conf = mock_ens(vec![registrar_owner(CallResult::Revert)]);
+        let result = get_ens_owner(&conf, "vitalik.eth").await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+
+        // or returns nothing on some nodes
+        let conf = mock_ens(vec![]);
+        let result = get_ens_owner(&conf, "vitalik.eth").await;
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_failures_are_not_ownership() {
+        let conf = mock_ens(vec![registrar_owner(CallResult::RpcError)]);
+        let result = get_ens_owner(&conf, "vitalik.eth").await;
+        assert!(matches!(result, Err(ApiError::Upstream(_))));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    address::StarknetAddress,
+    errors::ApiError,
+    extractors::{Json, Query},
+    resolving::ResolutionError,
+};
+use axum::{
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+
+#[derive(Deserialize)]
+struct AddrQuery {
+    addr: StarknetAddress,
+}
+
+async fn query_handler(Query(query): Query<AddrQuery>) -> impl IntoResponse {
+    Json(query.addr.to_hex())
+}
+
+async fn json_handler(Json(query): Json<AddrQuery>) -> impl IntoResponse {
+    Json(query.addr.to_hex())
+}
+
+fn serve() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = Router::new()
+        .route("/query", get(query_handler))
+        .route("/json", post(json_handler));
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service())
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+// Checks that a response is a validation error with a JSON body
+async fn assert_validation_error(response: reqwest::Response) {
+    assert_eq!(response.status(), 422);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "validation_error");
+    assert!(body["message"].as_str().is_some());
+}
+
+#[cfg(test)]
+mod api_error {
+    use super::*;
+
+    #[test]
+    fn test_status_and_code() {
+        for (error, status, code) in [
+            (
+                ApiError::NotFound(String::new()),
+                StatusCode::NOT_FOUND,
+                "not_found",
+            ),
+            (
+                ApiError::Validation(String::new()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_error",
+            ),
+            (
+                ApiError::Unauthorized(String::new()),
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+            ),
+            (
+                ApiError::Upstream(String::new()),
+                StatusCode::BAD_GATEWAY,
+                "upstream_error",
+            ),
+            (
+                ApiError::ExpiredHint,
+                StatusCode::BAD_GATEWAY,
+                "expired_hint",
+            ),
+            (
+                ApiError::BadSignature,
+                StatusCode::BAD_GATEWAY,
+                "bad_signature",
+            ),
+            (
+                ApiError::ResolverUnreachable(String::new()),
+                StatusCode::BAD_GATEWAY,
+                "resolver_unreachable",
+            ),
+            (
+                ApiError::Database(String::new()),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "database_unavailable",
+            ),
+            (
+                ApiError::Internal(String::new()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+            ),
+        ] {
+            assert_eq!(error.status(), status, "{}", code);
+            assert_eq!(error.code(), code);
+        }
+    }
+
+    #[test]
+    fn test_resolution_errors_keep_their_code() {
+        for (error, code) in [
+            (ResolutionError::NotFound(String::new()), "not_found"),
+            (ResolutionError::Invalid(String::new()), "validation_error"),
+            (
+                ResolutionError::Database(String::new()),
+                "database_unavailable",
+            ),
+            (ResolutionError::Failed(String::new()), "upstream_error"),
+            (ResolutionError::ExpiredHint, "expired_hint"),
+            (ResolutionError::BadSignature, "bad_signature"),
+            (
+                ResolutionError::ResolverUnreachable(String::new()),
+                "resolver_unreachable",
+            ),
+        ] {
+            assert_eq!(ApiError::from(error).code(), code);
+        }
+    }
+}
+
+#[cfg(test)]
+mod extractors {
+    use super::*;
+
+    const ADDR: &str = "0x02fd23d9182193775423497fc0c472e156c57c69e4089a1967fb288a2d84e914";
+
+    #[tokio::test]
+    async fn test_valid_query() {
+        let addr = serve();
+        let response = reqwest::get(format!("http://{}/query?addr={}", addr, ADDR))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.json::<Value>().await.unwrap(), json!(ADDR));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_query_is_a_json_validation_error() {
+        let addr = serve();
+        for query in [
+            "",
+            "?addr=0xzz",
+            "?addr=0xd8da6bf26964af9d7eed9e03e53415d37aa96045",
+        ] {
+            let response = reqwest::get(format!("http://{}/query{}", addr, query))
+                .await
+                .unwrap();
+            assert_validation_error(response).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_json_is_a_json_validation_error() {
+        let addr = serve();
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/json", addr);
+
+        let response = client
+            .post(&url)
+            .json(&json!({ "addr": ADDR }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        for body in [json!({ "addr": "0xzz" }), json!({})] {
+            let response = client.post(&url).json(&body).send().await.unwrap();
+            assert_validation_error(response).await;
+        }
+        // malformed body and missing content type
+        let response = client.post(&url).body("{").send().await.unwrap();
+        assert_validation_error(response).await;
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    endpoints::addr_to_domain::create_normal_pipeline,
+    errors::ApiError,
+    history::{at_block, filter_at_block, BlockTimestamps},
+};
+use mongodb::bson::{doc, Document};
+use starknet::core::types::BlockStatus;
+
+#[cfg(test)]
+mod at_block {
+    use super::*;
+
+    #[test]
+    fn test_latest_state_is_untouched() {
+        let pipeline = vec![doc! { "$match": { "_cursor.to": null, "domain": "th0rgal.stark" } }];
+        assert_eq!(at_block(pipeline.clone(), None).unwrap(), pipeline);
+    }
+
+    #[test]
+    fn test_live_filters_are_rewritten() {
+        let pipeline = vec![doc! { "$match": { "_cursor.to": null, "domain": "th0rgal.stark" } }];
+        assert_eq!(
+            at_block(pipeline, Some(1000)).unwrap(),
+            vec![doc! { "$match": {
+                "_cursor.to": { "$not": { "$lte": 1000_i64 } },
+                "_cursor.from": { "$lte": 1000_i64 },
+                "domain": "th0rgal.stark"
+            } }]
+        );
+    }
+
+    #[test]
+    fn test_nested_lookups_are_rewritten() {
+        let pipeline = vec![doc! { "$lookup": {
+            "from": "id_owners",
+            "pipeline": [
+                doc! { "$match": {
+                    "$or": [
+                        { "_cursor.to": null },
+                        { "_cursor.to": { "$exists": false } }
+                    ]
+                } }
+            ],
+            "as": "id_data"
+        } }];
+        let live = doc! {
+            "_cursor.to": { "$not": { "$lte": 42_i64 } },
+            "_cursor.from": { "$lte": 42_i64 }
+        };
+        assert_eq!(
+            at_block(pipeline, Some(42)).unwrap(),
+            vec![doc! { "$lookup": {
+                "from": "id_owners",
+                "pipeline": [
+                    doc! { "$match": { "$or": [live.clone(), live] } }
+                ],
+                "as": "id_data"
+            } }]
+        );
+    }
+
+    #[test]
+    fn test_blocks_out_of_the_stored_range_are_refused() {
+        let pipeline = vec![doc! { "$match": { "_cursor.to": null } }];
+        assert!(matches!(
+            at_block(pipeline, Some(u64::MAX)),
+            Err(ApiError::Validation(_))
+        ));
+        assert!(matches!(
+            filter_at_block(doc! { "_cursor.to": null }, Some(i64::MAX as u64 + 1)),
+            Err(ApiError::Validation(_))
+        ));
+        assert!(filter_at_block(doc! { "_cursor.to": null }, Some(i64::MAX as u64)).is_ok());
+    }
+
+    #[test]
+    fn test_other_cursor_filters_are_kept() {
+        let pipeline = vec![doc! { "$match": { "_cursor.to": { "$ne": null } } }];
+        assert_eq!(at_block(pipeline.clone(), Some(42)).unwrap(), pipeline);
+    }
+}
+
+#[cfg(test)]
+mod addr_to_domain_at_block {
+    use super::*;
+
+    // `$match` of the first stage of the `$lookup` of a collection
+    fn lookup_match<'a>(pipeline: &'a [Document], collection: &str) -> &'a Document {
+        pipeline
+            .iter()
+            .filter_map(|stage| stage.get_document("$lookup").ok())
+            .find(|lookup| lookup.get_str("from").ok() == Some(collection))
+            .and_then(|lookup| lookup.get_array("pipeline").ok())
+            .and_then(|stages| stages[0].as_document())
+            .and_then(|stage| stage.get_document("$match").ok())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_custom_resolutions_are_read_at_the_block() {
+        let pipeline = create_normal_pipeline(&"0x0123".to_string());
+        assert!(lookup_match(&pipeline, "custom_resolutions")
+            .get("_cursor.to")
+            .is_some());
+
+        let pipeline = at_block(pipeline, Some(42)).unwrap();
+        let filter = lookup_match(&pipeline, "custom_resolutions");
+        assert_eq!(
+            filter.get_document("_cursor.to").unwrap(),
+            &doc! { "$not": { "$lte": 42_i64 } }
+        );
+        assert_eq!(
+            filter.get_document("_cursor.from").unwrap(),
+            &doc! { "$lte": 42_i64 }
+        );
+    }
+}
+
+#[cfg(test)]
+mod block_timestamps {
+    use super::*;
+
+    #[test]
+    fn test_only_final_blocks_are_kept() {
+        let timestamps = BlockTimestamps::default();
+        timestamps.insert(1, 1_700_000_000, BlockStatus::AcceptedOnL1);
+        timestamps.insert(2, 1_700_000_030, BlockStatus::AcceptedOnL2);
+        assert_eq!(timestamps.get(1), Some(1_700_000_000));
+        // may still be reorganized
+        assert_eq!(timestamps.get(2), None);
+        assert_eq!(timestamps.get(3), None);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::endpoints::crosschain::ethereum::{
+    lookup::ResolverFunctionCall,
+    utils::{decode_data, decode_ens},
+};
+use ethabi::Token;
+use proptest::{collection::vec, prelude::*};
+
+// every selector handled by ResolverFunctionCall
+const SELECTORS: [[u8; 4]; 9] = [
+    [0x3b, 0x3b, 0x57, 0xde],
+    [0x69, 0x1f, 0x34, 0x31],
+    [0x22, 0x03, 0xab, 0x56],
+    [0x59, 0xd1, 0xd4, 0x3c],
+    [0xbc, 0x1c, 0x58, 0xd1],
+    [0xb8, 0xf2, 0xbb, 0xb4],
+    [0xf1, 0xcb, 0x7e, 0x06],
+    [0xc8, 0x69, 0x02, 0x33],
+    [0xac, 0x96, 0x50, 0xd8],
+];
+
+#[cfg(test)]
+mod multicall {
+    use super::*;
+
+    fn call(selector: &str, params: &[Token]) -> Vec<u8> {
+        [hex::decode(selector).unwrap(), ethers::abi::encode(params)].concat()
+    }
+
+    #[test]
+    fn test_inner_calls_are_decoded() {
+        let node = vec![7u8; 32];
+        let text = call(
+            "59d1d43c",
+            &[
+                Token::FixedBytes(node.clone()),
+                Token::String("avatar".to_string()),
+            ],
+        );
+        let content_hash = call("bc1c58d1", &[Token::FixedBytes(node.clone())]);
+        let data = call(
+            "ac9650d8",
+            &[Token::Array(vec![
+                Token::Bytes(text),
+                Token::Bytes(content_hash),
+            ])],
+        );
+
+        assert_eq!(
+            ResolverFunctionCall::try_from(data.as_slice()).unwrap(),
+            ResolverFunctionCall::Multicall(vec![
+                ResolverFunctionCall::Text(node.clone(), "avatar".to_string()),
+                ResolverFunctionCall::ContentHash(node),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_nested_multicall_is_rejected() {
+        let inner = call("ac9650d8", &[Token::Array(vec![])]);
+        let data = call("ac9650d8", &[Token::Array(vec![Token::Bytes(inner)])]);
+        assert!(ResolverFunctionCall::try_from(data.as_slice()).is_err());
+    }
+}
+
+#[cfg(test)]
+mod decode_ens {
+    use super::*;
+
+    #[test]
+    fn test_dns_encoded_name() {
+        assert_eq!(
+            decode_ens(b"\x07th0rgal\x04snid\x03eth\x00").unwrap(),
+            "th0rgal.snid.eth"
+        );
+    }
+
+    #[test]
+    fn test_malformed_names() {
+        // not terminated
+        assert!(decode_ens(b"\x07th0rgal").is_err());
+        // label longer than the name
+        assert!(decode_ens(b"\x3fth0rgal\x00").is_err());
+        // invalid utf-8
+        assert!(decode_ens(b"\x02\xff\xfe\x00").is_err());
+    }
+}
+
+#[cfg(test)]
+mod decoding_never_panics {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_arbitrary_call_data(data in vec(any::<u8>(), 0..512)) {
+            let _ = ResolverFunctionCall::try_from(data.as_slice());
+        }
+
+        #[test]
+        fn test_known_selector_with_arbitrary_payload(
+            selector in proptest::sample::select(SELECTORS.to_vec()),
+            payload in vec(any::<u8>(), 0..512),
+        ) {
+            let data = [selector.to_vec(), payload].concat();
+            let _ = ResolverFunctionCall::try_from(data.as_slice());
+        }
+
+        #[test]
+        fn test_known_selector_with_arbitrary_words(
+            selector in proptest::sample::select(SELECTORS.to_vec()),
+            words in vec(prop_oneof![Just(0u8), Just(0xff), any::<u8>()], 0..8),
+        ) {
+            // 32 bytes words make offsets and lengths reach the abi decoder
+            let payload: Vec<u8> = words.iter().flat_map(|byte| [*byte; 32]).collect();
+            let data = [selector.to_vec(), payload].concat();
+            let _ = ResolverFunctionCall::try_from(data.as_slice());
+        }
+
+        #[test]
+        fn test_arbitrary_names(name in vec(any::<u8>(), 0..128)) {
+            let _ = decode_ens(&name);
+        }
+
+        #[test]
+        fn test_arbitrary_request_data(data in "(0x9061b923)?[0-9a-fA-F]{0,600}") {
+            let _ = decode_data(&data);
+        }
+
+        #[test]
+        fn test_well_formed_request_with_arbitrary_content(
+            name in vec(any::<u8>(), 0..64),
+            call in vec(any::<u8>(), 0..256),
+        ) {
+            let data = format!(
+                "0x9061b923{}",
+                hex::encode(ethers::abi::encode(&[Token::Bytes(name), Token::Bytes(call)]))
+            );
+            let _ = decode_data(&data);
+        }
+    }
+}
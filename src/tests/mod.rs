@@ -1 +1,71 @@
+use crate::{
+    cache::ResponseCache,
+    config::Config,
+    endpoints::crosschain::ethereum::record_handlers::RecordHandlers,
+    logger::Logger,
+    models::{AppState, States},
+    offchain_gateway::OffchainGateway,
+    price_oracle::Oracle,
+    quotes::QuoteCache,
+    resolving::ResolverChain,
+};
+use mongodb::Client;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+mod address;
+mod admin;
+mod cache;
+mod ccip_gateway;
+mod claims;
+mod domain_history;
+mod domain_price;
+mod domains_to_addrs;
+mod ens;
+mod errors;
+mod history;
+mod lookup;
+mod offchain_gateway;
+mod openapi;
+mod price;
+mod price_oracle;
+mod profile_records;
+mod quote_history;
+mod quotes;
+mod record_handlers;
+mod resolving;
+mod reverse;
+mod social_names;
+mod sns;
 mod utils;
+
+lazy_static::lazy_static! {
+    // the logger can only be initialized once per process
+    static ref LOGGER: Logger = Logger::new(&Config::default().watchtower);
+}
+
+// State of the app, the databases are only reached when a test queries them
+async fn app_state(conf: Config, resolvers: ResolverChain) -> Arc<AppState> {
+    let db = Client::with_uri_str("mongodb://127.0.0.1:27017")
+        .await
+        .unwrap();
+    Arc::new(AppState {
+        starknetid_db: db.database("starknetid"),
+        sales_db: db.database("sales"),
+        free_domains_db: db.database("free_domains"),
+        states: States {
+            states: HashMap::new(),
+        },
+        dynamic_offchain_resolvers: Arc::new(Mutex::new(HashMap::new())),
+        logger: LOGGER.clone(),
+        resolvers,
+        offchain_gateway: OffchainGateway::new(&conf.offchain_resolving).unwrap(),
+        cache: ResponseCache::new(&conf.cache),
+        record_handlers: RecordHandlers::new(&conf).unwrap(),
+        price_oracle: Oracle::new(&conf).unwrap(),
+        quotes: QuoteCache::new(&conf.price_oracle).unwrap(),
+        conf,
+    })
+}
@@ -0,0 +1,251 @@
+use crate::{
+    config::{OffchainResolver, OffchainResolving},
+    offchain_gateway::OffchainGateway,
+    resolving::ResolutionError,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde_json::json;
+use std::{
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Offchain resolver gateway answering every request with a hint valid until
+/// `max_validity`, or with a server error while `failing` is set.
+#[derive(Default)]
+struct MockGateway {
+    failing: AtomicBool,
+    max_validity: AtomicU64,
+    calls: AtomicUsize,
+}
+
+impl MockGateway {
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+async fn gateway_handler(State(gateway): State<Arc<MockGateway>>) -> Response {
+    gateway.calls.fetch_add(1, Ordering::SeqCst);
+    if gateway.failing.load(Ordering::SeqCst) {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    Json(json!({
+        "address": "0x123",
+        "r": "0x1",
+        "s": "0x2",
+        "max_validity": gateway.max_validity.load(Ordering::SeqCst),
+    }))
+    .into_response()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+// Resolver whose only uri is a mock gateway
+fn mock_resolver(gateway: &Arc<MockGateway>) -> OffchainResolver {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = Router::new()
+        .fallback(gateway_handler)
+        .with_state(gateway.clone());
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service())
+            .await
+            .unwrap();
+    });
+    OffchainResolver {
+        resolver_address: "0x153be68cf8fc71138610811dd2b4fa481eb99f3eedcb3fce7369569055be275"
+            .to_string(),
+        public_key: None,
+        uri: vec![format!("http://{}/resolve?domain=", addr)],
+    }
+}
+
+fn offchain_gateway(failure_threshold: u32, cooldown: f64) -> OffchainGateway {
+    OffchainGateway::new(&OffchainResolving {
+        timeout: 5.0,
+        failure_threshold,
+        cooldown,
+    })
+    .unwrap()
+}
+
+#[cfg(test)]
+mod new {
+    use super::*;
+
+    #[test]
+    fn test_invalid_durations_are_refused() {
+        for (timeout, cooldown) in [(-1.0, 60.0), (f64::NAN, 60.0), (5.0, -1.0)] {
+            assert!(OffchainGateway::new(&OffchainResolving {
+                timeout,
+                failure_threshold: 3,
+                cooldown,
+            })
+            .is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod circuit_breaker {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_opens_after_consecutive_failures() {
+        let gateway = Arc::new(MockGateway::default());
+        gateway.failing.store(true, Ordering::SeqCst);
+        let resolver = mock_resolver(&gateway);
+        let offchain_gateway = offchain_gateway(2, 60.0);
+
+        for _ in 0..2 {
+            let result = offchain_gateway
+                .get_hint(&resolver, "alice.notion.stark")
+                .await;
+            assert!(matches!(
+                result,
+                Err(ResolutionError::ResolverUnreachable(_))
+            ));
+        }
+        assert_eq!(gateway.calls(), 2);
+
+        // open, the uri isn't queried anymore
+        let result = offchain_gateway
+            .get_hint(&resolver, "alice.notion.stark")
+            .await;
+        match result {
+            Err(ResolutionError::ResolverUnreachable(message)) => {
+                assert!(message.contains("skipped"))
+            }
+            _ => panic!("expected an unreachable resolver"),
+        }
+        assert_eq!(gateway.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_after_the_cooldown() {
+        let gateway = Arc::new(MockGateway::default());
+        gateway.failing.store(true, Ordering::SeqCst);
+        let resolver = mock_resolver(&gateway);
+        let offchain_gateway = offchain_gateway(2, 0.2);
+
+        for _ in 0..2 {
+            let _ = offchain_gateway
+                .get_hint(&resolver, "alice.notion.stark")
+                .await;
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        // a single request is let through, its failure opens the circuit again
+        let _ = offchain_gateway
+            .get_hint(&resolver, "alice.notion.stark")
+            .await;
+        assert_eq!(gateway.calls(), 3);
+        let _ = offchain_gateway
+            .get_hint(&resolver, "alice.notion.stark")
+            .await;
+        assert_eq!(gateway.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_closed_after_a_success() {
+        let gateway = Arc::new(MockGateway::default());
+        gateway.failing.store(true, Ordering::SeqCst);
+        gateway.max_validity.store(now() + 3600, Ordering::SeqCst);
+        let resolver = mock_resolver(&gateway);
+        let offchain_gateway = offchain_gateway(2, 0.2);
+
+        for _ in 0..2 {
+            let _ = offchain_gateway
+                .get_hint(&resolver, "alice.notion.stark")
+                .await;
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        gateway.failing.store(false, Ordering::SeqCst);
+        assert!(offchain_gateway
+            .get_hint(&resolver, "alice.notion.stark")
+            .await
+            .is_ok());
+
+        // failures are counted from zero again
+        gateway.failing.store(true, Ordering::SeqCst);
+        let _ = offchain_gateway
+            .get_hint(&resolver, "bob.notion.stark")
+            .await;
+        let _ = offchain_gateway
+            .get_hint(&resolver, "bob.notion.stark")
+            .await;
+        assert_eq!(gateway.calls(), 5);
+    }
+}
+
+#[cfg(test)]
+mod hint_cache {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_valid_hints_are_reused() {
+        let gateway = Arc::new(MockGateway::default());
+        gateway.max_validity.store(now() + 3600, Ordering::SeqCst);
+        let resolver = mock_resolver(&gateway);
+        let offchain_gateway = offchain_gateway(3, 60.0);
+
+        for _ in 0..3 {
+            let hint = offchain_gateway
+                .get_hint(&resolver, "alice.notion.stark")
+                .await
+                .unwrap();
+            assert_eq!(
+                hint.max_validity,
+                gateway.max_validity.load(Ordering::SeqCst)
+            );
+        }
+        assert_eq!(gateway.calls(), 1);
+
+        // hints of other domains aren't shared
+        let _ = offchain_gateway
+            .get_hint(&resolver, "bob.notion.stark")
+            .await;
+        assert_eq!(gateway.calls(), 2);
+
+        // a forgotten hint is fetched again
+        offchain_gateway.forget_hint("alice.notion.stark");
+        let _ = offchain_gateway
+            .get_hint(&resolver, "alice.notion.stark")
+            .await;
+        assert_eq!(gateway.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_expiring_hints_are_not_reused() {
+        let gateway = Arc::new(MockGateway::default());
+        // within the validity margin
+        gateway.max_validity.store(now() + 5, Ordering::SeqCst);
+        let resolver = mock_resolver(&gateway);
+        let offchain_gateway = offchain_gateway(3, 60.0);
+
+        for _ in 0..2 {
+            assert!(offchain_gateway
+                .get_hint(&resolver, "alice.notion.stark")
+                .await
+                .is_ok());
+        }
+        assert_eq!(gateway.calls(), 2);
+    }
+}
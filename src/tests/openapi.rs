@@ -0,0 +1,127 @@
+use super::app_state;
+use crate::{config::Config, openapi::ApiDoc, resolving::ResolverChain, ROUTE_REGISTRY};
+use axum::Router;
+use reqwest::{Method, StatusCode};
+use std::net::{SocketAddr, TcpListener};
+use utoipa::OpenApi;
+
+// registered routes which are not part of the API
+const UNDOCUMENTED_ROUTES: [&str; 2] = ["/", "/openapi.json"];
+
+// Serves every route of the registry merged like the server does, with their count
+async fn serve_registered_routes() -> (SocketAddr, usize) {
+    let state = app_state(Config::default(), ResolverChain::default()).await;
+    let routes = ROUTE_REGISTRY.lock().unwrap().clone();
+    let count = routes.len();
+    let router = routes.into_iter().fold(Router::new(), |acc, route| {
+        acc.merge(route.to_router(state.clone()))
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router.into_make_service())
+            .await
+            .unwrap();
+    });
+    (addr, count)
+}
+
+#[cfg(test)]
+mod openapi {
+    use super::*;
+
+    #[test]
+    fn test_spec_contains_resolution_endpoints() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for path in [
+            "/domain_to_addr",
+            "/addr_to_domain",
+            "/id_to_data",
+            "/domain_to_data",
+        ] {
+            assert!(spec["paths"][path]["get"].is_object(), "missing {}", path);
+        }
+        assert!(spec["paths"]["/domains_to_addrs"]["post"].is_object());
+    }
+
+    #[test]
+    fn test_admin_endpoints_require_an_api_key() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        for path in ["/crosschain/claims", "/get_altcoin_quote_history"] {
+            let parameters = spec["paths"][path]["get"]["parameters"].as_array().unwrap();
+            assert!(
+                parameters.iter().any(
+                    |parameter| parameter["name"] == "x-api-key" && parameter["in"] == "header"
+                ),
+                "{} has no api key",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_response_fields_are_documented() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemas = &spec["components"]["schemas"];
+        assert!(schemas["DomainToAddrData"]["properties"]["domain_expiry"].is_object());
+        assert!(schemas["AddrToDomainData"]["properties"]["domain_expiry"].is_object());
+        assert!(schemas["IdentityData"]["properties"]["domain"].is_object());
+        assert!(schemas["ErrorBody"]["properties"]["code"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_every_registered_route_is_documented() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let (addr, registered) = serve_registered_routes().await;
+        let client = reqwest::Client::new();
+
+        let mut documented = 0;
+        for (path, operations) in spec["paths"].as_object().unwrap() {
+            for method in operations.as_object().unwrap().keys() {
+                documented += 1;
+                // a served path answers a method it does not route with a 405, so the
+                // handlers are never run
+                let other_method = if method == "delete" {
+                    Method::PATCH
+                } else {
+                    Method::DELETE
+                };
+                let response = client
+                    .request(other_method, format!("http://{}{}", addr, path))
+                    .send()
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    response.status(),
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "{} {} is documented but not registered",
+                    method,
+                    path
+                );
+            }
+        }
+        for path in UNDOCUMENTED_ROUTES {
+            let response = client
+                .delete(format!("http://{}{}", addr, path))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{}",
+                path
+            );
+        }
+        // each route of the registry serves a single method, so the documented routes
+        // being registered means the other registered routes are undocumented
+        assert_eq!(
+            registered,
+            documented + UNDOCUMENTED_ROUTES.len(),
+            "some registered routes are missing from the OpenAPI document"
+        );
+    }
+}
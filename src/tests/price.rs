@@ -0,0 +1,151 @@
+use crate::{
+    config::AltcoinData,
+    price::{Price, PRICE_DECIMALS},
+    quotes::quote_in_range,
+};
+use ethers::types::{U256, U512};
+use proptest::prelude::*;
+use starknet::core::types::FieldElement;
+
+// decimals of the tokens of the config template
+fn configured_decimals() -> Vec<usize> {
+    let template: toml::Value = toml::from_str(include_str!("../../config.template.toml")).unwrap();
+    let altcoins = template.get("altcoins").unwrap().as_table().unwrap();
+    let decimals: Vec<usize> = altcoins
+        .values()
+        .filter_map(|altcoin| altcoin.get("decimals")?.as_integer())
+        .map(|decimals| decimals as usize)
+        .collect();
+    assert!(!decimals.is_empty());
+    decimals
+}
+
+fn altcoin(decimals: u32, min_price: u64, max_price: u64) -> AltcoinData {
+    AltcoinData {
+        address: FieldElement::ONE,
+        min_price,
+        max_price,
+        decimals,
+        max_quote_validity: 300,
+        auto_renew_contract: None,
+    }
+}
+
+#[cfg(test)]
+mod parsing {
+    use super::*;
+
+    #[test]
+    fn test_decimal_strings() {
+        let one = U256::exp10(PRICE_DECIMALS);
+        assert_eq!(Price::from_decimal_str("1").unwrap().raw(), one);
+        assert_eq!(
+            Price::from_decimal_str("0.00041").unwrap().raw(),
+            U256::from(41) * U256::exp10(PRICE_DECIMALS - 5)
+        );
+        assert_eq!(
+            Price::from_decimal_str("4.1e-4"),
+            Price::from_decimal_str("0.00041")
+        );
+        assert_eq!(
+            Price::from_decimal_str("1.5E3").unwrap().raw(),
+            U256::from(1500) * one
+        );
+        // beyond PRICE_DECIMALS
+        assert_eq!(Price::from_decimal_str("1e-37").unwrap(), Price::default());
+        assert!(Price::from_decimal_str("").is_none());
+        assert!(Price::from_decimal_str("-1").is_none());
+        assert!(Price::from_decimal_str("0x10").is_none());
+        assert!(Price::from_decimal_str("1e100").is_none());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Price::from_decimal_str("0.00041").unwrap().to_string(),
+            "0.00041"
+        );
+        assert_eq!(Price::from_decimal_str("12").unwrap().to_string(), "12");
+        assert_eq!(
+            Price::from_decimal_str("12.50").unwrap().to_string(),
+            "12.5"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_scientific_notation(mantissa in any::<u64>(), scale in 0..=PRICE_DECIMALS) {
+            let price = Price::from_decimal_str(&format!("{}e-{}", mantissa, scale)).unwrap();
+            prop_assert_eq!(
+                price.raw(),
+                U256::from(mantissa) * U256::exp10(PRICE_DECIMALS - scale)
+            );
+        }
+
+        #[test]
+        fn test_display_roundtrip(raw in any::<u128>()) {
+            let price = Price::from_raw(U256::from(raw));
+            prop_assert_eq!(Price::from_decimal_str(&price.to_string()), Some(price));
+        }
+    }
+}
+
+#[cfg(test)]
+mod quote {
+    use super::*;
+
+    #[test]
+    fn test_known_quotes() {
+        // 1 STRK = 0.0004 ETH, so 1 ETH = 2500 STRK
+        let price = Price::from_decimal_str("0.0004").unwrap();
+        assert_eq!(
+            price.quote_wei(18).unwrap(),
+            U256::from(2500) * U256::exp10(18)
+        );
+        assert_eq!(price.quote_wei(6).unwrap(), U256::from(2_500_000_000u64));
+        // 1 / 0.0003 = 3333.333..., rounded down to the last unit
+        let price = Price::from_decimal_str("0.0003").unwrap();
+        assert_eq!(
+            price.quote_wei(18).unwrap().to_string(),
+            "3333333333333333333333"
+        );
+        assert!(Price::default().quote_wei(18).is_none());
+    }
+
+    #[test]
+    fn test_quote_bounds() {
+        let price = Price::from_decimal_str("0.0004").unwrap();
+        assert!(quote_in_range(price, &altcoin(18, 500, 5000)).is_ok());
+        assert!(quote_in_range(price, &altcoin(18, 2500, 2500)).is_ok());
+        assert!(quote_in_range(price, &altcoin(18, 3000, 5000)).is_err());
+        assert!(quote_in_range(price, &altcoin(18, 500, 2000)).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_quote_is_exact(raw in 1..=u128::MAX, index in any::<prop::sample::Index>()) {
+            let decimals = configured_decimals();
+            let decimals = decimals[index.index(decimals.len())];
+            let price = Price::from_raw(U256::from(raw));
+            let quote = price.quote_wei(decimals).unwrap();
+            // quote is the largest amount with quote * price <= 10^decimals
+            let one_eth = U256::exp10(decimals).full_mul(U256::exp10(PRICE_DECIMALS));
+            prop_assert!(quote.full_mul(price.raw()) <= one_eth);
+            prop_assert!((U512::from(quote) + 1) * U512::from(price.raw()) > one_eth);
+        }
+
+        #[test]
+        fn test_quote_of_decimal_prices_is_exact(
+            mantissa in 1..=u64::MAX,
+            scale in 0..=18usize,
+            index in any::<prop::sample::Index>(),
+        ) {
+            let decimals = configured_decimals();
+            let decimals = decimals[index.index(decimals.len())];
+            // the quote of mantissa / 10^scale is 10^(decimals + scale) / mantissa
+            let price = Price::from_decimal_str(&format!("{}e-{}", mantissa, scale)).unwrap();
+            let expected = U256::exp10(decimals + scale) / U256::from(mantissa);
+            prop_assert_eq!(price.quote_wei(decimals).unwrap(), expected);
+        }
+    }
+}
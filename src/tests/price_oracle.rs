@@ -0,0 +1,160 @@
+use crate::{
+    config::seconds_setting,
+    price::Price,
+    price_oracle::{aggregate, median, parse_avnu_price, parse_pragma_response, SourcePrice},
+};
+use starknet::{core::types::FieldElement, macros::felt};
+use std::time::Duration;
+
+fn price(value: &str) -> Price {
+    Price::from_decimal_str(value).unwrap()
+}
+
+fn prices(values: &[&str]) -> Vec<Price> {
+    values.iter().map(|value| price(value)).collect()
+}
+
+fn sources(values: &[&str]) -> Vec<SourcePrice> {
+    prices(values)
+        .into_iter()
+        .enumerate()
+        .map(|(i, price)| SourcePrice {
+            source: format!("source{}", i),
+            price,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod median {
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&prices(&["3"])), Some(price("3")));
+        assert_eq!(median(&prices(&["3", "1", "2"])), Some(price("2")));
+        assert_eq!(median(&prices(&["4", "1", "2", "3"])), Some(price("2.5")));
+    }
+}
+
+#[cfg(test)]
+mod aggregate {
+    use super::*;
+
+    #[test]
+    fn test_agreeing_sources() {
+        let oracle_price = aggregate(sources(&["0.0010", "0.00101", "0.00099"]), 2, 5.0).unwrap();
+        assert_eq!(oracle_price.price, price("0.001"));
+        assert_eq!(oracle_price.sources.len(), 3);
+    }
+
+    #[test]
+    fn test_disagreeing_sources() {
+        let error = aggregate(sources(&["0.0010", "0.0010", "0.0012"]), 2, 5.0).unwrap_err();
+        assert!(error.contains("source2"));
+    }
+
+    #[test]
+    fn test_not_enough_sources() {
+        assert!(aggregate(sources(&["0.0010"]), 2, 5.0).is_err());
+        assert!(aggregate(sources(&[]), 0, 5.0).is_err());
+        assert!(aggregate(sources(&["0.0010"]), 1, 5.0).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod parse_avnu_price {
+    use super::*;
+
+    const RESPONSE: &str = r#"[
+        {"address": "0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d", "currentPrice": 0.0001234567890123456789012},
+        {"address": "0x53c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8", "currentPrice": 2934.123456789012345678901}
+    ]"#;
+
+    #[test]
+    fn test_price_keeps_every_digit() {
+        let strk = felt!("0x4718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d");
+        assert_eq!(
+            parse_avnu_price(RESPONSE, &strk).unwrap(),
+            Some(price("0.0001234567890123456789012"))
+        );
+        let usdc = felt!("0x53c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8");
+        assert_eq!(
+            parse_avnu_price(RESPONSE, &usdc).unwrap(),
+            Some(price("2934.123456789012345678901"))
+        );
+    }
+
+    #[test]
+    fn test_unlisted_token() {
+        assert_eq!(parse_avnu_price(RESPONSE, &felt!("0x1")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_price() {
+        let response = r#"[{"address": "0x1", "currentPrice": null}]"#;
+        assert!(parse_avnu_price(response, &felt!("0x1")).is_err());
+    }
+}
+
+#[cfg(test)]
+mod parse_pragma_response {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+    const MAX_AGE: Duration = Duration::from_secs(600);
+
+    // price, decimals, last_updated_timestamp, num_sources_aggregated
+    fn response(price: u128, last_updated: u64) -> Vec<FieldElement> {
+        vec![
+            FieldElement::from(price),
+            FieldElement::from(8u32),
+            FieldElement::from(last_updated),
+            FieldElement::from(5u32),
+        ]
+    }
+
+    #[test]
+    fn test_recent_price() {
+        assert_eq!(
+            parse_pragma_response(&response(123456789, NOW - 60), NOW, MAX_AGE).unwrap(),
+            price("1.23456789")
+        );
+        assert!(parse_pragma_response(&response(123456789, NOW - 600), NOW, MAX_AGE).is_ok());
+    }
+
+    #[test]
+    fn test_stale_price_is_refused() {
+        assert!(parse_pragma_response(&response(123456789, NOW - 601), NOW, MAX_AGE).is_err());
+        // never updated
+        assert!(parse_pragma_response(&response(123456789, 0), NOW, MAX_AGE).is_err());
+    }
+
+    #[test]
+    fn test_invalid_responses() {
+        assert!(parse_pragma_response(&response(0, NOW), NOW, MAX_AGE).is_err());
+        assert!(parse_pragma_response(&response(123456789, NOW)[..2], NOW, MAX_AGE).is_err());
+    }
+}
+
+#[cfg(test)]
+mod seconds_setting {
+    use super::*;
+
+    #[test]
+    fn test_valid_durations() {
+        assert_eq!(
+            seconds_setting("timeout", 2.5).unwrap(),
+            Duration::from_millis(2500)
+        );
+        assert_eq!(seconds_setting("timeout", 0.0).unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_invalid_durations_are_errors() {
+        assert!(seconds_setting("timeout", -1.0).is_err());
+        assert!(seconds_setting("timeout", f64::NAN).is_err());
+        assert!(seconds_setting("timeout", f64::INFINITY).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::endpoints::crosschain::ethereum::profile_records::{
+    abi_tokens, content_hash_tokens, decode_pubkey, encode_abi, encode_content_hash, pubkey_tokens,
+};
+use ethabi::Token;
+use ethers::types::U256;
+
+#[cfg(test)]
+mod encode_content_hash {
+    use super::*;
+
+    // ENSIP-7 example
+    const IPFS_CONTENT_HASH: &str =
+        "e3010170122029f2d17be6139079dc48696d1f582a8530eb9805b561eda517e22a892c7e3f1f";
+
+    #[test]
+    fn test_ipfs_cid_v0() {
+        let content_hash =
+            encode_content_hash("ipfs://QmRAQB6YaCyidP37UdDnjFY5vQuiBrcqdyoW1CuDgwxkD4").unwrap();
+        assert_eq!(hex::encode(content_hash), IPFS_CONTENT_HASH);
+    }
+
+    #[test]
+    fn test_ipfs_cid_v1() {
+        let content_hash = encode_content_hash(
+            "ipfs://bafybeibj6lixxzqtsb45ysdjnupvqkufgdvzqbnvmhw2kf7cfkesy7r7d4",
+        )
+        .unwrap();
+        assert_eq!(hex::encode(content_hash), IPFS_CONTENT_HASH);
+    }
+
+    #[test]
+    fn test_ipns_dnslink() {
+        let content_hash = encode_content_hash("ipns://app.starknet.id").unwrap();
+        assert_eq!(
+            hex::encode(content_hash),
+            format!("e5010172000e{}", hex::encode("app.starknet.id"))
+        );
+    }
+
+    #[test]
+    fn test_swarm() {
+        let hash = "d1de9994b4d039f6548d191eb26786769f580809256b4685ef316805265ea162";
+        let content_hash = encode_content_hash(&format!("bzz://{}", hash)).unwrap();
+        assert_eq!(hex::encode(content_hash), format!("e40101fa011b20{}", hash));
+    }
+
+    #[test]
+    fn test_unsupported_uri() {
+        assert!(encode_content_hash("https://starknet.id").is_err());
+        assert!(encode_content_hash("ipfs://not-a-cid").is_err());
+    }
+}
+
+#[cfg(test)]
+mod decode_pubkey {
+    use super::*;
+
+    #[test]
+    fn test_prefixed_and_raw_keys() {
+        let x = "11".repeat(32);
+        let y = "22".repeat(32);
+        for value in [format!("0x04{}{}", x, y), format!("{}{}", x, y)] {
+            let (decoded_x, decoded_y) = decode_pubkey(&value).unwrap();
+            assert_eq!(hex::encode(decoded_x), x);
+            assert_eq!(hex::encode(decoded_y), y);
+        }
+        assert!(decode_pubkey("0x1234").is_err());
+    }
+}
+
+#[cfg(test)]
+mod encode_abi {
+    use super::*;
+
+    #[test]
+    fn test_requested_content_type() {
+        let abi = r#"[{"type":"function","name":"transfer"}]"#;
+        assert_eq!(
+            encode_abi(abi, U256::from(1 | 8)),
+            (U256::from(1), abi.as_bytes().to_vec())
+        );
+        assert_eq!(
+            encode_abi("https://starknet.id/abi.json", U256::from(8)),
+            (U256::from(8), b"https://starknet.id/abi.json".to_vec())
+        );
+        assert_eq!(encode_abi(abi, U256::from(8)), (U256::zero(), Vec::new()));
+    }
+}
+
+#[cfg(test)]
+mod record_tokens {
+    use super::*;
+
+    const PUBKEY: &str = "0x04836b35a026743e823a90a0ee3b91bf615c6a757e2b60b9e1dc1826fd0dd16106f7bc1e8179f665015f43c6c81f39062fc2086ed849625c06e04697698b21855e";
+
+    #[test]
+    fn test_unset_records_are_empty() {
+        assert_eq!(content_hash_tokens(None), vec![Token::Bytes(Vec::new())]);
+        assert_eq!(
+            pubkey_tokens(None),
+            vec![
+                Token::FixedBytes(vec![0; 32]),
+                Token::FixedBytes(vec![0; 32])
+            ]
+        );
+        assert_eq!(
+            abi_tokens(None, U256::from(1)),
+            vec![Token::Uint(U256::zero()), Token::Bytes(Vec::new())]
+        );
+    }
+
+    #[test]
+    fn test_malformed_records_are_empty() {
+        assert_eq!(
+            content_hash_tokens(Some("https://starknet.id")),
+            content_hash_tokens(None)
+        );
+        assert_eq!(pubkey_tokens(Some("0x1234")), pubkey_tokens(None));
+    }
+
+    #[test]
+    fn test_stored_records() {
+        let (x, y) = decode_pubkey(PUBKEY).unwrap();
+        assert_eq!(
+            pubkey_tokens(Some(PUBKEY)),
+            vec![Token::FixedBytes(x), Token::FixedBytes(y)]
+        );
+        let abi = r#"[{"type":"function","name":"transfer"}]"#;
+        assert_eq!(
+            abi_tokens(Some(abi), U256::from(1)),
+            vec![
+                Token::Uint(U256::from(1)),
+                Token::Bytes(abi.as_bytes().to_vec())
+            ]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    endpoints::get_altcoin_quote_history::{aggregated_range, candles_pipeline},
+    errors::ApiError,
+};
+use mongodb::bson::{doc, Document};
+
+const NOW: i64 = 1_700_000_000;
+const DAY: i64 = 24 * 3600;
+
+fn stage<'a>(pipeline: &'a [Document], name: &str) -> &'a Document {
+    pipeline
+        .iter()
+        .find_map(|stage| stage.get_document(name).ok())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod aggregated_range {
+    use super::*;
+
+    #[test]
+    fn test_defaults_to_the_last_days() {
+        assert_eq!(
+            aggregated_range(None, None, NOW).unwrap(),
+            (NOW - 31 * DAY, NOW)
+        );
+        assert_eq!(
+            aggregated_range(None, Some(NOW - DAY), NOW).unwrap(),
+            (NOW - 32 * DAY, NOW - DAY)
+        );
+    }
+
+    #[test]
+    fn test_given_range() {
+        assert_eq!(
+            aggregated_range(Some(NOW - DAY), Some(NOW), NOW).unwrap(),
+            (NOW - DAY, NOW)
+        );
+    }
+
+    #[test]
+    fn test_long_ranges_are_refused() {
+        assert!(matches!(
+            aggregated_range(Some(NOW - 32 * DAY), Some(NOW), NOW),
+            Err(ApiError::Validation(_))
+        ));
+        assert!(matches!(
+            aggregated_range(Some(0), None, NOW),
+            Err(ApiError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_reversed_range() {
+        assert!(matches!(
+            aggregated_range(Some(NOW), Some(NOW - DAY), NOW),
+            Err(ApiError::Validation(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod candles_pipeline {
+    use super::*;
+
+    #[test]
+    fn test_quotes_are_bucketed_by_token_and_interval() {
+        let pipeline = candles_pipeline(doc! { "token": "0x1" }, 3600);
+        assert_eq!(stage(&pipeline, "$match"), &doc! { "token": "0x1" });
+        let group = stage(&pipeline, "$group");
+        assert_eq!(
+            group.get_document("_id").unwrap(),
+            &doc! {
+                "token": "$token",
+                "start": { "$subtract": ["$created_at", { "$mod": ["$created_at", 3600_i64] }] },
+            }
+        );
+    }
+
+    #[test]
+    fn test_open_and_close_follow_the_signing_order() {
+        let pipeline = candles_pipeline(doc! {}, 3600);
+        // the quotes are sorted before being grouped
+        assert_eq!(pipeline[1], doc! { "$sort": { "created_at": 1 } });
+        let group = stage(&pipeline, "$group");
+        assert_eq!(
+            group.get_document("open").unwrap(),
+            &doc! { "$first": "$quote" }
+        );
+        assert_eq!(
+            group.get_document("close").unwrap(),
+            &doc! { "$last": "$quote" }
+        );
+    }
+
+    #[test]
+    fn test_quotes_are_compared_as_decimals() {
+        let pipeline = candles_pipeline(doc! {}, 3600);
+        let group = stage(&pipeline, "$group");
+        for bound in ["high", "low"] {
+            let accumulator = group.get_document(bound).unwrap();
+            let value = accumulator.values().next().unwrap().as_document().unwrap();
+            assert_eq!(
+                value.get_document("$convert").unwrap().get_str("to").ok(),
+                Some("decimal")
+            );
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    config::{AltcoinData, PriceOracle},
+    errors::ApiError,
+    price::Price,
+    price_oracle::{OraclePrice, SourcePrice},
+    quotes::{sign_quote, QuoteCache, StoredQuote},
+};
+use futures::future::join_all;
+use starknet::{core::types::FieldElement, macros::felt};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+const PRIVATE_KEY: FieldElement = felt!("0x123");
+const NOW: i64 = 1_700_000_000;
+
+fn strk() -> AltcoinData {
+    AltcoinData {
+        address: felt!("0x04718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d"),
+        min_price: 500,
+        max_price: 5000,
+        decimals: 18,
+        max_quote_validity: 300,
+        auto_renew_contract: None,
+    }
+}
+
+fn oracle_price(value: &str) -> OraclePrice {
+    let price = Price::from_decimal_str(value).unwrap();
+    OraclePrice {
+        price,
+        sources: vec![SourcePrice {
+            source: "avnu".to_string(),
+            price,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod sign_quote {
+    use super::*;
+
+    #[test]
+    fn test_signed_quote() {
+        let quote = sign_quote(&PRIVATE_KEY, &strk(), oracle_price("0.0004"), NOW).unwrap();
+        assert_eq!(quote.token, strk().address);
+        assert_eq!(quote.quote.to_string(), "2500000000000000000000");
+        assert_eq!(quote.max_quote_validity, NOW + 300);
+    }
+
+    #[test]
+    fn test_stored_with_the_signing_time() {
+        let quote = sign_quote(&PRIVATE_KEY, &strk(), oracle_price("0.0004"), NOW).unwrap();
+        let stored = StoredQuote::from(&quote);
+        assert_eq!(stored.created_at, NOW);
+        assert_eq!(stored.max_quote_validity, NOW + 300);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert!(sign_quote(&PRIVATE_KEY, &strk(), oracle_price("0.01"), NOW).is_err());
+    }
+}
+
+fn cache() -> QuoteCache {
+    QuoteCache::new(&PriceOracle {
+        requote_threshold: 0.5,
+        requote_margin: 60,
+        ..PriceOracle::default()
+    })
+    .unwrap()
+}
+
+#[cfg(test)]
+mod quote_cache {
+    use super::*;
+
+    #[test]
+    fn test_reused_while_the_price_is_stable() {
+        let cache = cache();
+        let quote = sign_quote(&PRIVATE_KEY, &strk(), oracle_price("0.0004"), NOW).unwrap();
+        cache.store_quote(quote);
+        let price = Price::from_decimal_str("0.000401").unwrap();
+        assert!(cache
+            .reusable_quote(&strk().address, price, NOW + 10)
+            .is_some());
+    }
+
+    #[test]
+    fn test_requoted_when_the_price_moves() {
+        let cache = cache();
+        let quote = sign_quote(&PRIVATE_KEY, &strk(), oracle_price("0.0004"), NOW).unwrap();
+        cache.store_quote(quote);
+        let price = Price::from_decimal_str("0.000403").unwrap();
+        assert!(cache
+            .reusable_quote(&strk().address, price, NOW + 10)
+            .is_none());
+    }
+
+    #[test]
+    fn test_requoted_close_to_expiry() {
+        let cache = cache();
+        let quote = sign_quote(&PRIVATE_KEY, &strk(), oracle_price("0.0004"), NOW).unwrap();
+        cache.store_quote(quote);
+        let price = Price::from_decimal_str("0.0004").unwrap();
+        assert!(cache
+            .reusable_quote(&strk().address, price, NOW + 239)
+            .is_some());
+        assert!(cache
+            .reusable_quote(&strk().address, price, NOW + 240)
+            .is_none());
+        assert!(cache.reusable_quote(&felt!("0x1"), price, NOW).is_none());
+    }
+}
+
+#[cfg(test)]
+mod single_flight {
+    use super::*;
+
+    // slow price source counting its calls
+    async fn fetch(calls: &AtomicUsize, value: &str) -> Result<OraclePrice, ApiError> {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(oracle_price(value))
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_a_price_fetch() {
+        let cache = cache();
+        let calls = AtomicUsize::new(0);
+        let token = strk().address;
+        let prices =
+            join_all((0..10).map(|_| cache.price_or_fetch(&token, fetch(&calls, "0.0004")))).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(prices
+            .into_iter()
+            .all(|price| price.unwrap().price == Price::from_decimal_str("0.0004").unwrap()));
+
+        // other tokens are fetched separately
+        cache
+            .price_or_fetch(&felt!("0x1"), fetch(&calls, "0.0004"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetches_are_not_cached() {
+        let cache = cache();
+        let token = strk().address;
+        let result = cache
+            .price_or_fetch(&token, async {
+                Err(ApiError::Upstream("no price".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        let calls = AtomicUsize::new(0);
+        cache
+            .price_or_fetch(&token, fetch(&calls, "0.0004"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_requests_wait_for_the_background_refresh() {
+        let cache = cache();
+        let calls = AtomicUsize::new(0);
+        let token = strk().address;
+        let (refreshed, price) = futures::join!(
+            cache.refresh_price(&token, fetch(&calls, "0.0005")),
+            cache.price_or_fetch(&token, fetch(&calls, "0.0004")),
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(refreshed.unwrap().price, price.unwrap().price);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_a_signature() {
+        let cache = cache();
+        let signatures = AtomicUsize::new(0);
+        let token = strk().address;
+        let price = Price::from_decimal_str("0.0004").unwrap();
+        let quotes = join_all((0..10).map(|_| {
+            cache.quote_or_sign(&token, price, NOW, async {
+                signatures.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                sign_quote(&PRIVATE_KEY, &strk(), oracle_price("0.0004"), NOW)
+            })
+        }))
+        .await;
+        assert_eq!(signatures.load(Ordering::SeqCst), 1);
+        let quotes: Vec<_> = quotes.into_iter().map(|quote| quote.unwrap()).collect();
+        assert!(quotes.iter().all(|quote| quote.r == quotes[0].r));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    config::{Config, EvmRecordVerifier},
+    endpoints::crosschain::ethereum::record_handlers::{
+        extract_json_path, RecordHandler, RecordHandlers, StaticHandler,
+    },
+};
+use anyhow::Result;
+use axum::async_trait;
+use reqwest::Client;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+fn verifier(handler: &str) -> EvmRecordVerifier {
+    EvmRecordVerifier {
+        verifier_contracts: vec![FieldElement::ONE],
+        field: "social".to_string(),
+        handler: handler.to_string(),
+        timeout: None,
+        cache_ttl: None,
+        api_url: None,
+        token: None,
+        url: None,
+        json_path: None,
+        headers: HashMap::new(),
+    }
+}
+
+fn config(record: &str, verifier: EvmRecordVerifier) -> Config {
+    let mut conf = Config::default();
+    conf.evm_records_verifiers
+        .insert(record.to_string(), verifier);
+    conf
+}
+
+// counts its calls and answers after a delay
+struct CountingHandler {
+    calls: Arc<AtomicUsize>,
+    delay: Duration,
+}
+
+#[async_trait]
+impl RecordHandler for CountingHandler {
+    async fn handle(&self, _client: &Client, social_id: FieldElement) -> Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        Ok(format!("user{}", social_id))
+    }
+}
+
+fn counting_handlers(
+    delay: Duration,
+    timeout: Duration,
+    cache_ttl: Duration,
+) -> (RecordHandlers, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut record_handlers = RecordHandlers::new(&Config::default()).unwrap();
+    record_handlers.register(
+        "com.example",
+        Box::new(CountingHandler {
+            calls: calls.clone(),
+            delay,
+        }),
+        timeout,
+        cache_ttl,
+    );
+    (record_handlers, calls)
+}
+
+#[cfg(test)]
+mod extract_json_path {
+    use super::*;
+
+    #[test]
+    fn test_nested_keys() {
+        let json = json!({ "data": { "user_result": { "result": { "legacy": { "screen_name": "starknet_id" } } } } });
+        assert_eq!(
+            extract_json_path(&json, "data.user_result.result.legacy.screen_name"),
+            Some("starknet_id".to_string())
+        );
+    }
+
+    #[test]
+    fn test_array_indexes_and_numbers() {
+        let json = json!({ "users": [{ "fid": 3 }, { "fid": 42, "name": "dwr" }] });
+        assert_eq!(
+            extract_json_path(&json, "users.1.name"),
+            Some("dwr".to_string())
+        );
+        assert_eq!(
+            extract_json_path(&json, "users.0.fid"),
+            Some("3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let json = json!({ "result": { "username": null, "ids": [1] } });
+        assert_eq!(extract_json_path(&json, "result.username"), None);
+        assert_eq!(extract_json_path(&json, "result.name"), None);
+        assert_eq!(extract_json_path(&json, "result.ids.3"), None);
+        assert_eq!(extract_json_path(&json, "result.ids.first"), None);
+        // objects are not record values
+        assert_eq!(extract_json_path(&json, "result"), None);
+    }
+}
+
+#[cfg(test)]
+mod registry {
+    use super::*;
+
+    #[test]
+    fn test_builds_configured_handlers() {
+        for handler in [
+            "Static",
+            "GetDiscordName",
+            "GetGithubName",
+            "GetTwitterName",
+            "GetFarcasterName",
+        ] {
+            assert!(RecordHandlers::new(&config("com.example", verifier(handler))).is_ok());
+        }
+
+        let mut telegram = verifier("GetTelegramName");
+        telegram.token = Some("123:abc".to_string());
+        assert!(RecordHandlers::new(&config("org.telegram", telegram)).is_ok());
+
+        let mut http = verifier("HttpJsonPath");
+        http.url = Some("https://api.example.com/users/{id}".to_string());
+        http.json_path = Some("data.username".to_string());
+        assert!(RecordHandlers::new(&config("com.example", http)).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_invalid_handlers() {
+        assert!(RecordHandlers::new(&config("com.example", verifier("GetMyspaceName"))).is_err());
+        // missing settings
+        assert!(RecordHandlers::new(&config("org.telegram", verifier("GetTelegramName"))).is_err());
+        let mut http = verifier("HttpJsonPath");
+        http.url = Some("https://api.example.com/users/{id}".to_string());
+        assert!(RecordHandlers::new(&config("com.example", http)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_errors_do_not_leak_the_token() {
+        let mut telegram = verifier("GetTelegramName");
+        telegram.token = Some("123:secret".to_string());
+        // nothing listens on the discard port
+        telegram.api_url = Some("http://127.0.0.1:9".to_string());
+        let record_handlers = RecordHandlers::new(&config("org.telegram", telegram)).unwrap();
+
+        let error = record_handlers
+            .execute("org.telegram", FieldElement::ONE)
+            .await
+            .unwrap_err();
+        assert!(!format!("{:?}", error).contains("secret"));
+        assert!(!format!("{:#}", error).contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_static_handler() {
+        let record_handlers =
+            RecordHandlers::new(&config("com.example", verifier("Static"))).unwrap();
+        let name = record_handlers
+            .execute("com.example", FieldElement::from(1234_u64))
+            .await
+            .unwrap();
+        assert_eq!(name, "1234");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_record() {
+        let mut record_handlers = RecordHandlers::new(&Config::default()).unwrap();
+        record_handlers.register(
+            "com.example",
+            Box::new(StaticHandler),
+            Duration::from_secs(1),
+            Duration::ZERO,
+        );
+        assert!(record_handlers
+            .execute("com.other", FieldElement::ONE)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_names_are_cached() {
+        let (record_handlers, calls) = counting_handlers(
+            Duration::ZERO,
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+        );
+        for _ in 0..3 {
+            let name = record_handlers
+                .execute("com.example", FieldElement::from(7_u64))
+                .await
+                .unwrap();
+            assert_eq!(name, "user7");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            record_handlers.cached_name("com.example", &FieldElement::from(7_u64)),
+            Some("user7".to_string())
+        );
+
+        // other ids are fetched
+        record_handlers
+            .execute("com.example", FieldElement::from(8_u64))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_zero_cache_ttl() {
+        let (record_handlers, calls) =
+            counting_handlers(Duration::ZERO, Duration::from_secs(1), Duration::ZERO);
+        for _ in 0..2 {
+            record_handlers
+                .execute("com.example", FieldElement::from(7_u64))
+                .await
+                .unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let (record_handlers, _) = counting_handlers(
+            Duration::from_secs(5),
+            Duration::from_millis(50),
+            Duration::from_secs(60),
+        );
+        let result = record_handlers
+            .execute("com.example", FieldElement::from(7_u64))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("timed out"));
+    }
+}
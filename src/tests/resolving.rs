@@ -0,0 +1,357 @@
+use super::app_state;
+use crate::{
+    config::{Config, OffchainResolver},
+    models::{AppState, OffchainResolverHint},
+    resolving::{
+        encode_domain, hint_message_hash, register_offchain_resolver, verify_hint, Resolution,
+        ResolutionError, ResolutionResult, Resolver, ResolverChain,
+    },
+};
+use axum::async_trait;
+use mongodb::bson::{doc, Document};
+use starknet::{
+    core::{
+        crypto::{ecdsa_sign, pedersen_hash},
+        types::FieldElement,
+    },
+    macros::{felt, short_string},
+};
+use starknet_crypto::get_public_key;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+const RESOLVER_KEY: FieldElement = felt!("0x1234567890abcdef");
+const TARGET: FieldElement = felt!("0x0123");
+// far in the future
+const MAX_VALIDITY: u64 = 4_000_000_000;
+
+fn resolver(public_key: Option<FieldElement>) -> OffchainResolver {
+    OffchainResolver {
+        resolver_address: "0x153be68cf8fc71138610811dd2b4fa481eb99f3eedcb3fce7369569055be275"
+            .to_string(),
+        public_key,
+        uri: vec![],
+    }
+}
+
+// Hint for a domain signed with a private key, as the gateways do
+fn signed_hint(
+    private_key: &FieldElement,
+    domain: &str,
+    address: FieldElement,
+    max_validity: u64,
+) -> OffchainResolverHint {
+    let mut hint = OffchainResolverHint {
+        address,
+        r: FieldElement::ZERO,
+        s: FieldElement::ZERO,
+        max_validity,
+    };
+    let message_hash = hint_message_hash(&hint, &encode_domain(domain).unwrap());
+    let signature = ecdsa_sign(private_key, &message_hash).unwrap();
+    hint.r = signature.r;
+    hint.s = signature.s;
+    hint
+}
+
+/// Resolver answering the domains of its table and recording the domains it is asked
+#[derive(Clone, Default)]
+struct MockResolver {
+    results: HashMap<String, ResolutionResult>,
+    calls: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+impl MockResolver {
+    fn new(results: Vec<(&str, ResolutionResult)>) -> Self {
+        MockResolver {
+            results: results
+                .into_iter()
+                .map(|(domain, result)| (domain.to_string(), result))
+                .collect(),
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Resolver for MockResolver {
+    async fn resolve(
+        &self,
+        _state: &Arc<AppState>,
+        domains: &[String],
+        _block: Option<u64>,
+    ) -> HashMap<String, ResolutionResult> {
+        self.calls.lock().unwrap().push(domains.to_vec());
+        domains
+            .iter()
+            .filter_map(|domain| {
+                self.results
+                    .get(domain)
+                    .map(|result| (domain.clone(), result.clone()))
+            })
+            .collect()
+    }
+}
+
+fn resolved(addr: &str) -> ResolutionResult {
+    Ok(Resolution {
+        addr: addr.to_string(),
+        domain_expiry: None,
+        valid_until: None,
+    })
+}
+
+fn domains(domains: &[&str]) -> Vec<String> {
+    domains.iter().map(|domain| domain.to_string()).collect()
+}
+
+#[cfg(test)]
+mod hint_message_hash {
+    use super::*;
+
+    #[test]
+    fn test_message_format() {
+        let hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, MAX_VALIDITY);
+        let encoded_domain = encode_domain("alice.notion.stark").unwrap();
+        // hash_domain of the subdomain only, the root domain holds the resolver
+        let domain_hash = pedersen_hash(&encoded_domain[0], &FieldElement::ZERO);
+        let expected = pedersen_hash(
+            &pedersen_hash(
+                &pedersen_hash(
+                    &pedersen_hash(
+                        &short_string!("ccip_demo resolving"),
+                        &FieldElement::from(MAX_VALIDITY),
+                    ),
+                    &domain_hash,
+                ),
+                &short_string!("starknet"),
+            ),
+            &TARGET,
+        );
+        assert_eq!(hint_message_hash(&hint, &encoded_domain), expected);
+    }
+
+    #[test]
+    fn test_root_domain_is_not_hashed() {
+        let hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, MAX_VALIDITY);
+        assert_eq!(
+            hint_message_hash(&hint, &encode_domain("alice.notion.stark").unwrap()),
+            hint_message_hash(&hint, &encode_domain("alice.other.stark").unwrap())
+        );
+        assert_ne!(
+            hint_message_hash(&hint, &encode_domain("alice.notion.stark").unwrap()),
+            hint_message_hash(&hint, &encode_domain("bob.notion.stark").unwrap())
+        );
+    }
+}
+
+#[cfg(test)]
+mod verify_hint {
+    use super::*;
+
+    #[test]
+    fn test_valid_hint() {
+        let hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, MAX_VALIDITY);
+        let resolver = resolver(Some(get_public_key(&RESOLVER_KEY)));
+        let encoded_domain = encode_domain("alice.notion.stark").unwrap();
+        assert!(verify_hint(&hint, &resolver, &encoded_domain).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let hint = signed_hint(&felt!("0x42"), "alice.notion.stark", TARGET, MAX_VALIDITY);
+        let resolver = resolver(Some(get_public_key(&RESOLVER_KEY)));
+        let encoded_domain = encode_domain("alice.notion.stark").unwrap();
+        assert!(matches!(
+            verify_hint(&hint, &resolver, &encoded_domain),
+            Err(ResolutionError::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn test_tampered_hint() {
+        let mut hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, MAX_VALIDITY);
+        hint.address = felt!("0x0456");
+        let resolver = resolver(Some(get_public_key(&RESOLVER_KEY)));
+        let encoded_domain = encode_domain("alice.notion.stark").unwrap();
+        assert!(matches!(
+            verify_hint(&hint, &resolver, &encoded_domain),
+            Err(ResolutionError::BadSignature)
+        ));
+        // signed for another domain
+        let encoded_domain = encode_domain("bob.notion.stark").unwrap();
+        let hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, MAX_VALIDITY);
+        assert!(matches!(
+            verify_hint(&hint, &resolver, &encoded_domain),
+            Err(ResolutionError::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn test_resolver_without_key_is_rejected() {
+        let hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, MAX_VALIDITY);
+        let encoded_domain = encode_domain("alice.notion.stark").unwrap();
+        assert!(matches!(
+            verify_hint(&hint, &resolver(None), &encoded_domain),
+            Err(ResolutionError::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn test_expired_hint() {
+        let hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, 1_600_000_000);
+        let resolver = resolver(Some(get_public_key(&RESOLVER_KEY)));
+        let encoded_domain = encode_domain("alice.notion.stark").unwrap();
+        assert!(matches!(
+            verify_hint(&hint, &resolver, &encoded_domain),
+            Err(ResolutionError::ExpiredHint)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod resolver_chain {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_first_resolver_wins() {
+        let first = MockResolver::new(vec![("ben.stark", resolved("0x1"))]);
+        let second = MockResolver::new(vec![("ben.stark", resolved("0x2"))]);
+        let chain = ResolverChain::new(vec![Box::new(first.clone()), Box::new(second.clone())]);
+        let state = app_state(Config::default(), ResolverChain::new(vec![])).await;
+
+        let resolution = chain.resolve(&state, "ben.stark", None).await.unwrap();
+
+        assert_eq!(resolution.addr, "0x1");
+        assert_eq!(first.calls(), vec![domains(&["ben.stark"])]);
+        // the domain is never handed over once resolved
+        assert!(second.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_domains_fall_through() {
+        let first = MockResolver::new(vec![("ben.stark", resolved("0x1"))]);
+        let second = MockResolver::new(vec![("alice.stark", resolved("0x2"))]);
+        let chain = ResolverChain::new(vec![Box::new(first.clone()), Box::new(second.clone())]);
+        let state = app_state(Config::default(), ResolverChain::new(vec![])).await;
+
+        let results = chain
+            .resolve_many(&state, &domains(&["ben.stark", "alice.stark"]), None)
+            .await;
+
+        assert_eq!(results[0].as_ref().unwrap().addr, "0x1");
+        assert_eq!(results[1].as_ref().unwrap().addr, "0x2");
+        assert_eq!(first.calls(), vec![domains(&["alice.stark", "ben.stark"])]);
+        assert_eq!(second.calls(), vec![domains(&["alice.stark"])]);
+    }
+
+    #[tokio::test]
+    async fn test_errors_stop_the_chain() {
+        let first = MockResolver::new(vec![(
+            "ben.stark",
+            Err(ResolutionError::ResolverUnreachable("down".to_string())),
+        )]);
+        let second = MockResolver::new(vec![("ben.stark", resolved("0x2"))]);
+        let chain = ResolverChain::new(vec![Box::new(first), Box::new(second.clone())]);
+        let state = app_state(Config::default(), ResolverChain::new(vec![])).await;
+
+        let result = chain.resolve(&state, "ben.stark", None).await;
+
+        assert!(matches!(
+            result,
+            Err(ResolutionError::ResolverUnreachable(_))
+        ));
+        assert!(second.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_domains_are_not_found() {
+        let chain = ResolverChain::new(vec![Box::new(MockResolver::default())]);
+        let state = app_state(Config::default(), ResolverChain::new(vec![])).await;
+
+        let results = chain
+            .resolve_many(&state, &domains(&["ben.stark", "ben.stark"]), None)
+            .await;
+
+        // duplicates are resolved once but answered in the input order
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result, Err(ResolutionError::NotFound(_)))));
+    }
+}
+
+#[cfg(test)]
+mod register_offchain_resolver {
+    use super::*;
+
+    // document of the update_offchain_resolvers pipeline
+    fn resolver_doc(uri: &str, public_key: Option<FieldElement>) -> Document {
+        let mut doc = doc! {
+            "resolver_contract": "0x153be68cf8fc71138610811dd2b4fa481eb99f3eedcb3fce7369569055be275",
+            "uri": uri,
+            "active": true,
+        };
+        if let Some(public_key) = public_key {
+            doc.insert("public_key", format!("{:#x}", public_key));
+        }
+        doc
+    }
+
+    fn verify(resolvers: &HashMap<String, OffchainResolver>) -> Result<(), ResolutionError> {
+        let hint = signed_hint(&RESOLVER_KEY, "alice.notion.stark", TARGET, MAX_VALIDITY);
+        verify_hint(
+            &hint,
+            &resolvers["notion.stark"],
+            &encode_domain("alice.notion.stark").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_new_resolver_verifies_its_hints() {
+        let mut resolvers = HashMap::new();
+        register_offchain_resolver(
+            &mut resolvers,
+            "notion.stark",
+            &resolver_doc(
+                "https://gateway.notion.so/",
+                Some(get_public_key(&RESOLVER_KEY)),
+            ),
+        );
+        assert!(verify(&resolvers).is_ok());
+    }
+
+    #[test]
+    fn test_refresh_sets_the_key_of_an_existing_resolver() {
+        let mut resolvers = HashMap::new();
+        register_offchain_resolver(
+            &mut resolvers,
+            "notion.stark",
+            &resolver_doc("https://gateway.notion.so/", None),
+        );
+        assert!(matches!(
+            verify(&resolvers),
+            Err(ResolutionError::BadSignature)
+        ));
+
+        register_offchain_resolver(
+            &mut resolvers,
+            "notion.stark",
+            &resolver_doc(
+                "https://gateway.notion.so/",
+                Some(get_public_key(&RESOLVER_KEY)),
+            ),
+        );
+        assert!(verify(&resolvers).is_ok());
+        assert_eq!(
+            resolvers["notion.stark"].uri,
+            vec!["https://gateway.notion.so/".to_string()]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use crate::endpoints::crosschain::ethereum::reverse::parse_reverse_name;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod parse_reverse_name {
+    use super::*;
+
+    const ADDRESS: &str = "d8da6bf26964af9d7eed9e03e53415d37aa96045";
+
+    fn address() -> FieldElement {
+        FieldElement::from_hex_be(ADDRESS).unwrap()
+    }
+
+    #[test]
+    fn test_ethereum_reverse_name() {
+        let name = format!("{}.addr.reverse", ADDRESS);
+        assert_eq!(parse_reverse_name(&name), Some((address(), 60)));
+    }
+
+    #[test]
+    fn test_evm_chain_reverse_name() {
+        // optimism, coin type 2147483658
+        let name = format!("{}.8000000a.reverse", ADDRESS);
+        assert_eq!(parse_reverse_name(&name), Some((address(), 2147483658)));
+    }
+
+    #[test]
+    fn test_checksummed_address() {
+        let name = "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045.addr.reverse";
+        assert_eq!(parse_reverse_name(name), Some((address(), 60)));
+    }
+
+    #[test]
+    fn test_forward_names_are_not_reverse() {
+        assert_eq!(parse_reverse_name("fricoben.snid.eth"), None);
+        assert_eq!(parse_reverse_name("addr.reverse"), None);
+        assert_eq!(
+            parse_reverse_name(&format!("sub.{}.addr.reverse", ADDRESS)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalid_reverse_names() {
+        // not an evm address
+        assert_eq!(parse_reverse_name("d8da6bf2.addr.reverse"), None);
+        assert_eq!(
+            parse_reverse_name(&format!("{}zz.addr.reverse", &ADDRESS[..38])),
+            None
+        );
+        // not a coin type
+        assert_eq!(
+            parse_reverse_name(&format!("{}.optimism.reverse", ADDRESS)),
+            None
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use crate::endpoints::crosschain::solana::sns::{get_domain_key, parse_owner, parse_sol_domain};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod parse_sol_domain {
+    use super::*;
+
+    #[test]
+    fn test_valid_domains() {
+        assert_eq!(parse_sol_domain("bonfida.sol").unwrap(), "bonfida");
+        assert_eq!(parse_sol_domain("bonfida").unwrap(), "bonfida");
+    }
+
+    #[test]
+    fn test_invalid_domains() {
+        assert!(parse_sol_domain(".sol").is_err());
+        assert!(parse_sol_domain("").is_err());
+        assert!(parse_sol_domain("dex.bonfida.sol").is_err());
+    }
+}
+
+#[cfg(test)]
+mod name_registry {
+    use super::*;
+
+    #[test]
+    fn test_domain_key() {
+        // bonfida.sol, as given by the SNS SDK
+        assert_eq!(
+            get_domain_key("bonfida"),
+            Pubkey::from_str("Crf8hzfthWGbGbLTVCiqRqV5MVnbpHB1L9KQMd6gsinb").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_owner() {
+        let owner = Pubkey::from_str("HKKp49qGWXd639QsuH7JiLijfVW5UtCVY4s1n2HANwEA").unwrap();
+        // parent, owner, class then the record data
+        let data = [
+            [1u8; 32].to_vec(),
+            owner.to_bytes().to_vec(),
+            [0u8; 32].to_vec(),
+            vec![7; 10],
+        ]
+        .concat();
+        assert_eq!(parse_owner(&data), Some(owner));
+    }
+
+    #[test]
+    fn test_parse_owner_short_data() {
+        assert_eq!(parse_owner(&[0u8; 63]), None);
+        assert_eq!(parse_owner(&[]), None);
+    }
+}
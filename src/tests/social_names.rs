@@ -0,0 +1,108 @@
+use crate::endpoints::crosschain::ethereum::social_names::{
+    expired_filter, fetched_or_stale, name_update, parse_stored_name, social_names_indexes,
+};
+use anyhow::anyhow;
+use mongodb::bson::{doc, DateTime as BsonDateTime, Document};
+
+const NOW: i64 = 1_700_000_000_000;
+
+fn date(millis: i64) -> BsonDateTime {
+    BsonDateTime::from_millis(millis)
+}
+
+// row as stored by an upsert of this update
+fn stored_row(update: &Document) -> Document {
+    let mut row = doc! { "record": "com.github", "social_id": "0x0123" };
+    row.extend(update.get_document("$set").unwrap().clone());
+    row
+}
+
+#[cfg(test)]
+mod persistence {
+    use super::*;
+
+    #[test]
+    fn test_fetched_names_are_stored_with_their_date() {
+        let row = stored_row(&name_update(Some("th0rgal"), date(NOW)));
+        assert_eq!(
+            parse_stored_name(&row),
+            Some(("th0rgal".to_string(), date(NOW)))
+        );
+        assert_eq!(row.get_datetime("checked_at").unwrap(), &date(NOW));
+    }
+
+    #[test]
+    fn test_failed_fetches_keep_the_stored_name() {
+        let update = name_update(None, date(NOW));
+        assert_eq!(update, doc! { "$set": { "checked_at": date(NOW) } });
+
+        let mut row = stored_row(&name_update(Some("th0rgal"), date(NOW - 1000)));
+        row.extend(update.get_document("$set").unwrap().clone());
+        assert_eq!(
+            parse_stored_name(&row),
+            Some(("th0rgal".to_string(), date(NOW - 1000)))
+        );
+    }
+
+    #[test]
+    fn test_rows_without_a_name() {
+        assert_eq!(parse_stored_name(&doc! { "checked_at": date(NOW) }), None);
+    }
+}
+
+#[cfg(test)]
+mod stale_fallback {
+    use super::*;
+
+    #[test]
+    fn test_fetched_name_is_preferred() {
+        let name = fetched_or_stale(Ok("new".to_string()), Some("old".to_string()));
+        assert_eq!(name.unwrap(), "new");
+    }
+
+    #[test]
+    fn test_stored_name_while_the_handler_fails() {
+        let name = fetched_or_stale(Err(anyhow!("rate limited")), Some("old".to_string()));
+        assert_eq!(name.unwrap(), "old");
+        assert!(fetched_or_stale(Err(anyhow!("rate limited")), None).is_err());
+    }
+}
+
+#[cfg(test)]
+mod background_refresh {
+    use super::*;
+
+    #[test]
+    fn test_rows_checked_before_the_expiry_are_refreshed() {
+        assert_eq!(
+            expired_filter(date(NOW)),
+            doc! { "checked_at": { "$lt": date(NOW) } }
+        );
+    }
+
+    #[test]
+    fn test_failed_refresh_postpones_the_row() {
+        // the row leaves the expired rows, it's retried after another ttl
+        let row = stored_row(&name_update(None, date(NOW)));
+        let expired_before = date(NOW - 86_400_000);
+        assert!(*row.get_datetime("checked_at").unwrap() >= expired_before);
+    }
+}
+
+#[cfg(test)]
+mod indexes {
+    use super::*;
+
+    #[test]
+    fn test_lookup_key_is_unique() {
+        let indexes = social_names_indexes();
+        let lookup = indexes
+            .iter()
+            .find(|index| index.keys == doc! { "record": 1, "social_id": 1 })
+            .unwrap();
+        assert_eq!(
+            lookup.options.as_ref().and_then(|options| options.unique),
+            Some(true)
+        );
+    }
+}
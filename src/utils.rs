@@ -1,27 +1,12 @@
 use ark_ff::{biginteger::BigInteger256, BigInteger};
-use axum::{
-    body::Body,
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    Router,
-};
+use axum::{body::Body, Router};
 use base64::{engine::general_purpose::STANDARD, Engine};
-use serde::Serialize;
 use serde_json::Value;
 use starknet::core::types::FieldElement;
 use std::{fmt::Write, str, sync::Arc};
 
 use crate::{config::Config, models::AppState};
 
-#[derive(Serialize)]
-pub struct ErrorMessage {
-    error: String,
-}
-
-pub fn get_error(error: String) -> Response {
-    (StatusCode::BAD_REQUEST, error).into_response()
-}
-
 pub fn extract_prefix_and_root(domain: String) -> (String, String) {
     let parts: Vec<&str> = domain.split('.').rev().collect();
 